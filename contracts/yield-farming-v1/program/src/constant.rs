@@ -1,7 +1,6 @@
-/// constants declaration file
+//! constants declaration file
 
 /// mode of mainnet-beta or devnet, in case of mainnet-beta - const DEVNET_MODE:bool = false;
-
 const DEVNET_MODE:bool = {
     #[cfg(feature = "devnet")]
     {
@@ -14,18 +13,48 @@ const DEVNET_MODE:bool = {
 };
 
 
-/// Farm additaional fee
-/// To create new farm without CRP token pairing, the creator must pay this additional farm fee as stable coin (USDC)
-/// If the creator doesn't pay farm fee, displays "Not Allowed" instead of "Stake" button
-/// So creator and farmers can't stake/unstake/harvest
+// Farm additaional fee
+// To create new farm without CRP token pairing, the creator must pay this additional farm fee as stable coin (USDC)
+// If the creator doesn't pay farm fee, displays "Not Allowed" instead of "Stake" button
+// So creator and farmers can't stake/unstake/harvest
 
 pub const VERSION:u8 = 2;
 pub const PREFIX:&str = "cropperfarm";
 
+/// seed of whitelisted fee token accounts
+pub const FEE_TOKEN_SEED:&str = "feetoken";
+
+/// seed of multisig authority which signs admin instructions of executed proposals
+pub const MULTISIG_SEED:&str = "multisig";
+
+/// seed of timelock account which stores queued program data change
+pub const TIMELOCK_SEED:&str = "timelock";
+
+/// queued program data change can't be executed earlier than this delay in seconds
+pub const MIN_TIMELOCK_DELAY:u64 = 86400;
+
+/// max harvest fee in basis points of harvested amount
+pub const MAX_HARVEST_FEE_BPS:u64 = 1000;
+pub const BPS_DENOMINATOR:u64 = 10000;
+
+/// seed of global emitter account which splits CRP emission across registered farms
+pub const EMITTER_SEED:&str = "emitter";
+
+/// number of leading accounts of SetAllocPoint instruction which are used to update allocation points
+pub const SET_ALLOC_POINT_ACCOUNTS:usize = 8;
+
+/// seeds of gauge voting accounts
+pub const GAUGE_CONTROLLER_SEED:&str = "gaugecontroller";
+pub const GAUGE_SEED:&str = "gauge";
+pub const VOTER_SEED:&str = "voter";
+pub const GAUGE_VOTE_SEED:&str = "gaugevote";
+
 /// initial super owner of this program. this owner can change program state
 pub const INITIAL_SUPER_OWNER:&str = if DEVNET_MODE {"4GJ3z4skEHJADz3MVeNYBg4YV8H27rBQey2YYdiPC8PA"} else {"AwtDEd9GThBNWNahvLZUok1BiRULNQ86VruXkYAckCtV"};
 
 pub const TOKEN_PROGRAM_ID:&str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID:&str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID:&str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
 pub const RENT_SYSVAR_ID:&str = "SysvarRent111111111111111111111111111111111";
 pub const CLOCK_SYSVAR_ID:&str = "SysvarC1ock11111111111111111111111111111111";
 pub const SYSTEM_PROGRAM_ID:&str = "11111111111111111111111111111111";
@@ -34,7 +63,9 @@ pub const SYSTEM_PROGRAM_ID:&str = "11111111111111111111111111111111";
 pub const CRP_MINT_ADDRESS:&str = if DEVNET_MODE {"GGaUYeET8HXK34H2D1ieh4YYQPhkWcfWBZ4rdp6iCZtG"} else {"DubwWZNWiNGMMeeQHPnMATNj77YZPZSAz2WVR5WjLJqz"};
 pub const USDC_MINT_ADDRESS:&str = if DEVNET_MODE {"6MBRfPbzejwVpADXq3LCotZetje3N16m5Yn7LCs2ffU4"} else {"EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"};
 pub const USDT_MINT_ADDRESS:&str = if DEVNET_MODE {"6La9ryWrDPByZViuQCizmo6aW98cK8DSL7angqmTFf9i"} else {"Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"};
+#[allow(clippy::if_same_then_else)]
 pub const SOL_MINT_ADDRESS:&str = if DEVNET_MODE {"So11111111111111111111111111111111111111112"} else {"So11111111111111111111111111111111111111112"};
+#[allow(clippy::if_same_then_else)]
 pub const ETH_MINT_ADDRESS:&str = if DEVNET_MODE {"2FPyTwcZLUg1MDrwsyoP4D6s1tM7hAkHYRjkNb5w6Pxk"} else {"2FPyTwcZLUg1MDrwsyoP4D6s1tM7hAkHYRjkNb5w6Pxk"};
 
 /// reward multipler constant
@@ -44,4 +75,48 @@ pub const REWARD_MULTIPLER:u64 = 1000000000;
 pub const JUMP_DEBT:u64 = 10_000_000_000_000_000_000;
 
 
-pub const REMOVE_REWARDS_FARM_ADDRESS:&str = if DEVNET_MODE {"Fv1ghuzaXvLmSFyMZoxbUBRJhDQp4ik4trak5c5rHuve"} else {"H9jkwKVS6YFCY87EuxF4P2z1yCJ4a4px1bpL1i49AGkB"};
\ No newline at end of file
+pub const REMOVE_REWARDS_FARM_ADDRESS:&str = if DEVNET_MODE {"Fv1ghuzaXvLmSFyMZoxbUBRJhDQp4ik4trak5c5rHuve"} else {"H9jkwKVS6YFCY87EuxF4P2z1yCJ4a4px1bpL1i49AGkB"};
+
+/// listing states of farms created without super owner
+/// farms created by super owner stay in LISTING_NONE
+pub const LISTING_NONE:u8 = 0;
+pub const LISTING_PENDING:u8 = 1;
+pub const LISTING_LISTED:u8 = 2;
+pub const LISTING_REJECTED:u8 = 3;
+
+/// maximum number of farms harvested by one HarvestMany instruction, bounded by compute budget
+pub const MAX_HARVEST_MANY_FARMS:usize = 8;
+
+/// number of accounts per farm in HarvestMany instruction
+pub const HARVEST_MANY_ACCOUNTS:usize = 9;
+
+/// number of stake checkpoints kept in a user info, oldest one is overwritten when full
+pub const STAKE_CHECKPOINTS:usize = 16;
+
+/// seed of sponsor accounts recording rewards added by each contributor
+pub const SPONSOR_SEED:&str = "sponsor";
+
+/// seed of audit log account recording privileged instructions
+pub const AUDIT_LOG_SEED:&str = "auditlog";
+
+/// number of entries kept in audit log, oldest one is overwritten when full
+pub const AUDIT_LOG_ENTRIES:usize = 32;
+
+/// number of fixed accounts of PayFarmFee instruction, audit log follows them
+pub const PAY_FARM_FEE_ACCOUNTS:usize = 8;
+
+/// kinds of privileged instructions recorded in audit log
+pub const AUDIT_SET_PROGRAM_DATA:u8 = 1;
+pub const AUDIT_REMOVE_REWARDS:u8 = 2;
+pub const AUDIT_PAY_FARM_FEE:u8 = 3;
+pub const AUDIT_SET_LISTING_OPTIONS:u8 = 4;
+pub const AUDIT_REJECT_FARM:u8 = 5;
+pub const AUDIT_SET_FEE_TOKEN:u8 = 6;
+pub const AUDIT_QUEUE_PROGRAM_DATA:u8 = 7;
+pub const AUDIT_CANCEL_PROGRAM_DATA:u8 = 8;
+pub const AUDIT_INITIALIZE_EMITTER:u8 = 9;
+pub const AUDIT_SET_EMITTER_RATE:u8 = 10;
+pub const AUDIT_SET_ALLOC_POINT:u8 = 11;
+pub const AUDIT_INITIALIZE_GAUGE_CONTROLLER:u8 = 12;
+pub const AUDIT_CREATE_GAUGE:u8 = 13;
+pub const AUDIT_EXECUTE_PROGRAM_DATA:u8 = 14;
@@ -1,5 +1,8 @@
 //! All error types for this program
 
+// FromPrimitive derive of num-derive 0.3 implements the trait inside a const block
+#![allow(non_local_definitions)]
+
 use num_derive::FromPrimitive;
 use solana_program::{decode_error::DecodeError, program_error::ProgramError};
 use thiserror::Error;
@@ -135,6 +138,186 @@ pub enum FarmError {
      #[error("Invalid Clock Sysvar Id")]
      InvalidClockSysvarId,
 
+     /// Account data is shorter than the current layout
+     #[error("Account is not extended")]
+     AccountNotExtended,
+
+     /// Receipt mint is already set for this farm
+     #[error("Receipt is already enabled")]
+     ReceiptAlreadyEnabled,
+
+     /// This farm doesn't issue receipt tokens
+     #[error("Receipt is not enabled")]
+     ReceiptNotEnabled,
+
+     /// Invalid receipt token mint
+     #[error("Invalid receipt mint")]
+     InvalidReceiptMint,
+
+     /// Too many farms in one instruction
+     #[error("Too many farms")]
+     TooManyFarms,
+
+     /// Unbonding period is not over yet
+     #[error("Still unbonding")]
+     StillUnbonding,
+
+     /// Nothing to claim
+     #[error("Nothing to claim")]
+     NothingToClaim,
+
+     /// Fee token is not whitelisted
+     #[error("Fee token is not whitelisted")]
+     FeeTokenNotWhitelisted,
+
+     /// Invalid AMM pool to price fee token
+     #[error("Invalid price pool")]
+     InvalidPricePool,
+
+     /// Paid fee value is out of tolerance band
+     #[error("Fee value out of tolerance")]
+     FeeOutOfTolerance,
+
+     /// Farm is waiting for review
+     #[error("Farm is in review")]
+     InReview,
+
+     /// Farm is not waiting for review
+     #[error("Farm is not in review")]
+     NotInReview,
+
+     /// Review window is over
+     #[error("Review window closed")]
+     ReviewWindowClosed,
+
+     /// Farm was rejected by super owner
+     #[error("Farm rejected")]
+     FarmRejected,
+
+     /// Farm is not ended yet
+     #[error("Farm not ended")]
+     FarmNotEnded,
+
+     /// Farm still has staked lp
+     #[error("Farm not empty")]
+     FarmNotEmpty,
+
+     /// Invalid multisig account
+     #[error("Invalid multisig")]
+     InvalidMultisig,
+
+     /// Signer is not one of multisig signers
+     #[error("Not multisig signer")]
+     NotMultisigSigner,
+
+     /// Proposal doesn't have enough approvals
+     #[error("Not enough approvals")]
+     NotEnoughApprovals,
+
+     /// Proposal was executed already
+     #[error("Proposal executed")]
+     ProposalExecuted,
+
+     /// Parameter change has to be queued in timelock
+     #[error("Timelock required")]
+     TimelockRequired,
+
+     /// Another change is queued already
+     #[error("Already queued")]
+     AlreadyQueued,
+
+     /// No change is queued
+     #[error("Nothing queued")]
+     NothingQueued,
+
+     /// ETA is earlier than minimum delay
+     #[error("Invalid ETA")]
+     InvalidEta,
+
+     /// Queued change can't be executed before ETA
+     #[error("Timelock not ready")]
+     TimelockNotReady,
+
+     /// Stake can't be migrated between given farms
+     #[error("Migration not allowed")]
+     MigrationNotAllowed,
+
+     /// Invalid emitter account
+     #[error("Invalid emitter")]
+     InvalidEmitter,
+
+     /// Farm is registered to emitter
+     #[error("Farm registered")]
+     FarmRegistered,
+
+     /// Invalid lock duration
+     #[error("Invalid lock duration")]
+     InvalidLockDuration,
+
+     /// Voting power is not enough
+     #[error("Not enough voting power")]
+     NotEnoughVotingPower,
+
+     /// Locked tokens can't be withdrawn yet
+     #[error("Still locked")]
+     StillLocked,
+
+     /// Gauge was checkpointed in this epoch
+     #[error("Epoch not ended")]
+     EpochNotEnded,
+
+     /// Farm doesn't boost rewards
+     #[error("Not boosted")]
+     NotBoosted,
+
+     /// Snapshot time is not in the past
+     #[error("Snapshot not finalized")]
+     SnapshotNotFinalized,
+
+     /// Checkpoint at snapshot time was overwritten
+     #[error("Checkpoint overwritten")]
+     CheckpointOverwritten,
+
+     /// Sponsors have to be refunded before closing farm
+     #[error("Sponsors not refunded")]
+     SponsorsNotRefunded,
+
+     /// Sponsor was refunded already
+     #[error("Already refunded")]
+     AlreadyRefunded,
+
+     /// New vault received different amount
+     #[error("Vault amount mismatch")]
+     VaultAmountMismatch,
+
+     /// Loyalty multiplier is less than 1
+     #[error("Invalid multiplier")]
+     InvalidMultiplier,
+
+     /// Fee denominator is zero
+     #[error("Zero fee denominator")]
+     ZeroFeeDenominator,
+
+     /// Harvest fee is more than MAX_HARVEST_FEE_BPS
+     #[error("Harvest fee too high")]
+     HarvestFeeTooHigh,
+
+     /// Fee owner is default pubkey
+     #[error("Fee owner not set")]
+     FeeOwnerNotSet,
+
+     /// AMM program account is not an executable program
+     #[error("AMM program not executable")]
+     AmmProgramNotExecutable,
+
+     /// Reference price of fee token is not set or deviation is out of bounds
+     #[error("Invalid reference price")]
+     InvalidReferencePrice,
+
+     /// AMM pool price deviates from reference price of fee token
+     #[error("Price out of reference")]
+     PriceOutOfReference,
+
 }
 impl From<FarmError> for ProgramError {
     fn from(e: FarmError) -> Self {
@@ -5,12 +5,19 @@
 #![allow(clippy::too_many_arguments)]
 
 use {
+    crate::{
+        constant::{ASSOCIATED_TOKEN_PROGRAM_ID, AUDIT_LOG_SEED, PAY_FARM_FEE_ACCOUNTS, PREFIX},
+        state::{AuditEntry, AuditLog, ProposalAccount, UserInfo},
+    },
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     solana_program::{
         instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
         pubkey::Pubkey,
+        system_program,
         sysvar
     },
+    std::str::FromStr,
 };
 
 /// Instructions supported by the FarmPool program.
@@ -18,18 +25,20 @@ use {
 #[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
 pub enum FarmInstruction {
     ///   Set program data
+    ///   After initialization, amm program id and fees are changed by `QueueProgramData`
     ///   [w] - writable, [s] - signer
     /// 
+    ///   Fee owner must be set, harvest fee denominator must not be zero
+    ///   and harvest fee can't be more than MAX_HARVEST_FEE_BPS
+    /// 
     ///   0. `[w]` program account.
     ///   1. `[s]` super owner of this program
-    ///   2. `[]` new super owner
-    ///   3. `[]` fee owner
-    ///   4. `[]` allowed creator
-    ///   5. `[]` AMM program id
-    ///   6. `[]` farm fee
-    ///   7. `[]` harvest fee numerator
-    ///   8. `[]` harvest fee denominator
-    ///   9. `[]` program id
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    ///   4. `[]` AMM program, must be executable
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
     SetProgramData {
         #[allow(dead_code)]
         super_owner: Pubkey,
@@ -59,19 +68,31 @@ pub enum FarmInstruction {
     /// 
     ///   0. `[w]` New FarmPool account to create.
     ///   1. `[]` authority to initialize this farm pool account
-    ///   2. `[s]` Creator/Manager of this farm
+    ///   2. `[s]` Creator/Manager of this farm, anyone can create a farm by escrowing farm fee
     ///   3. `[w]` LP token account of this farm to store lp token
     ///   4. `[w]` reward token account of this farm to store rewards for the farmers
-    ///             Creator has to transfer/deposit his reward token to this account.
-    ///             only support spl tokens
+    ///      Creator has to transfer/deposit his reward token to this account.
+    ///      supports spl-token and Token-2022 tokens, owned by same program with its mint
+    ///      For native SOL rewards, pass farm authority with SOL mint, rewards are held as its lamports.
+    ///      Then reward token accounts are wallets and reward token program is system program.
+    ///      Farms waiting for review can't pay native SOL rewards
     ///   5. `[]` Pool token mint address
     ///   6. `[]` Reward token mint address
     ///   7. `[]` Amm Id
     ///   8. `[]` farm program data id
     ///   9. `[]` nonce
-    ///   10.'[]' start timestamp. this reflects that the farm starts at this time
-    ///   11.'[]' end timestamp. this reflects that the farm ends at this time
+    ///   10. `[]` start timestamp. this reflects that the farm starts at this time
+    ///   11. `[]` end timestamp. this reflects that the farm ends at this time
     ///   12. `[]` program id
+    ///
+    ///   If creator is not super owner, these accounts follow and the farm waits for review
+    ///   - `[w]` Creator USDC token account to pay farm fee
+    ///   - `[w]` escrow USDC token account owned by farm authority
+    ///   - `[]` USDC mint
+    ///   - `[]` Token program id of USDC
+    ///   - `[w]` Creator reward token account to deposit minimum reward
+    ///   - `[]` Reward token program id
+    ///   - `[]` clock sysvar
     InitializeFarm {
         #[allow(dead_code)]
         /// nonce
@@ -103,13 +124,46 @@ pub enum FarmInstruction {
     ///   10. `[]` farm program data id
     ///   11. `[]` Token program id
     ///   12. `[]` clock sysvar
-    ///   13. `[]` amount
-    ///   14. `[]` program id
-    Deposit(u64),
+    ///   13. `[]` rent sysvar
+    ///   14. `[]` system program id
+    ///   15. `[]` Reward token mint
+    ///   16. `[]` Reward token program id
+    ///   17. `[]` associated token account program id
+    ///   18. `[]` fee owner wallet
+    ///
+    ///   Breaking change: reward token mint (15) and reward token program id (16) were added for Token-2022 farms,
+    ///   clients built for the older layout ending at system program id (14) have to pass them
+    ///
+    ///   User reward token account and fee reward ata are created if they don't exist, depositor pays rent
+    ///
+    ///   If this farm issues receipt tokens, these accounts follow
+    ///   - `[w]` Receipt token mint
+    ///   - `[w]` User receipt token account to mint receipt tokens to
+    ///
+    ///   If this farm is registered to the emitter, these accounts follow
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    ///
+    ///   If this farm boosts rewards, these accounts follow
+    ///   - `[]` Voter of the user, may be empty
+    ///   - `[]` Gauge controller
+    ///
+    ///   User info of the position is created if it doesn't exist,
+    ///   it has to be the PDA of [PREFIX, farm, wallet] for position 0, [PREFIX, farm, wallet, position index] otherwise
+    Deposit {
+        #[allow(dead_code)]
+        amount: u64,
+
+        #[allow(dead_code)]
+        position_index: u64,
+    },
 
     ///   Unstake LP tokens from this farm pool
     ///   Before unstake lp tokens, "harvest" works
     /// 
+    ///   User info created with an older layout is extended, withdrawer pays rent
+    /// 
     ///   0. `[w]` FarmPool to withdraw to.
     ///   1. `[]` authority of this farm pool
     ///   2. `[s]` Withdrawer
@@ -123,27 +177,63 @@ pub enum FarmInstruction {
     ///   10. `[]` farm program data id
     ///   11. `[]` Token program id
     ///   12. `[]` clock sysvar
-    ///   13. `[]` amount
-    ///   14. `[]` program id
-    Withdraw(u64),
+    ///   13. `[]` Reward token mint
+    ///   14. `[]` Reward token program id
+    ///   15. `[]` rent sysvar
+    ///   16. `[]` system program id
+    ///   17. `[]` associated token account program id
+    ///   18. `[]` fee owner wallet
+    ///
+    ///   Breaking change: reward token mint (13) and reward token program id (14) were added for Token-2022 farms,
+    ///   clients built for the older layout ending at clock sysvar (12) have to pass them
+    ///
+    ///   User reward token account and fee reward ata are created if they don't exist, withdrawer pays rent
+    ///
+    ///   If this farm issues receipt tokens, these accounts follow
+    ///   - `[w]` Receipt token mint
+    ///   - `[w]` User receipt token account to burn receipt tokens from
+    ///
+    ///   If withdrawn before minimum stake duration with early withdraw fee, this account follows
+    ///   - `[w]` fee owner's LP token account
+    ///
+    ///   If this farm is registered to the emitter, these accounts follow
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    ///
+    ///   If this farm boosts rewards, these accounts follow
+    ///   - `[]` Voter of the user, may be empty
+    ///   - `[]` Gauge controller
+    ///
+    ///   If this farm has unbonding period, withdrawn LP is claimed later by `ClaimUnbonded`
+    Withdraw(#[allow(dead_code)] u64),
 
     ///   Creator can add reward to his farm 
+    ///   If open funding is enabled, anyone can add reward and is recorded as sponsor
     /// 
     ///   0. `[w]` FarmPool to add reward to.
     ///   1. `[]` authority of this farm pool
-    ///   2. `[s]` creator
+    ///   2. `[s]` creator or sponsor
     ///   3. `[]` User reward token account
     ///   4. `[]` Pool reward token account
-    ///   5. `[]` Pool lp token mint
-    ///   6. `[]` farm program data id
-    ///   7. `[]` token program id
-    ///   8. `[]` clock sysvar
-    ///   9. `[]` amount
-    ///   10. `[]` program id
-    AddReward(u64),
+    ///   5. `[]` Pool lp token account
+    ///   6. `[]` Pool lp token mint
+    ///   7. `[]` farm program data id
+    ///   8. `[]` reward token program id
+    ///   9. `[]` clock sysvar
+    ///   10. `[]` Reward token mint
+    ///
+    ///   Breaking change: reward token mint (10) was added for Token-2022 farms
+    ///
+    ///   If open funding is enabled, these accounts follow
+    ///   - `[w]` Sponsor account of the contributor, created if empty
+    ///   - `[]` rent sysvar
+    ///   - `[]` system program id
+    AddReward(#[allow(dead_code)] u64),
     
     ///   Creator has to pay farm fee (if not CRP token pairing)
     ///   So this farm can be allowed to stake/unstake/harvest
+    ///   Farm fee is paid in USDC or a whitelisted token priced by its AMM pool
     /// 
     ///   0. `[w]` FarmPool to pay farm fee.
     ///   1. `[]` authority of this farm pool
@@ -151,12 +241,821 @@ pub enum FarmInstruction {
     ///   3. `[]` User USDC token account
     ///   4. `[]` fee usdc ata
     ///   5. `[]` farm program data account
-    ///   6. `[]` token program id
-    ///   7. `[]` amount
-    ///   8. `[]` program id
-    PayFarmFee(u64),
+    ///   6. `[]` token program id of fee token
+    ///   7. `[]` fee token mint
+    ///
+    ///   Breaking change: fee token mint (7) was added for Token-2022 fee tokens
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    ///
+    ///   If fee token is not USDC, these accounts follow
+    ///   - `[]` whitelisted fee token account
+    ///   - `[]` AMM pool pairing fee token with USDC
+    ///   - `[]` AMM pool's token a account
+    ///   - `[]` AMM pool's token b account
+    PayFarmFee(#[allow(dead_code)] u64),
 
     RemoveRewards,
+
+    ///   Farm owner enables receipt tokens for this farm
+    ///   Receipt mint must be created with the farm authority as mint and freeze authority,
+    ///   zero supply and same decimals as lp token
+    ///   Receipt token accounts are kept frozen, receipts move only by `TransferReceipt`
+    ///   So receipts are non-transferable outside this program and can't be used by other protocols, e.g. as collateral
+    ///   They record staked balance of the holder, reward debt follows them by `TransferReceipt`
+    ///   Only allowed before anyone stakes
+    /// 
+    ///   0. `[w]` FarmPool to enable receipt.
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` farm owner, pays rent if farm account is extended
+    ///   3. `[]` Receipt token mint
+    ///   4. `[]` Pool LP token account
+    ///   5. `[]` Pool LP token mint
+    ///   6. `[]` rent sysvar
+    ///   7. `[]` system program id
+    EnableReceipt,
+
+    ///   Transfer receipt tokens to another wallet
+    ///   Deposit balance moves together with receipt tokens and
+    ///   pending rewards of both users are kept as reward credit, paid by their next harvest
+    ///   Both receipt token accounts are thawed only for this transfer and frozen again
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` Sender, pays rent if receiver's user info is created
+    ///   3. `[w]` Sender Farming Information Account
+    ///   4. `[w]` Sender receipt token account
+    ///   5. `[]` Receiver wallet
+    ///   6. `[w]` Receiver Farming Information Account
+    ///   7. `[w]` Receiver receipt token account
+    ///   8. `[]` Receipt token mint
+    ///   9. `[]` Pool LP token account
+    ///   10. `[w]` Pool reward token account
+    ///   11. `[]` Token program id
+    ///   12. `[]` clock sysvar
+    ///   13. `[]` rent sysvar
+    ///   14. `[]` system program id
+    ///
+    ///   If this farm is registered to the emitter, these accounts follow
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    ///   - `[]` Reward token mint
+    ///   - `[]` Reward token program id
+    ///
+    ///   If this farm boosts rewards, these accounts follow
+    ///   - `[]` Voter of the sender, may be empty
+    ///   - `[]` Voter of the receiver, may be empty
+    ///   - `[]` Gauge controller
+    ///
+    ///   Receiver's user info of the given position is created if it doesn't exist
+    TransferReceipt {
+        #[allow(dead_code)]
+        amount: u64,
+
+        #[allow(dead_code)]
+        receiver_position_index: u64,
+    },
+
+    ///   Harvest pending rewards from several farms in one instruction
+    ///   Farms which fail validation are skipped
+    ///   Per-farm results are returned as a list of `HarvestResult` by return data
+    ///   Results only report validation failures, failed token transfers (CPI) abort the whole batch
+    ///   User infos created with an older layout are extended, harvester pays rent
+    ///   User reward token accounts and fee reward atas are created if they don't exist, harvester pays rent
+    /// 
+    ///   0. `[s]` Harvester
+    ///   1. `[]` farm program data id
+    ///   2. `[]` clock sysvar
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    ///   5. `[]` associated token account program id
+    ///   6. `[]` fee owner wallet
+    ///
+    ///   Following accounts are repeated for each farm, up to `MAX_HARVEST_MANY_FARMS` farms
+    ///   - `[w]` FarmPool
+    ///   - `[]` authority of this farm pool
+    ///   - `[w]` User Farming Information Account
+    ///   - `[]` Pool LP token account
+    ///   - `[w]` Pool reward token account
+    ///   - `[w]` User reward token account
+    ///   - `[w]` fee reward ata account
+    ///   - `[]` Reward token mint
+    ///   - `[]` Reward token program id
+    ///
+    ///   If the farm is registered to the emitter, these accounts follow its accounts
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    ///
+    ///   If the farm boosts rewards, these accounts follow its accounts
+    ///   - `[]` Voter of the harvester, may be empty
+    ///   - `[]` Gauge controller
+    HarvestMany,
+
+    ///   View pending rewards of a user and reward state of a farm at current time
+    ///   Nothing is written, the result is returned as `PendingRewardsView` by return data
+    ///   so clients can get it by simulating this instruction
+    /// 
+    ///   0. `[]` FarmPool
+    ///   1. `[]` User Farming Information Account, may be empty
+    ///   2. `[]` Pool LP token account
+    ///   3. `[]` Pool reward token account
+    ///   4. `[]` clock sysvar
+    ///
+    ///   If this farm is registered to the emitter, these accounts may follow to include its emission
+    ///   - `[]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[]` Emitter reward vault
+    ViewPendingRewards,
+
+    ///   Extend farm account created with an older layout to the current size
+    ///   Anyone can call this and pay rent for the extended space
+    ///   Deposit extends farm account automatically
+    /// 
+    ///   0. `[w]` FarmPool to extend
+    ///   1. `[s]` payer
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    ///   4. `[w]` optional User Farming Information Accounts of this farm to extend, any number of them
+    ExtendFarm,
+
+    ///   Farm owner sets lockup options of his farm
+    ///   Withdrawing before minimum stake duration pays early withdraw fee in LP token to fee owner
+    ///   If unbonding period is not zero, withdrawn LP can be claimed after this period
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    SetLockupOptions {
+        #[allow(dead_code)]
+        min_stake_duration: u64,
+
+        #[allow(dead_code)]
+        early_withdraw_fee_numerator: u64,
+
+        #[allow(dead_code)]
+        early_withdraw_fee_denominator: u64,
+
+        #[allow(dead_code)]
+        unbonding_period: u64,
+    },
+
+    ///   Claim withdrawn LP tokens after unbonding period
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` Withdrawer
+    ///   3. `[w]` User Farming Information Account
+    ///   4. `[w]` User LP token account
+    ///   5. `[w]` Pool LP token account
+    ///   6. `[]` Token program id
+    ///   7. `[]` clock sysvar
+    ///   8. `[]` Pool LP token mint
+    ClaimUnbonded,
+
+    ///   Super owner whitelists a token to pay farm fee
+    ///   USDC value of the payment is computed from reserves of the AMM pool pairing it with USDC
+    ///   Pool price must be within max deviation of the reference price set here
+    ///   Paid value must be in the tolerance band around farm fee after discount
+    /// 
+    ///   0. `[w]` fee token account, PDA of [PREFIX, FEE_TOKEN_SEED, mint]
+    ///   1. `[s]` super owner, pays rent if fee token account is created or extended
+    ///   2. `[]` farm program data id
+    ///   3. `[]` fee token mint
+    ///   4. `[]` AMM pool pairing fee token with USDC
+    ///   5. `[]` rent sysvar
+    ///   6. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    SetFeeToken {
+        #[allow(dead_code)]
+        is_enabled: u8,
+
+        #[allow(dead_code)]
+        tolerance_numerator: u64,
+
+        #[allow(dead_code)]
+        tolerance_denominator: u64,
+
+        #[allow(dead_code)]
+        discount_numerator: u64,
+
+        #[allow(dead_code)]
+        discount_denominator: u64,
+
+        #[allow(dead_code)]
+        reference_usdc_amount: u64,
+
+        #[allow(dead_code)]
+        reference_token_amount: u64,
+
+        #[allow(dead_code)]
+        max_price_deviation_bps: u64,
+    },
+
+    ///   Super owner sets review window and minimum reward deposit of farms created without him
+    /// 
+    ///   0. `[w]` farm program data id
+    ///   1. `[s]` super owner, pays rent if program data account is extended
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    SetListingOptions {
+        #[allow(dead_code)]
+        review_period: u64,
+
+        #[allow(dead_code)]
+        min_reward_deposit: u64,
+    },
+
+    ///   Super owner rejects a farm in review window
+    ///   Escrowed farm fee and rewards are refunded to the creator
+    /// 
+    ///   0. `[w]` FarmPool to reject
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` super owner
+    ///   3. `[]` farm program data id
+    ///   4. `[w]` escrow USDC token account
+    ///   5. `[w]` Creator USDC token account
+    ///   6. `[]` USDC mint
+    ///   7. `[]` Token program id of USDC
+    ///   8. `[w]` Pool reward token account
+    ///   9. `[w]` Creator reward token account
+    ///   10. `[]` Reward token mint
+    ///   11. `[]` Reward token program id
+    ///   12. `[]` clock sysvar
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    RejectFarm,
+
+    ///   Release escrowed farm fee to fee owner after review window and allow the farm
+    ///   Anyone can call this
+    /// 
+    ///   0. `[w]` FarmPool to list
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[]` farm program data id
+    ///   3. `[w]` escrow USDC token account
+    ///   4. `[w]` fee usdc ata
+    ///   5. `[]` USDC mint
+    ///   6. `[]` Token program id of USDC
+    ///   7. `[]` clock sysvar
+    FinalizeListing,
+
+    ///   Farm owner closes an ended farm without staked LP
+    ///   Remaining rewards are swept to the owner, token accounts and farm account are closed
+    ///   and their rent is refunded to the owner
+    ///   For native SOL rewards, pool reward account is farm authority and its lamports are moved to the owner
+    /// 
+    ///   0. `[w]` FarmPool to close
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[ws]` farm owner
+    ///   3. `[w]` Pool LP token account
+    ///   4. `[w]` Pool reward token account
+    ///   5. `[w]` Owner reward token account
+    ///   6. `[]` Reward token mint
+    ///   7. `[]` LP token program id
+    ///   8. `[]` Reward token program id
+    ///   9. `[]` clock sysvar
+    CloseFarm,
+
+    ///   Initializes M-of-N multisig account, same rules as spl-token multisig
+    ///   Super owner can be set to the multisig authority, PDA of [PREFIX, MULTISIG_SEED, multisig],
+    ///   then admin instructions are executed by proposals
+    /// 
+    ///   0. `[w]` multisig account owned by this program, uninitialized with `Multisig::LEN` bytes
+    ///   1. `[]` rent sysvar
+    ///   2. `[]` N signer accounts, 1 <= N <= 11
+    CreateMultisig {
+        #[allow(dead_code)]
+        /// number of approvals required to execute a proposal
+        m: u8,
+    },
+
+    ///   Multisig signer proposes an instruction of this program signed by multisig authority
+    ///   Proposer's approval is recorded
+    /// 
+    ///   0. `[w]` proposal account owned by this program, zero data large enough for the instruction
+    ///   1. `[]` multisig account
+    ///   2. `[s]` proposer
+    CreateProposal {
+        #[allow(dead_code)]
+        instruction_accounts: Vec<ProposalAccount>,
+
+        #[allow(dead_code)]
+        instruction_data: Vec<u8>,
+    },
+
+    ///   Multisig signer approves a proposal
+    /// 
+    ///   0. `[w]` proposal account
+    ///   1. `[]` multisig account
+    ///   2. `[s]` approver
+    ApproveProposal,
+
+    ///   Execute a proposal with enough approvals
+    ///   Anyone can call this
+    /// 
+    ///   0. `[w]` proposal account
+    ///   1. `[]` multisig account
+    ///   2. `[]` multisig authority
+    ///   3. accounts of the proposed instruction in order
+    ///   4. `[]` this program id, after all accounts of the proposed instruction
+    ExecuteProposal,
+
+    ///   Super owner queues new amm program id and fees
+    ///   ETA must be at least minimum delay ahead, only one change can be queued
+    /// 
+    ///   0. `[w]` farm program data id
+    ///   1. `[w]` timelock account, PDA of [PREFIX, TIMELOCK_SEED, program id]
+    ///   2. `[s]` super owner, pays rent of timelock account and extended program data
+    ///   3. `[]` clock sysvar
+    ///   4. `[]` rent sysvar
+    ///   5. `[]` system program id
+    ///   6. `[]` new amm program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    QueueProgramData {
+        #[allow(dead_code)]
+        amm_program_id: Pubkey,
+
+        #[allow(dead_code)]
+        farm_fee: u64,
+
+        #[allow(dead_code)]
+        harvest_fee_numerator: u64,
+
+        #[allow(dead_code)]
+        harvest_fee_denominator: u64,
+
+        #[allow(dead_code)]
+        min_delay: u64,
+
+        #[allow(dead_code)]
+        eta: u64,
+    },
+
+    ///   Apply queued change after ETA
+    ///   Anyone can call this
+    /// 
+    ///   0. `[w]` farm program data id
+    ///   1. `[w]` timelock account
+    ///   2. `[]` clock sysvar
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    ExecuteProgramData,
+
+    ///   Super owner cancels queued change
+    /// 
+    ///   0. `[]` farm program data id
+    ///   1. `[w]` timelock account
+    ///   2. `[s]` super owner
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    CancelProgramData,
+
+    ///   Move staked LP tokens from source farm to destination farm with the same LP mint
+    ///   Pending rewards of both farms are harvested, LP tokens are moved vault to vault
+    ///   Fails if early withdraw fee applies or destination unbonding period is shorter
+    ///   LP tokens are moved into the given position of the user in destination farm
+    /// 
+    ///   0. `[w]` Source FarmPool
+    ///   1. `[]` authority of source farm pool
+    ///   2. `[w]` User Farming Information Account of source farm
+    ///   3. `[w]` Source pool LP token account
+    ///   4. `[w]` Source pool reward token account
+    ///   5. `[w]` User reward token account of source farm
+    ///   6. `[w]` fee reward ata account of source farm
+    ///   7. `[]` Source reward token mint
+    ///   8. `[]` Source reward token program id
+    ///   9. `[w]` Destination FarmPool
+    ///   10. `[]` authority of destination farm pool
+    ///   11. `[w]` User Farming Information Account of destination position, created if empty
+    ///   12. `[w]` Destination pool LP token account
+    ///   13. `[w]` Destination pool reward token account
+    ///   14. `[w]` User reward token account of destination farm
+    ///   15. `[w]` fee reward ata account of destination farm
+    ///   16. `[]` Destination reward token mint
+    ///   17. `[]` Destination reward token program id
+    ///   18. `[s]` User wallet
+    ///   19. `[]` Pool LP token mint
+    ///   20. `[]` Token program id
+    ///   21. `[]` farm program data id
+    ///   22. `[]` clock sysvar
+    ///   23. `[]` rent sysvar
+    ///   24. `[]` system program id
+    ///   25. `[]` associated token account program id
+    ///   26. `[]` fee owner wallet
+    ///
+    ///   User reward token accounts and fee reward atas of both farms are created if they don't exist
+    ///
+    ///   If source farm issues receipt tokens, these accounts follow
+    ///   - `[w]` Source receipt token mint
+    ///   - `[w]` User receipt token account to burn receipt tokens from
+    ///
+    ///   If destination farm issues receipt tokens, these accounts follow
+    ///   - `[w]` Destination receipt token mint
+    ///   - `[w]` User receipt token account to mint receipt tokens to
+    ///
+    ///   If source farm and then destination farm are registered to the emitter, these accounts follow for each
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    ///
+    ///   If source farm and then destination farm boost rewards, these accounts follow for each
+    ///   - `[]` Voter of the user, may be empty
+    ///   - `[]` Gauge controller
+    MigrateStake {
+        #[allow(dead_code)]
+        amount: u64,
+
+        #[allow(dead_code)]
+        destination_position_index: u64,
+    },
+
+    ///   Super owner creates the global emitter of CRP
+    ///   Emitter vault has to be a CRP token account owned by emitter authority
+    /// 
+    ///   0. `[w]` Emitter, PDA of [PREFIX, EMITTER_SEED, program id]
+    ///   1. `[]` authority of emitter
+    ///   2. `[]` Emitter reward vault
+    ///   3. `[]` Reward token mint, CRP
+    ///   4. `[]` farm program data id
+    ///   5. `[s]` super owner, pays rent of emitter
+    ///   6. `[]` Token program id
+    ///   7. `[]` clock sysvar
+    ///   8. `[]` rent sysvar
+    ///   9. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    InitializeEmitter {
+        #[allow(dead_code)]
+        reward_per_second: u64,
+    },
+
+    ///   Super owner changes emission rate
+    ///   Emission so far is accumulated with the old rate
+    /// 
+    ///   0. `[w]` Emitter
+    ///   1. `[]` farm program data id
+    ///   2. `[s]` super owner
+    ///   3. `[]` clock sysvar
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    SetEmitterRate {
+        #[allow(dead_code)]
+        reward_per_second: u64,
+    },
+
+    ///   Super owner sets allocation points of a farm, zero unregisters the farm
+    ///   Farm pulls its emission so far before allocation changes
+    /// 
+    ///   0. `[w]` Emitter
+    ///   1. `[]` authority of emitter
+    ///   2. `[w]` Emitter reward vault
+    ///   3. `[w]` FarmPool
+    ///   4. `[]` Pool LP token account
+    ///   5. `[w]` Pool reward token account
+    ///   6. `[]` Reward token mint
+    ///   7. `[]` Reward token program id
+    ///   8. `[]` farm program data id
+    ///   9. `[s]` super owner, pays rent if farm account is extended
+    ///   10. `[]` clock sysvar
+    ///   11. `[]` rent sysvar
+    ///   12. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    SetAllocPoint {
+        #[allow(dead_code)]
+        alloc_point: u64,
+    },
+
+    ///   Super owner creates gauge controller
+    ///   Lock vault has to be a CRP token account owned by gauge controller authority
+    /// 
+    ///   0. `[w]` Gauge controller, PDA of [PREFIX, GAUGE_CONTROLLER_SEED, program id]
+    ///   1. `[]` authority of gauge controller
+    ///   2. `[]` Lock vault
+    ///   3. `[]` farm program data id
+    ///   4. `[s]` super owner, pays rent of gauge controller
+    ///   5. `[]` Token program id
+    ///   6. `[]` clock sysvar
+    ///   7. `[]` rent sysvar
+    ///   8. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    InitializeGaugeController {
+        #[allow(dead_code)]
+        epoch_duration: u64,
+
+        #[allow(dead_code)]
+        max_lock_duration: u64,
+    },
+
+    ///   Super owner creates gauge of a farm which rewards emitter's token
+    /// 
+    ///   0. `[w]` Gauge, PDA of [PREFIX, GAUGE_SEED, farm id]
+    ///   1. `[]` FarmPool
+    ///   2. `[]` Gauge controller
+    ///   3. `[]` Emitter
+    ///   4. `[]` farm program data id
+    ///   5. `[s]` super owner, pays rent of gauge
+    ///   6. `[]` clock sysvar
+    ///   7. `[]` rent sysvar
+    ///   8. `[]` system program id
+    ///
+    ///   If audit log is created, this account follows
+    ///   - `[w]` Audit log
+    CreateGauge,
+
+    ///   Lock CRP to get voting power
+    ///   Lock end is extended to `duration` from now if it is later than current lock end
+    /// 
+    ///   0. `[w]` Voter, PDA of [PREFIX, VOTER_SEED, wallet]
+    ///   1. `[w]` Gauge controller
+    ///   2. `[w]` Lock vault
+    ///   3. `[w]` User CRP token account
+    ///   4. `[]` CRP mint
+    ///   5. `[s]` Voter wallet, pays rent of voter account
+    ///   6. `[]` Token program id
+    ///   7. `[]` clock sysvar
+    ///   8. `[]` rent sysvar
+    ///   9. `[]` system program id
+    LockVotingPower {
+        #[allow(dead_code)]
+        amount: u64,
+
+        #[allow(dead_code)]
+        duration: u64,
+    },
+
+    ///   Set voting power given to a gauge, zero removes the vote
+    ///   Changed votes are counted by checkpoints from the next epoch
+    /// 
+    ///   0. `[w]` Gauge vote, PDA of [PREFIX, GAUGE_VOTE_SEED, wallet, farm id]
+    ///   1. `[w]` Voter
+    ///   2. `[w]` Gauge
+    ///   3. `[w]` Gauge controller
+    ///   4. `[s]` Voter wallet, pays rent of gauge vote account
+    ///   5. `[]` clock sysvar
+    ///   6. `[]` rent sysvar
+    ///   7. `[]` system program id
+    VoteGauge {
+        #[allow(dead_code)]
+        power: u64,
+    },
+
+    ///   Withdraw locked CRP after lock end, all votes have to be removed first
+    /// 
+    ///   0. `[w]` Voter
+    ///   1. `[w]` Gauge controller
+    ///   2. `[]` authority of gauge controller
+    ///   3. `[w]` Lock vault
+    ///   4. `[w]` User CRP token account
+    ///   5. `[]` CRP mint
+    ///   6. `[s]` Voter wallet
+    ///   7. `[]` Token program id
+    ///   8. `[]` clock sysvar
+    UnlockVotingPower,
+
+    ///   Turn gauge weight into allocation points of the farm, once per epoch
+    ///   Weight is taken at the start of current epoch, votes changed in this epoch are counted from the next one
+    ///   Anyone can call this
+    /// 
+    ///   0. `[w]` Emitter
+    ///   1. `[]` authority of emitter
+    ///   2. `[w]` Emitter reward vault
+    ///   3. `[w]` FarmPool
+    ///   4. `[]` Pool LP token account
+    ///   5. `[w]` Pool reward token account
+    ///   6. `[]` Reward token mint
+    ///   7. `[]` Reward token program id
+    ///   8. `[w]` Gauge
+    ///   9. `[]` Gauge controller
+    ///   10. `[s]` payer, pays rent if farm account is extended
+    ///   11. `[]` clock sysvar
+    ///   12. `[]` rent sysvar
+    ///   13. `[]` system program id
+    CheckpointGauge,
+
+    ///   Farm owner sets boost options before anyone stakes
+    ///   Effective stake = min(deposit, base * deposit + (1 - base) * total deposit * locked CRP / total locked CRP)
+    ///   base = boost_base_numerator / boost_base_denominator, zero denominator disables boost
+    ///   Locked CRP boosts only position 0 of each wallet
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` Pool LP token account
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    SetBoostOptions {
+        #[allow(dead_code)]
+        boost_base_numerator: u64,
+
+        #[allow(dead_code)]
+        boost_base_denominator: u64,
+    },
+
+    ///   Refresh user's effective stake in a boosted or loyalty farm with current CRP lock and staking duration
+    ///   Pending rewards are harvested with old effective stake first
+    ///   Anyone can call this, `TransferReceipt` doesn't read locks and needs this after it
+    ///   Loyalty multiplier only grows in effective stake when it is refreshed
+    ///   Voter and gauge controller are ignored if the farm doesn't boost rewards
+    ///   User info created with an older layout has to be extended by `ExtendFarm` first
+    ///   User reward token account and fee reward ata have to exist, nobody pays rent here
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[w]` User Farming Information Account
+    ///   3. `[]` Pool LP token account
+    ///   4. `[w]` Pool reward token account
+    ///   5. `[w]` User reward token account
+    ///   6. `[w]` fee reward ata account
+    ///   7. `[]` Reward token mint
+    ///   8. `[]` Reward token program id
+    ///   9. `[]` User wallet
+    ///   10. `[]` Voter of the user, may be empty
+    ///   11. `[]` Gauge controller
+    ///   12. `[]` farm program data id
+    ///   13. `[]` clock sysvar
+    ///
+    ///   If this farm is registered to the emitter, these accounts follow
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    Poke,
+
+    ///   View user's deposit balance in a farm at a past time, for governance voting weight
+    ///   Result is returned as `StakeSnapshotView` by `set_return_data`, nothing is written
+    ///   Time must be before current block time, so balance at that time can't be changed anymore
+    /// 
+    ///   0. `[]` FarmPool
+    ///   1. `[]` User Farming Information Account, may be empty
+    ///   2. `[]` clock sysvar
+    ViewStakeAt {
+        #[allow(dead_code)]
+        timestamp: u64,
+    },
+
+    ///   Farm owner lets anyone add rewards to his farm
+    ///   Open funding can't be disabled, sponsors rely on it for pro rata refunds
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    EnableOpenFunding,
+
+    ///   Refund sponsor's pro rata share of unspent rewards after the farm ends
+    ///   Anyone can call this, refund goes to sponsor's token account
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[w]` Sponsor account
+    ///   3. `[w]` Sponsor's reward token account
+    ///   4. `[w]` Pool reward token account
+    ///   5. `[]` Pool LP token account
+    ///   6. `[]` Reward token mint
+    ///   7. `[]` Reward token program id
+    ///   8. `[]` clock sysvar
+    RefundSponsor,
+
+    ///   Farm owner moves LP or reward vault to a new token account, balance is carried over
+    ///   New vault must be owned by the farm authority without delegate and close authority
+    ///   LP vault fails if the token's transfer fee would change staked amount
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` farm owner
+    ///   3. `[w]` Current pool LP or reward token account
+    ///   4. `[w]` New token account
+    ///   5. `[]` Token mint of the vault
+    ///   6. `[]` Token program id of the vault
+    RotateVault,
+
+    ///   Farm owner proposes new owner of his farm, default pubkey cancels pending proposal
+    ///   Ownership moves when new owner accepts it
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` proposed owner
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    ProposeOwner,
+
+    ///   Proposed owner accepts ownership of the farm
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` proposed owner
+    AcceptOwner,
+
+    ///   Farm owner sets loyalty options before anyone stakes
+    ///   Reward weight grows linearly from 1 to max multiplier over loyalty period of uninterrupted staking
+    ///   Withdrawing restarts the period, zero loyalty period disables loyalty
+    ///   max multiplier = loyalty_max_numerator / loyalty_max_denominator
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` Pool LP token account
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    SetLoyaltyOptions {
+        #[allow(dead_code)]
+        loyalty_period: u64,
+
+        #[allow(dead_code)]
+        loyalty_max_numerator: u64,
+
+        #[allow(dead_code)]
+        loyalty_max_denominator: u64,
+    },
+
+    ///   Wallet opens another independent position in a farm
+    ///   Position's user info is passed as User Farming Information Account to all other instructions
+    ///   Position 0 is the default user info
+    ///   Voting power boosts only position 0, other positions of boosted farms earn by their deposit share
+    /// 
+    ///   0. `[]` FarmPool
+    ///   1. `[s]` wallet, pays rent
+    ///   2. `[w]` User Farming Information Account of the position
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    OpenPosition {
+        #[allow(dead_code)]
+        position_index: u64,
+    },
+
+    ///   Farm owner sets bounty paid to keepers who crank his farm
+    ///   Bounty is taken from rewards not distributed yet, at most once per crank interval
+    ///   Zero bounty disables paid cranks
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[s]` farm owner, pays rent if farm account is extended
+    ///   2. `[]` rent sysvar
+    ///   3. `[]` system program id
+    SetCrankOptions {
+        #[allow(dead_code)]
+        crank_bounty: u64,
+
+        #[allow(dead_code)]
+        crank_interval: u64,
+    },
+
+    ///   Anyone updates reward per share of a farm up to current time
+    ///   Keeper receives crank bounty if the crank interval has passed since the latest paid crank
+    /// 
+    ///   0. `[w]` FarmPool
+    ///   1. `[]` authority of this farm pool
+    ///   2. `[s]` keeper
+    ///   3. `[w]` Keeper's reward token account to receive bounty
+    ///   4. `[]` Pool LP token account
+    ///   5. `[w]` Pool reward token account
+    ///   6. `[]` Reward token mint
+    ///   7. `[]` Reward token program id
+    ///   8. `[]` clock sysvar
+    ///
+    ///   If this farm is registered to the emitter, these accounts follow
+    ///   - `[w]` Emitter
+    ///   - `[]` authority of emitter
+    ///   - `[w]` Emitter reward vault
+    Crank,
+
+    ///   Super owner creates audit log, then privileged instructions are recorded in it
+    ///   Once created, audit log account has to be passed right after fixed accounts of these instructions:
+    ///   SetProgramData, RemoveRewards, PayFarmFee, SetListingOptions, RejectFarm, SetFeeToken,
+    ///   QueueProgramData, ExecuteProgramData, CancelProgramData, InitializeEmitter, SetEmitterRate, SetAllocPoint,
+    ///   InitializeGaugeController, CreateGauge
+    ///   Clients decode the audit log account with `decode_audit_log`
+    /// 
+    ///   0. `[w]` Audit log, PDA of [PREFIX, AUDIT_LOG_SEED, program id]
+    ///   1. `[w]` farm program data id
+    ///   2. `[s]` super owner, pays rent of audit log and extended program data
+    ///   3. `[]` rent sysvar
+    ///   4. `[]` system program id
+    InitializeAuditLog,
+
+    ///   Remove a vote whose lock is ended, expired locks have no voting power
+    ///   Anyone can call this
+    /// 
+    ///   0. `[w]` Gauge vote
+    ///   1. `[w]` Voter
+    ///   2. `[w]` Gauge
+    ///   3. `[w]` Gauge controller
+    ///   4. `[]` clock sysvar
+    RemoveExpiredVote,
 }
 
 // below functions are used to test above instructions in the rust test side
@@ -191,6 +1090,9 @@ pub fn initialize_program(
     let accounts = vec![
         AccountMeta::new(*program_data_account, false),
         AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(amm_program_id, false),
     ];
     Instruction {
         program_id: *program_id,
@@ -214,6 +1116,7 @@ pub fn initialize_farm(
     nonce: u8,
     start_timestamp: u64,
     end_timestamp: u64,
+    listing_accounts: Option<&ListingAccounts>,
     program_id: &Pubkey,
 ) -> Instruction {
     
@@ -224,7 +1127,7 @@ pub fn initialize_farm(
     };
     
     let data = init_data.try_to_vec().unwrap();
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*farm_id, false),
         AccountMeta::new(*authority, false),
         AccountMeta::new_readonly(*owner, true),
@@ -235,6 +1138,15 @@ pub fn initialize_farm(
         AccountMeta::new_readonly(*amm_id, false),
         AccountMeta::new_readonly(*program_data_account, false),
     ];
+    if let Some(listing_accounts) = listing_accounts {
+        accounts.push(AccountMeta::new(listing_accounts.user_usdc_token_account, false));
+        accounts.push(AccountMeta::new(listing_accounts.escrow_fee_account, false));
+        accounts.push(AccountMeta::new_readonly(listing_accounts.usdc_mint, false));
+        accounts.push(AccountMeta::new_readonly(listing_accounts.fee_token_program_id, false));
+        accounts.push(AccountMeta::new(listing_accounts.user_reward_token_account, false));
+        accounts.push(AccountMeta::new_readonly(listing_accounts.reward_token_program_id, false));
+        accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    }
     Instruction {
         program_id: *program_id,
         accounts,
@@ -257,13 +1169,17 @@ pub fn deposit(
     fee_reward_ata: &Pubkey,
     program_data_account: &Pubkey,
     token_program_id: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    fee_owner: &Pubkey,
     amount: u64,
+    position_index: u64,
     program_id: &Pubkey,
 ) -> Instruction {
     let accounts = vec![
         AccountMeta::new(*farm_id, false),
         AccountMeta::new_readonly(*authority, false),
-        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(*owner, true),
         AccountMeta::new(*user_info_account, false),
         AccountMeta::new(*user_lp_token_account, false),
         AccountMeta::new(*pool_lp_token_account, false),
@@ -274,11 +1190,17 @@ pub fn deposit(
         AccountMeta::new(*program_data_account, false),
         AccountMeta::new(*token_program_id, false),
         AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(), false),
+        AccountMeta::new_readonly(*fee_owner, false),
     ];
     Instruction {
         program_id: *program_id,
         accounts,
-        data: FarmInstruction::Deposit(amount).try_to_vec().unwrap(),
+        data: FarmInstruction::Deposit { amount, position_index }.try_to_vec().unwrap(),
     }
 }
 
@@ -296,6 +1218,9 @@ pub fn withdraw(
     fee_reward_ata: &Pubkey,
     program_data_account: &Pubkey,
     token_program_id: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    fee_owner: &Pubkey,
     amount: u64,
     program_id: &Pubkey,
 ) -> Instruction {
@@ -313,6 +1238,12 @@ pub fn withdraw(
         AccountMeta::new(*program_data_account, false),
         AccountMeta::new(*token_program_id, false),
         AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(), false),
+        AccountMeta::new_readonly(*fee_owner, false),
     ];
     Instruction {
         program_id: *program_id,
@@ -333,6 +1264,7 @@ pub fn add_reward(
     pool_lp_mint_info: &Pubkey,
     program_data_account: &Pubkey,
     token_program_id: &Pubkey,
+    reward_mint: &Pubkey,
     amount: u64,
     program_id: &Pubkey,
 ) -> Instruction {
@@ -347,6 +1279,7 @@ pub fn add_reward(
         AccountMeta::new(*program_data_account, false),
         AccountMeta::new(*token_program_id, false),
         AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*reward_mint, false),
     ];
     Instruction {
         program_id: *program_id,
@@ -364,10 +1297,12 @@ pub fn pay_farm_fee(
     fee_usdc_ata: &Pubkey,
     program_data_account: &Pubkey,
     token_program_id: &Pubkey,
+    fee_mint: &Pubkey,
+    price_accounts: Option<&FeePriceAccounts>,
     amount: u64,
     program_id: &Pubkey,
 ) -> Instruction {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*farm_id, false),
         AccountMeta::new_readonly(*authority, false),
         AccountMeta::new_readonly(*owner, true),
@@ -375,10 +1310,1343 @@ pub fn pay_farm_fee(
         AccountMeta::new(*fee_usdc_ata, false),
         AccountMeta::new(*program_data_account, false),
         AccountMeta::new(*token_program_id, false),
+        AccountMeta::new_readonly(*fee_mint, false),
     ];
+    if let Some(price_accounts) = price_accounts {
+        accounts.push(AccountMeta::new_readonly(price_accounts.fee_token, false));
+        accounts.push(AccountMeta::new_readonly(price_accounts.amm_id, false));
+        accounts.push(AccountMeta::new_readonly(price_accounts.amm_token_a_account, false));
+        accounts.push(AccountMeta::new_readonly(price_accounts.amm_token_b_account, false));
+    }
     Instruction {
         program_id: *program_id,
         accounts,
         data: FarmInstruction::PayFarmFee(amount).try_to_vec().unwrap(),
     }
-}
\ No newline at end of file
+}
+
+/// Accounts to escrow farm fee and minimum reward by InitializeFarm instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListingAccounts {
+    pub user_usdc_token_account: Pubkey,
+    pub escrow_fee_account: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub fee_token_program_id: Pubkey,
+    pub user_reward_token_account: Pubkey,
+    pub reward_token_program_id: Pubkey,
+}
+
+/// Accounts to price a whitelisted fee token by PayFarmFee instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeePriceAccounts {
+    pub fee_token: Pubkey,
+    pub amm_id: Pubkey,
+    pub amm_token_a_account: Pubkey,
+    pub amm_token_b_account: Pubkey,
+}
+
+/// Accounts of a farm to harvest by HarvestMany instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct HarvestAccounts {
+    pub farm_id: Pubkey,
+    pub authority: Pubkey,
+    pub user_info_account: Pubkey,
+    pub pool_lp_token_account: Pubkey,
+    pub pool_reward_token_account: Pubkey,
+    pub user_reward_token_account: Pubkey,
+    pub fee_reward_ata: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_program_id: Pubkey,
+}
+
+/// Creates a 'MigrateStake' instruction.
+pub fn migrate_stake(
+    source: &HarvestAccounts,
+    destination: &HarvestAccounts,
+    owner: &Pubkey,
+    pool_lp_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    program_data_account: &Pubkey,
+    fee_owner: &Pubkey,
+    amount: u64,
+    destination_position_index: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = Vec::new();
+    for farm in [source, destination].iter() {
+        accounts.push(AccountMeta::new(farm.farm_id, false));
+        accounts.push(AccountMeta::new_readonly(farm.authority, false));
+        accounts.push(AccountMeta::new(farm.user_info_account, false));
+        accounts.push(AccountMeta::new(farm.pool_lp_token_account, false));
+        accounts.push(AccountMeta::new(farm.pool_reward_token_account, false));
+        accounts.push(AccountMeta::new(farm.user_reward_token_account, false));
+        accounts.push(AccountMeta::new(farm.fee_reward_ata, false));
+        accounts.push(AccountMeta::new_readonly(farm.reward_mint, false));
+        accounts.push(AccountMeta::new_readonly(farm.reward_token_program_id, false));
+    }
+    accounts.push(AccountMeta::new(*owner, true));
+    accounts.push(AccountMeta::new_readonly(*pool_lp_mint, false));
+    accounts.push(AccountMeta::new_readonly(*token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(*program_data_account, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(), false));
+    accounts.push(AccountMeta::new_readonly(*fee_owner, false));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::MigrateStake { amount, destination_position_index }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a instruction required to enable receipt tokens for a farm
+pub fn enable_receipt(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    receipt_mint: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_lp_mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*receipt_mint, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(*pool_lp_mint, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::EnableReceipt.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a instruction required to transfer receipt tokens with deposit balance
+pub fn transfer_receipt(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    sender: &Pubkey,
+    sender_user_info_account: &Pubkey,
+    sender_receipt_token_account: &Pubkey,
+    receiver: &Pubkey,
+    receiver_user_info_account: &Pubkey,
+    receiver_receipt_token_account: &Pubkey,
+    receipt_mint: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+    receiver_position_index: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*sender, true),
+        AccountMeta::new(*sender_user_info_account, false),
+        AccountMeta::new(*sender_receipt_token_account, false),
+        AccountMeta::new_readonly(*receiver, false),
+        AccountMeta::new(*receiver_user_info_account, false),
+        AccountMeta::new(*receiver_receipt_token_account, false),
+        AccountMeta::new_readonly(*receipt_mint, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::TransferReceipt { amount, receiver_position_index }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'HarvestMany' instruction.
+/// Emitter and lock accounts of each farm are given with its accounts if the farm needs them
+pub fn harvest_many(
+    harvester: &Pubkey,
+    program_data_account: &Pubkey,
+    fee_owner: &Pubkey,
+    farms: &[(HarvestAccounts, Vec<AccountMeta>)],
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*harvester, true),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(), false),
+        AccountMeta::new_readonly(*fee_owner, false),
+    ];
+    for (farm, optional_accounts) in farms {
+        accounts.push(AccountMeta::new(farm.farm_id, false));
+        accounts.push(AccountMeta::new_readonly(farm.authority, false));
+        accounts.push(AccountMeta::new(farm.user_info_account, false));
+        accounts.push(AccountMeta::new_readonly(farm.pool_lp_token_account, false));
+        accounts.push(AccountMeta::new(farm.pool_reward_token_account, false));
+        accounts.push(AccountMeta::new(farm.user_reward_token_account, false));
+        accounts.push(AccountMeta::new(farm.fee_reward_ata, false));
+        accounts.push(AccountMeta::new_readonly(farm.reward_mint, false));
+        accounts.push(AccountMeta::new_readonly(farm.reward_token_program_id, false));
+        accounts.extend_from_slice(optional_accounts);
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::HarvestMany.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ViewPendingRewards' instruction.
+pub fn view_pending_rewards(
+    farm_id: &Pubkey,
+    user_info_account: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    emitter_accounts: Option<(&Pubkey, &Pubkey, &Pubkey)>,
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*farm_id, false),
+        AccountMeta::new_readonly(*user_info_account, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some((emitter, emitter_authority, emitter_reward_vault)) = emitter_accounts {
+        accounts.push(AccountMeta::new_readonly(*emitter, false));
+        accounts.push(AccountMeta::new_readonly(*emitter_authority, false));
+        accounts.push(AccountMeta::new_readonly(*emitter_reward_vault, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ViewPendingRewards.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ExtendFarm' instruction.
+pub fn extend_farm(
+    farm_id: &Pubkey,
+    payer: &Pubkey,
+    user_info_accounts: &[Pubkey],
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for user_info_account in user_info_accounts {
+        accounts.push(AccountMeta::new(*user_info_account, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ExtendFarm.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetLockupOptions' instruction.
+pub fn set_lockup_options(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    min_stake_duration: u64,
+    early_withdraw_fee_numerator: u64,
+    early_withdraw_fee_denominator: u64,
+    unbonding_period: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetLockupOptions {
+            min_stake_duration,
+            early_withdraw_fee_numerator,
+            early_withdraw_fee_denominator,
+            unbonding_period,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ClaimUnbonded' instruction.
+pub fn claim_unbonded(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    user_info_account: &Pubkey,
+    user_lp_token_account: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_lp_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(*user_info_account, false),
+        AccountMeta::new(*user_lp_token_account, false),
+        AccountMeta::new(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*pool_lp_mint, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ClaimUnbonded.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetFeeToken' instruction.
+pub fn set_fee_token(
+    fee_token: &Pubkey,
+    super_owner: &Pubkey,
+    program_data_account: &Pubkey,
+    fee_mint: &Pubkey,
+    amm_id: &Pubkey,
+    is_enabled: u8,
+    tolerance_numerator: u64,
+    tolerance_denominator: u64,
+    discount_numerator: u64,
+    discount_denominator: u64,
+    reference_usdc_amount: u64,
+    reference_token_amount: u64,
+    max_price_deviation_bps: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*fee_token, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new_readonly(*fee_mint, false),
+        AccountMeta::new_readonly(*amm_id, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetFeeToken {
+            is_enabled,
+            tolerance_numerator,
+            tolerance_denominator,
+            discount_numerator,
+            discount_denominator,
+            reference_usdc_amount,
+            reference_token_amount,
+            max_price_deviation_bps,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetListingOptions' instruction.
+pub fn set_listing_options(
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    review_period: u64,
+    min_reward_deposit: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*program_data_account, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetListingOptions {
+            review_period,
+            min_reward_deposit,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'RejectFarm' instruction.
+pub fn reject_farm(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    super_owner: &Pubkey,
+    program_data_account: &Pubkey,
+    escrow_fee_account: &Pubkey,
+    user_usdc_token_account: &Pubkey,
+    usdc_mint: &Pubkey,
+    fee_token_program_id: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    user_reward_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new_readonly(*super_owner, true),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*escrow_fee_account, false),
+        AccountMeta::new(*user_usdc_token_account, false),
+        AccountMeta::new_readonly(*usdc_mint, false),
+        AccountMeta::new_readonly(*fee_token_program_id, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new(*user_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::RejectFarm.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'FinalizeListing' instruction.
+pub fn finalize_listing(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    program_data_account: &Pubkey,
+    escrow_fee_account: &Pubkey,
+    fee_usdc_ata: &Pubkey,
+    usdc_mint: &Pubkey,
+    fee_token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*escrow_fee_account, false),
+        AccountMeta::new(*fee_usdc_ata, false),
+        AccountMeta::new_readonly(*usdc_mint, false),
+        AccountMeta::new_readonly(*fee_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::FinalizeListing.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CloseFarm' instruction.
+pub fn close_farm(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    user_reward_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*pool_lp_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new(*user_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CloseFarm.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CreateMultisig' instruction.
+pub fn create_multisig(
+    multisig: &Pubkey,
+    signers: &[Pubkey],
+    m: u8,
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    for signer in signers {
+        accounts.push(AccountMeta::new_readonly(*signer, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CreateMultisig { m }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CreateProposal' instruction to propose given instruction of this program.
+pub fn create_proposal(
+    proposal: &Pubkey,
+    multisig: &Pubkey,
+    proposer: &Pubkey,
+    instruction: &Instruction,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*proposal, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*proposer, true),
+    ];
+    let instruction_accounts = instruction.accounts.iter().map(|account| ProposalAccount {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    }).collect();
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CreateProposal {
+            instruction_accounts,
+            instruction_data: instruction.data.clone(),
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ApproveProposal' instruction.
+pub fn approve_proposal(
+    proposal: &Pubkey,
+    multisig: &Pubkey,
+    approver: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*proposal, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*approver, true),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ApproveProposal.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ExecuteProposal' instruction.
+/// `instruction` is the proposed instruction, its signers are not required to sign this one
+pub fn execute_proposal(
+    proposal: &Pubkey,
+    multisig: &Pubkey,
+    multisig_authority: &Pubkey,
+    instruction: &Instruction,
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*proposal, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*multisig_authority, false),
+    ];
+    for account in &instruction.accounts {
+        if account.is_writable {
+            accounts.push(AccountMeta::new(account.pubkey, false));
+        } else {
+            accounts.push(AccountMeta::new_readonly(account.pubkey, false));
+        }
+    }
+    accounts.push(AccountMeta::new_readonly(*program_id, false));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ExecuteProposal.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'QueueProgramData' instruction.
+pub fn queue_program_data(
+    program_data_account: &Pubkey,
+    timelock: &Pubkey,
+    super_owner: &Pubkey,
+    amm_program_id: Pubkey,
+    farm_fee: u64,
+    harvest_fee_numerator: u64,
+    harvest_fee_denominator: u64,
+    min_delay: u64,
+    eta: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*program_data_account, false),
+        AccountMeta::new(*timelock, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(amm_program_id, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::QueueProgramData {
+            amm_program_id,
+            farm_fee,
+            harvest_fee_numerator,
+            harvest_fee_denominator,
+            min_delay,
+            eta,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ExecuteProgramData' instruction.
+pub fn execute_program_data(
+    program_data_account: &Pubkey,
+    timelock: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*program_data_account, false),
+        AccountMeta::new(*timelock, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ExecuteProgramData.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CancelProgramData' instruction.
+pub fn cancel_program_data(
+    program_data_account: &Pubkey,
+    timelock: &Pubkey,
+    super_owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*timelock, false),
+        AccountMeta::new_readonly(*super_owner, true),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CancelProgramData.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'InitializeEmitter' instruction.
+pub fn initialize_emitter(
+    emitter: &Pubkey,
+    emitter_authority: &Pubkey,
+    reward_vault: &Pubkey,
+    reward_mint: &Pubkey,
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    token_program_id: &Pubkey,
+    reward_per_second: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*emitter, false),
+        AccountMeta::new_readonly(*emitter_authority, false),
+        AccountMeta::new_readonly(*reward_vault, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::InitializeEmitter { reward_per_second }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetEmitterRate' instruction.
+pub fn set_emitter_rate(
+    emitter: &Pubkey,
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    reward_per_second: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*emitter, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new_readonly(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetEmitterRate { reward_per_second }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetAllocPoint' instruction.
+pub fn set_alloc_point(
+    emitter: &Pubkey,
+    emitter_authority: &Pubkey,
+    emitter_reward_vault: &Pubkey,
+    farm_id: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    alloc_point: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*emitter, false),
+        AccountMeta::new_readonly(*emitter_authority, false),
+        AccountMeta::new(*emitter_reward_vault, false),
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetAllocPoint { alloc_point }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'InitializeGaugeController' instruction.
+pub fn initialize_gauge_controller(
+    gauge_controller: &Pubkey,
+    gauge_controller_authority: &Pubkey,
+    lock_vault: &Pubkey,
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    token_program_id: &Pubkey,
+    epoch_duration: u64,
+    max_lock_duration: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*gauge_controller, false),
+        AccountMeta::new_readonly(*gauge_controller_authority, false),
+        AccountMeta::new_readonly(*lock_vault, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::InitializeGaugeController {
+            epoch_duration,
+            max_lock_duration,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CreateGauge' instruction.
+pub fn create_gauge(
+    gauge: &Pubkey,
+    farm_id: &Pubkey,
+    gauge_controller: &Pubkey,
+    emitter: &Pubkey,
+    program_data_account: &Pubkey,
+    super_owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*gauge, false),
+        AccountMeta::new_readonly(*farm_id, false),
+        AccountMeta::new_readonly(*gauge_controller, false),
+        AccountMeta::new_readonly(*emitter, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CreateGauge.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'LockVotingPower' instruction.
+pub fn lock_voting_power(
+    voter: &Pubkey,
+    gauge_controller: &Pubkey,
+    lock_vault: &Pubkey,
+    user_crp_token_account: &Pubkey,
+    crp_mint: &Pubkey,
+    wallet: &Pubkey,
+    token_program_id: &Pubkey,
+    amount: u64,
+    duration: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*voter, false),
+        AccountMeta::new(*gauge_controller, false),
+        AccountMeta::new(*lock_vault, false),
+        AccountMeta::new(*user_crp_token_account, false),
+        AccountMeta::new_readonly(*crp_mint, false),
+        AccountMeta::new(*wallet, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::LockVotingPower { amount, duration }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'VoteGauge' instruction.
+pub fn vote_gauge(
+    gauge_vote: &Pubkey,
+    voter: &Pubkey,
+    gauge: &Pubkey,
+    gauge_controller: &Pubkey,
+    wallet: &Pubkey,
+    power: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*gauge_vote, false),
+        AccountMeta::new(*voter, false),
+        AccountMeta::new(*gauge, false),
+        AccountMeta::new(*gauge_controller, false),
+        AccountMeta::new(*wallet, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::VoteGauge { power }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'UnlockVotingPower' instruction.
+pub fn unlock_voting_power(
+    voter: &Pubkey,
+    gauge_controller: &Pubkey,
+    gauge_controller_authority: &Pubkey,
+    lock_vault: &Pubkey,
+    user_crp_token_account: &Pubkey,
+    crp_mint: &Pubkey,
+    wallet: &Pubkey,
+    token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*voter, false),
+        AccountMeta::new(*gauge_controller, false),
+        AccountMeta::new_readonly(*gauge_controller_authority, false),
+        AccountMeta::new(*lock_vault, false),
+        AccountMeta::new(*user_crp_token_account, false),
+        AccountMeta::new_readonly(*crp_mint, false),
+        AccountMeta::new_readonly(*wallet, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::UnlockVotingPower.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'CheckpointGauge' instruction.
+pub fn checkpoint_gauge(
+    emitter: &Pubkey,
+    emitter_authority: &Pubkey,
+    emitter_reward_vault: &Pubkey,
+    farm_id: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    gauge: &Pubkey,
+    gauge_controller: &Pubkey,
+    payer: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*emitter, false),
+        AccountMeta::new_readonly(*emitter_authority, false),
+        AccountMeta::new(*emitter_reward_vault, false),
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new(*gauge, false),
+        AccountMeta::new_readonly(*gauge_controller, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::CheckpointGauge.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetBoostOptions' instruction.
+pub fn set_boost_options(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    boost_base_numerator: u64,
+    boost_base_denominator: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetBoostOptions {
+            boost_base_numerator,
+            boost_base_denominator,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'Poke' instruction.
+pub fn poke(
+    farm: &HarvestAccounts,
+    wallet: &Pubkey,
+    voter: &Pubkey,
+    gauge_controller: &Pubkey,
+    program_data_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(farm.farm_id, false),
+        AccountMeta::new_readonly(farm.authority, false),
+        AccountMeta::new(farm.user_info_account, false),
+        AccountMeta::new_readonly(farm.pool_lp_token_account, false),
+        AccountMeta::new(farm.pool_reward_token_account, false),
+        AccountMeta::new(farm.user_reward_token_account, false),
+        AccountMeta::new(farm.fee_reward_ata, false),
+        AccountMeta::new_readonly(farm.reward_mint, false),
+        AccountMeta::new_readonly(farm.reward_token_program_id, false),
+        AccountMeta::new_readonly(*wallet, false),
+        AccountMeta::new_readonly(*voter, false),
+        AccountMeta::new_readonly(*gauge_controller, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::Poke.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ViewStakeAt' instruction.
+pub fn view_stake_at(
+    farm_id: &Pubkey,
+    user_info_account: &Pubkey,
+    timestamp: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*farm_id, false),
+        AccountMeta::new_readonly(*user_info_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ViewStakeAt { timestamp }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'EnableOpenFunding' instruction.
+pub fn enable_open_funding(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::EnableOpenFunding.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'RefundSponsor' instruction.
+pub fn refund_sponsor(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    sponsor: &Pubkey,
+    sponsor_reward_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*sponsor, false),
+        AccountMeta::new(*sponsor_reward_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::RefundSponsor.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'RotateVault' instruction.
+pub fn rotate_vault(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    current_vault: &Pubkey,
+    new_vault: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(*current_vault, false),
+        AccountMeta::new(*new_vault, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::RotateVault.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'ProposeOwner' instruction.
+pub fn propose_owner(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*new_owner, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::ProposeOwner.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'AcceptOwner' instruction.
+pub fn accept_owner(
+    farm_id: &Pubkey,
+    new_owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*new_owner, true),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::AcceptOwner.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetLoyaltyOptions' instruction.
+pub fn set_loyalty_options(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    loyalty_period: u64,
+    loyalty_max_numerator: u64,
+    loyalty_max_denominator: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetLoyaltyOptions {
+            loyalty_period,
+            loyalty_max_numerator,
+            loyalty_max_denominator,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates an 'OpenPosition' instruction.
+pub fn open_position(
+    farm_id: &Pubkey,
+    wallet: &Pubkey,
+    position_index: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let (user_info, _) = find_user_info_address(program_id, farm_id, wallet, position_index);
+    let accounts = vec![
+        AccountMeta::new_readonly(*farm_id, false),
+        AccountMeta::new(*wallet, true),
+        AccountMeta::new(user_info, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::OpenPosition { position_index }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'SetCrankOptions' instruction.
+pub fn set_crank_options(
+    farm_id: &Pubkey,
+    owner: &Pubkey,
+    crank_bounty: u64,
+    crank_interval: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::SetCrankOptions {
+            crank_bounty,
+            crank_interval,
+        }.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'Crank' instruction.
+pub fn crank(
+    farm_id: &Pubkey,
+    authority: &Pubkey,
+    keeper: &Pubkey,
+    keeper_reward_token_account: &Pubkey,
+    pool_lp_token_account: &Pubkey,
+    pool_reward_token_account: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program_id: &Pubkey,
+    emitter_accounts: Option<(Pubkey, Pubkey, Pubkey)>,
+    program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*farm_id, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new_readonly(*keeper, true),
+        AccountMeta::new(*keeper_reward_token_account, false),
+        AccountMeta::new_readonly(*pool_lp_token_account, false),
+        AccountMeta::new(*pool_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*reward_token_program_id, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some((emitter, emitter_authority, emitter_vault)) = emitter_accounts {
+        accounts.push(AccountMeta::new(emitter, false));
+        accounts.push(AccountMeta::new_readonly(emitter_authority, false));
+        accounts.push(AccountMeta::new(emitter_vault, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::Crank.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates an 'InitializeAuditLog' instruction.
+pub fn initialize_audit_log(
+    program_data: &Pubkey,
+    super_owner: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let (audit_log, _) = find_audit_log_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(audit_log, false),
+        AccountMeta::new(*program_data, false),
+        AccountMeta::new(*super_owner, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::InitializeAuditLog.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a 'RemoveExpiredVote' instruction.
+pub fn remove_expired_vote(
+    gauge_vote: &Pubkey,
+    voter: &Pubkey,
+    gauge: &Pubkey,
+    gauge_controller: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*gauge_vote, false),
+        AccountMeta::new(*voter, false),
+        AccountMeta::new(*gauge, false),
+        AccountMeta::new(*gauge_controller, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FarmInstruction::RemoveExpiredVote.try_to_vec().unwrap(),
+    }
+}
+
+/// Inserts audit log account right after fixed accounts of a privileged instruction.
+/// Optional accounts of `PayFarmFee` follow the audit log, other privileged instructions have fixed accounts only.
+pub fn with_audit_log(
+    mut instruction: Instruction,
+) -> Instruction {
+    let (audit_log, _) = find_audit_log_address(&instruction.program_id);
+    let index = match FarmInstruction::try_from_slice(&instruction.data) {
+        Ok(FarmInstruction::PayFarmFee(_)) => PAY_FARM_FEE_ACCOUNTS,
+        _ => instruction.accounts.len(),
+    };
+    instruction.accounts.insert(index.min(instruction.accounts.len()), AccountMeta::new(audit_log, false));
+    instruction
+}
+
+/// Finds audit log address.
+pub fn find_audit_log_address(
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PREFIX.as_bytes(), AUDIT_LOG_SEED.as_bytes(), program_id.as_ref()], program_id)
+}
+
+/// Decodes audit log account data fetched by a client into its entries, oldest first.
+/// `AuditEntry::get_kind_name` gives the instruction name of each entry.
+pub fn decode_audit_log(
+    data: &[u8],
+) -> Result<Vec<AuditEntry>, ProgramError> {
+    let audit_log = AuditLog::deserialize(&mut &data[..])?;
+    Ok(audit_log.get_entries())
+}
+
+/// Finds user info address of a wallet's position in a farm.
+/// Position 0 keeps the seeds of the default user info.
+pub fn find_user_info_address(
+    program_id: &Pubkey,
+    farm_id: &Pubkey,
+    wallet: &Pubkey,
+    position_index: u64,
+) -> (Pubkey, u8) {
+    let index = position_index.to_le_bytes();
+    if position_index == 0 {
+        Pubkey::find_program_address(&[PREFIX.as_bytes(), farm_id.as_ref(), wallet.as_ref()], program_id)
+    } else {
+        Pubkey::find_program_address(&[PREFIX.as_bytes(), farm_id.as_ref(), wallet.as_ref(), &index], program_id)
+    }
+}
+
+/// Memcmp filters as (offset, bytes) for `getProgramAccounts` to fetch a wallet's user infos in a farm.
+pub fn list_positions_filters(
+    farm_id: &Pubkey,
+    wallet: &Pubkey,
+) -> Vec<(usize, Vec<u8>)> {
+    vec![
+        (0, wallet.to_bytes().to_vec()),
+        (32, farm_id.to_bytes().to_vec()),
+    ]
+}
+
+/// Lists a wallet's positions in a farm from accounts fetched with `list_positions_filters`.
+/// Other accounts starting with the same wallet and farm are skipped by address, positions are sorted by index.
+pub fn list_positions(
+    farm_id: &Pubkey,
+    wallet: &Pubkey,
+    accounts: &[(Pubkey, Vec<u8>)],
+    program_id: &Pubkey,
+) -> Vec<(Pubkey, UserInfo)> {
+    let mut positions: Vec<(Pubkey, UserInfo)> = accounts
+        .iter()
+        .filter_map(|(key, data)| {
+            let user_info = UserInfo::unpack_from_slice(data).ok()?;
+            if  user_info.wallet != *wallet ||
+                user_info.farm_id != *farm_id ||
+                *key != find_user_info_address(program_id, farm_id, wallet, user_info.position_index).0 {
+                return None;
+            }
+            Some((*key, user_info))
+        })
+        .collect();
+    positions.sort_by_key(|(_, user_info)| user_info.position_index);
+    positions
+}
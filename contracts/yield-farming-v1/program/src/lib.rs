@@ -1,4 +1,4 @@
-/// Main Entrypoint and declaration file
+//! Main Entrypoint and declaration file
 
 use solana_program::{
     account_info::{ AccountInfo},
@@ -1,11 +1,14 @@
 //! Program state processor
 //! In here, All instructions are processed by Processor
 
+// this allows many arguments for the function parameters
+#![allow(clippy::too_many_arguments)]
+
 use {
     crate::{
         error::FarmError,
-        instruction::{FarmInstruction},
-        state::{FarmProgram,FarmPool,UserInfo},
+        instruction::{FarmInstruction, find_user_info_address},
+        state::{AuditLog,AuditEntry,FarmProgram,FarmPool,UserInfo,HarvestResult,PendingRewardsView,StakeSnapshotView,Sponsor,FeeToken,Proposal,ProposalAccount,ProgramDataChange,Emitter,GaugeController,Voter,Gauge,GaugeVote},
         constant::*,
         utils::*
     },
@@ -16,21 +19,37 @@ use {
             next_account_info,
             AccountInfo,
         },
-        borsh::try_from_slice_unchecked,
         decode_error::DecodeError,
         entrypoint::ProgramResult,
         msg,
-        program::{ invoke_signed},
+        instruction::{AccountMeta, Instruction},
+        program::{ invoke, invoke_signed, set_return_data},
+        program_option::COption,
         program_error::PrintProgramError,
         program_error::ProgramError,
         pubkey::Pubkey,
         clock::Clock,
-        sysvar::Sysvar,
-        program_pack::Pack,
+        hash::hash,
+        sysvar::{rent::Rent, Sysvar},
+        system_instruction,
+        system_program,
+        program_pack::{IsInitialized, Pack},
+    },
+    spl_token::{
+        instruction::MAX_SIGNERS,
+        state::{Mint, Account, AccountState, Multisig},
+    },
+    spl_token_2022::{
+        extension::StateWithExtensions,
+        state::{
+            Account as Token2022Account,
+            AccountState as Token2022AccountState,
+            Mint as Token2022Mint,
+        },
     },
-    spl_token::state::{Mint, Account, AccountState}, 
 };
 use std::str::FromStr;
+use std::convert::TryFrom;
 
 // useful amm program's state
 use cropper_liquidity_pool::amm_stats::{SwapVersion};
@@ -74,9 +93,9 @@ impl Processor {
                 // Instruction: Initialize
                 Self::process_initialize_farm(program_id, accounts, nonce, start_timestamp, end_timestamp)
             }
-            FarmInstruction::Deposit(amount) => {
+            FarmInstruction::Deposit { amount, position_index } => {
                 // Instruction: Deposit
-                Self::process_deposit(program_id, accounts, amount)
+                Self::process_deposit(program_id, accounts, amount, position_index)
             }
             FarmInstruction::Withdraw(amount) => {
                 // Instruction: Withdraw
@@ -93,6 +112,251 @@ impl Processor {
             FarmInstruction::RemoveRewards => {
                 Self::process_remove_rewards(program_id, accounts)
             }
+            FarmInstruction::EnableReceipt => {
+                // Instruction: EnableReceipt
+                Self::process_enable_receipt(program_id, accounts)
+            }
+            FarmInstruction::TransferReceipt { amount, receiver_position_index } => {
+                // Instruction: TransferReceipt
+                Self::process_transfer_receipt(program_id, accounts, amount, receiver_position_index)
+            }
+            FarmInstruction::HarvestMany => {
+                // Instruction: HarvestMany
+                Self::process_harvest_many(program_id, accounts)
+            }
+            FarmInstruction::ViewPendingRewards => {
+                // Instruction: ViewPendingRewards
+                Self::process_view_pending_rewards(program_id, accounts)
+            }
+            FarmInstruction::ExtendFarm => {
+                // Instruction: ExtendFarm
+                Self::process_extend_farm(program_id, accounts)
+            }
+            FarmInstruction::SetLockupOptions {
+                min_stake_duration,
+                early_withdraw_fee_numerator,
+                early_withdraw_fee_denominator,
+                unbonding_period,
+            } => {
+                // Instruction: SetLockupOptions
+                Self::process_set_lockup_options(
+                    program_id,
+                    accounts,
+                    min_stake_duration,
+                    early_withdraw_fee_numerator,
+                    early_withdraw_fee_denominator,
+                    unbonding_period,
+                )
+            }
+            FarmInstruction::ClaimUnbonded => {
+                // Instruction: ClaimUnbonded
+                Self::process_claim_unbonded(program_id, accounts)
+            }
+            FarmInstruction::SetFeeToken {
+                is_enabled,
+                tolerance_numerator,
+                tolerance_denominator,
+                discount_numerator,
+                discount_denominator,
+                reference_usdc_amount,
+                reference_token_amount,
+                max_price_deviation_bps,
+            } => {
+                // Instruction: SetFeeToken
+                Self::process_set_fee_token(
+                    program_id,
+                    accounts,
+                    FeeToken {
+                        is_enabled,
+                        tolerance_numerator,
+                        tolerance_denominator,
+                        discount_numerator,
+                        discount_denominator,
+                        reference_usdc_amount,
+                        reference_token_amount,
+                        max_price_deviation_bps,
+                        ..FeeToken::default()
+                    },
+                )
+            }
+            FarmInstruction::SetListingOptions {
+                review_period,
+                min_reward_deposit,
+            } => {
+                // Instruction: SetListingOptions
+                Self::process_set_listing_options(program_id, accounts, review_period, min_reward_deposit)
+            }
+            FarmInstruction::RejectFarm => {
+                // Instruction: RejectFarm
+                Self::process_reject_farm(program_id, accounts)
+            }
+            FarmInstruction::FinalizeListing => {
+                // Instruction: FinalizeListing
+                Self::process_finalize_listing(program_id, accounts)
+            }
+            FarmInstruction::CloseFarm => {
+                // Instruction: CloseFarm
+                Self::process_close_farm(program_id, accounts)
+            }
+            FarmInstruction::CreateMultisig { m } => {
+                // Instruction: CreateMultisig
+                Self::process_create_multisig(program_id, accounts, m)
+            }
+            FarmInstruction::CreateProposal {
+                instruction_accounts,
+                instruction_data,
+            } => {
+                // Instruction: CreateProposal
+                Self::process_create_proposal(program_id, accounts, instruction_accounts, instruction_data)
+            }
+            FarmInstruction::ApproveProposal => {
+                // Instruction: ApproveProposal
+                Self::process_approve_proposal(program_id, accounts)
+            }
+            FarmInstruction::ExecuteProposal => {
+                // Instruction: ExecuteProposal
+                Self::process_execute_proposal(program_id, accounts)
+            }
+            FarmInstruction::QueueProgramData {
+                amm_program_id,
+                farm_fee,
+                harvest_fee_numerator,
+                harvest_fee_denominator,
+                min_delay,
+                eta,
+            } => {
+                // Instruction: QueueProgramData
+                Self::process_queue_program_data(
+                    program_id,
+                    accounts,
+                    ProgramDataChange {
+                        is_queued: 1,
+                        eta,
+                        amm_program_id,
+                        farm_fee,
+                        harvest_fee_numerator,
+                        harvest_fee_denominator,
+                        min_delay,
+                    },
+                )
+            }
+            FarmInstruction::ExecuteProgramData => {
+                // Instruction: ExecuteProgramData
+                Self::process_execute_program_data(program_id, accounts)
+            }
+            FarmInstruction::CancelProgramData => {
+                // Instruction: CancelProgramData
+                Self::process_cancel_program_data(program_id, accounts)
+            }
+            FarmInstruction::MigrateStake { amount, destination_position_index } => {
+                // Instruction: MigrateStake
+                Self::process_migrate_stake(program_id, accounts, amount, destination_position_index)
+            }
+            FarmInstruction::InitializeEmitter { reward_per_second } => {
+                // Instruction: InitializeEmitter
+                Self::process_initialize_emitter(program_id, accounts, reward_per_second)
+            }
+            FarmInstruction::SetEmitterRate { reward_per_second } => {
+                // Instruction: SetEmitterRate
+                Self::process_set_emitter_rate(program_id, accounts, reward_per_second)
+            }
+            FarmInstruction::SetAllocPoint { alloc_point } => {
+                // Instruction: SetAllocPoint
+                Self::process_set_alloc_point(program_id, accounts, alloc_point)
+            }
+            FarmInstruction::InitializeGaugeController {
+                epoch_duration,
+                max_lock_duration,
+            } => {
+                // Instruction: InitializeGaugeController
+                Self::process_initialize_gauge_controller(program_id, accounts, epoch_duration, max_lock_duration)
+            }
+            FarmInstruction::CreateGauge => {
+                // Instruction: CreateGauge
+                Self::process_create_gauge(program_id, accounts)
+            }
+            FarmInstruction::LockVotingPower { amount, duration } => {
+                // Instruction: LockVotingPower
+                Self::process_lock_voting_power(program_id, accounts, amount, duration)
+            }
+            FarmInstruction::VoteGauge { power } => {
+                // Instruction: VoteGauge
+                Self::process_vote_gauge(program_id, accounts, power)
+            }
+            FarmInstruction::UnlockVotingPower => {
+                // Instruction: UnlockVotingPower
+                Self::process_unlock_voting_power(program_id, accounts)
+            }
+            FarmInstruction::CheckpointGauge => {
+                // Instruction: CheckpointGauge
+                Self::process_checkpoint_gauge(program_id, accounts)
+            }
+            FarmInstruction::SetBoostOptions {
+                boost_base_numerator,
+                boost_base_denominator,
+            } => {
+                // Instruction: SetBoostOptions
+                Self::process_set_boost_options(program_id, accounts, boost_base_numerator, boost_base_denominator)
+            }
+            FarmInstruction::Poke => {
+                // Instruction: Poke
+                Self::process_poke(program_id, accounts)
+            }
+            FarmInstruction::ViewStakeAt { timestamp } => {
+                // Instruction: ViewStakeAt
+                Self::process_view_stake_at(program_id, accounts, timestamp)
+            }
+            FarmInstruction::EnableOpenFunding => {
+                // Instruction: EnableOpenFunding
+                Self::process_enable_open_funding(program_id, accounts)
+            }
+            FarmInstruction::RefundSponsor => {
+                // Instruction: RefundSponsor
+                Self::process_refund_sponsor(program_id, accounts)
+            }
+            FarmInstruction::RotateVault => {
+                // Instruction: RotateVault
+                Self::process_rotate_vault(program_id, accounts)
+            }
+            FarmInstruction::ProposeOwner => {
+                // Instruction: ProposeOwner
+                Self::process_propose_owner(program_id, accounts)
+            }
+            FarmInstruction::AcceptOwner => {
+                // Instruction: AcceptOwner
+                Self::process_accept_owner(program_id, accounts)
+            }
+            FarmInstruction::SetLoyaltyOptions {
+                loyalty_period,
+                loyalty_max_numerator,
+                loyalty_max_denominator,
+            } => {
+                // Instruction: SetLoyaltyOptions
+                Self::process_set_loyalty_options(program_id, accounts, loyalty_period, loyalty_max_numerator, loyalty_max_denominator)
+            }
+            FarmInstruction::OpenPosition { position_index } => {
+                // Instruction: OpenPosition
+                Self::process_open_position(program_id, accounts, position_index)
+            }
+            FarmInstruction::SetCrankOptions {
+                crank_bounty,
+                crank_interval,
+            } => {
+                // Instruction: SetCrankOptions
+                Self::process_set_crank_options(program_id, accounts, crank_bounty, crank_interval)
+            }
+            FarmInstruction::Crank => {
+                // Instruction: Crank
+                Self::process_crank(program_id, accounts)
+            }
+            FarmInstruction::InitializeAuditLog => {
+                // Instruction: InitializeAuditLog
+                Self::process_initialize_audit_log(program_id, accounts)
+            }
+            FarmInstruction::RemoveExpiredVote => {
+                // Instruction: RemoveExpiredVote
+                Self::process_remove_expired_vote(program_id, accounts)
+            }
         }
     }
     pub fn process_remove_rewards(
@@ -122,9 +386,12 @@ impl Processor {
         // farm program data account info
         let farm_program_info = next_account_info(account_info_iter)?;
 
-        // spl-token program address
+        // spl-token program address of reward token
         let token_program_info = next_account_info(account_info_iter)?;
 
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
         if *farm_id_info.key != Pubkey::from_str(REMOVE_REWARDS_FARM_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
             return Err(FarmError::InvalidSystemProgramId.into());
         }
@@ -136,10 +403,13 @@ impl Processor {
         if Self::is_zero_account(farm_program_info) {
             return Err(FarmError::NotInitializedProgramData.into());
         }
-        let program_data = try_from_slice_unchecked::<FarmProgram>(&farm_program_info.data.borrow())?;
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
 
         // borrow farm pool account data
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
         if *remover_info.key != program_data.super_owner {
             return Err(FarmError::WrongManager.into());
@@ -156,8 +426,8 @@ impl Processor {
         }
 
         // token account - check if owner is saved token program
-        if  *user_reward_token_account_info.owner != farm_pool.token_program_id ||
-            *pool_reward_token_account_info.owner != farm_pool.token_program_id {
+        if  *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
                 return Err(FarmError::InvalidOwner.into());
         }
 
@@ -165,35 +435,45 @@ impl Processor {
                 return Err(FarmError::InvalidOwner.into());
         }
 
-        let user_reward_token_data = Account::unpack_from_slice(&user_reward_token_account_info.data.borrow())?;
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
 
         if  user_reward_token_data.owner != *remover_info.key ||
             pool_reward_token_data.owner != *authority_info.key {
             return Err(FarmError::InvalidOwner.into());
         }
 
-        if *token_program_info.key != farm_pool.token_program_id {
+        if *token_program_info.key != farm_pool.get_reward_token_program_id() {
             return Err(FarmError::InvalidProgramAddress.into());
         }
 
+        let old_value = farm_id_info.data.borrow().to_vec();
+
         // remove reward
         Self::token_transfer(
             farm_id_info.key,
             token_program_info.clone(),
             pool_reward_token_account_info.clone(),
+            reward_mint_info.clone(),
             user_reward_token_account_info.clone(),
             authority_info.clone(),
             farm_pool.nonce,
             pool_reward_token_data.amount
         )?;
 
-        farm_pool.remained_reward_amount -= pool_reward_token_data.amount;
+        // vault can hold pulled emission which isn't counted in remained reward amount
+        farm_pool.remained_reward_amount = farm_pool.remained_reward_amount.saturating_sub(pool_reward_token_data.amount);
 
         // store farm pool account data to network
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_REMOVE_REWARDS, remover_info.key, &old_value, &farm_id_info.data.borrow())
     } 
     pub fn process_initialize_or_set_program(
         program_id: &Pubkey,        // this program id
@@ -215,6 +495,9 @@ impl Processor {
         let rent_info = next_account_info(account_info_iter)?;
         let system_info = next_account_info(account_info_iter)?;
 
+        // AMM program account information to check if it is executable
+        let amm_program_info = next_account_info(account_info_iter)?;
+
         // check if rent sysvar program id is correct
         if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
             return Err(FarmError::InvalidRentSysvarId.into());
@@ -261,10 +544,14 @@ impl Processor {
             )?;
         }
 
-        let mut program_data = try_from_slice_unchecked::<FarmProgram>(&program_data_info.data.borrow())?;
+        let mut program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
 
         // if first initialization
-        if Self::is_zero_account(program_data_info) {
+        let is_first_initialization = Self::is_zero_account(program_data_info);
+        if is_first_initialization {
             program_data.version = VERSION;
             program_data.reward_multipler = REWARD_MULTIPLER;
             program_data.super_owner = Pubkey::from_str(INITIAL_SUPER_OWNER).map_err(|_| FarmError::InvalidPubkey)?;
@@ -275,6 +562,21 @@ impl Processor {
             return Err(FarmError::InvalidOwner.into());
         }
 
+        // validate parameters, so harvests can't fail by fees
+        FarmProgram::validate_params(fee_owner, harvest_fee_numerator, harvest_fee_denominator)?;
+        Self::assert_amm_program(amm_program_info, amm_program_id)?;
+
+        // fee and amm program changes have to be queued in timelock after initialization
+        if  !is_first_initialization && (
+            program_data.amm_program_id != *amm_program_id ||
+            program_data.farm_fee != farm_fee ||
+            program_data.harvest_fee_numerator != harvest_fee_numerator ||
+            program_data.harvest_fee_denominator != harvest_fee_denominator) {
+            return Err(FarmError::TimelockRequired.into());
+        }
+
+        let old_value = program_data_info.data.borrow().to_vec();
+
         // save given parameters
         program_data.super_owner = *super_owner;
         program_data.fee_owner = *fee_owner;
@@ -285,9 +587,10 @@ impl Processor {
         program_data.harvest_fee_denominator = harvest_fee_denominator;
 
         // serialize/store this initialized data
-        program_data
-            .serialize(&mut *program_data_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        program_data.pack_into_slice(&mut program_data_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_SET_PROGRAM_DATA, owner_info.key, &old_value, &program_data_info.data.borrow())
     } 
 
     /// process `Initialize` instruction.
@@ -339,7 +642,7 @@ impl Processor {
             return Err(FarmError::NotInitializedProgramData.into());
         }
 
-        let program_data = try_from_slice_unchecked::<FarmProgram>(&farm_program_info.data.borrow())?;
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
 
         // check if this farm account was created by this program with authority and nonce
         // if fail, returns InvalidProgramAddress error
@@ -353,9 +656,8 @@ impl Processor {
             return Err(FarmError::SignatureMissing.into());
         }
 
-        if *creator_info.key != program_data.super_owner {
-            return Err(FarmError::InvalidOwner.into());
-        }
+        // farms created without super owner escrow farm fee and wait for review
+        let is_permissionless = *creator_info.key != program_data.super_owner;
 
         // check if given farm was initialized already
         if !Self::is_zero_account(farm_id_info) {
@@ -367,19 +669,33 @@ impl Processor {
             return Err(FarmError::WrongPeriod.into());
         }
 
-        let token_program_pubkey = Pubkey::from_str(TOKEN_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)?;
+        // native SOL rewards are held as lamports by the farm authority instead of a wSOL account
+        let is_native_reward = *reward_mint_info.key == Pubkey::from_str(SOL_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? &&
+            *pool_reward_token_account_info.key == *authority_info.key;
+
+        // lp token and reward token can be under spl-token or Token-2022 program
+        let token_program_pubkey = *pool_lp_mint_info.owner;
+        let reward_token_program_pubkey = if is_native_reward {
+            system_program::id()
+        } else {
+            *reward_mint_info.owner
+        };
+        Self::assert_token_program(&token_program_pubkey)?;
+        if !is_native_reward {
+            Self::assert_token_program(&reward_token_program_pubkey)?;
+        }
 
-        // token account - check if owner is saved token program
+        // token account - check if owner is same token program with its mint
         if  *pool_lp_token_account_info.owner != token_program_pubkey ||
-            *pool_reward_token_account_info.owner != token_program_pubkey {
+            *pool_reward_token_account_info.owner != reward_token_program_pubkey {
                 return Err(FarmError::InvalidOwner.into());
         }
 
         // borrow lp token mint account data
-        let pool_mint = Mint::unpack_from_slice(&pool_lp_mint_info.data.borrow())?; 
+        let pool_mint = Self::unpack_mint(pool_lp_mint_info)?; 
         
-        let pool_lp_token_data = Account::unpack_from_slice(&pool_lp_token_account_info.data.borrow())?;
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
         
         // token account - check if user token's owner is depositor
         if  pool_lp_token_data.owner != *authority_info.key ||
@@ -411,7 +727,7 @@ impl Processor {
         }
 
         // borrow farm account data to initialize (mutable)
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
         let amm_program_id = program_data.amm_program_id;
 
@@ -442,6 +758,20 @@ impl Processor {
         // if not CRP token pairing,this farm is not allowed until creator pays farm fee
         farm_pool.set_allowed(1);
 
+        if is_permissionless {
+            Self::escrow_listing(
+                &program_data,
+                &mut farm_pool,
+                farm_id_info,
+                authority_info,
+                creator_info,
+                pool_reward_token_account_info,
+                reward_mint_info,
+                nonce,
+                account_info_iter.as_slice(),
+            )?;
+        }
+
         // owner of this farm - creator
         farm_pool.owner = *creator_info.key;
 
@@ -460,6 +790,10 @@ impl Processor {
         // store spl-token program address
         farm_pool.token_program_id = token_program_pubkey;
 
+        // store spl-token program address of reward token
+        farm_pool.reward_token_program_id = reward_token_program_pubkey;
+        farm_pool.native_reward = is_native_reward as u8;
+
         // store reward token mint address
         farm_pool.reward_mint_address = *reward_mint_info.key;
 
@@ -479,9 +813,7 @@ impl Processor {
         farm_pool.end_timestamp = end_timestamp;
         
         // serialize/store this initialized farm again
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
     } 
 
     /// process deposit instruction
@@ -490,6 +822,7 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
+        position_index: u64,
     ) -> ProgramResult {
         msg!("depositing ...");
         // get account informations
@@ -537,6 +870,18 @@ impl Processor {
         let rent_info = next_account_info(account_info_iter)?;
         let system_info = next_account_info(account_info_iter)?;
 
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // associated token account program address to create missing reward token accounts
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // fee owner wallet account information, owner of fee reward ata
+        let fee_owner_info = next_account_info(account_info_iter)?;
+
         msg!("validating ... ");
 
         // check if rent sysvar program id is correct
@@ -564,7 +909,7 @@ impl Processor {
 
         msg!("getting data ... ");
 
-        let program_data = try_from_slice_unchecked::<FarmProgram>(&farm_program_info.data.borrow())?;
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
         
         // get clock from clock sysvar account information
         let clock = &Clock::from_account_info(clock_sysvar_info)?;
@@ -573,45 +918,25 @@ impl Processor {
         let cur_timestamp: u64 = clock.unix_timestamp as u64;
 
         // borrow farm pool account data
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
         if user_info_account_info.data_is_empty() {
-            msg!("creating user info account ... ");
-
-            let seeds = [
-                PREFIX.as_bytes(),
-                farm_id_info.key.as_ref(),
-                depositor_info.key.as_ref(),
-            ];
-
-            let (found_user_info_key, bump) = Pubkey::find_program_address(&seeds, program_id);
-
-            if found_user_info_key != *user_info_account_info.key {
-                return Err(FarmError::InvalidProgramAddress.into());
-            }
-
-            let size = std::mem::size_of::<UserInfo>();
-            // Create account with enough space
-            create_or_allocate_account_raw(
-                *program_id,
-                &user_info_account_info.clone(),
-                &rent_info.clone(),
-                &system_info.clone(),
-                &depositor_info.clone(),
-                size,
-                &[
-                    PREFIX.as_bytes(),
-                    farm_id_info.key.as_ref(),
-                    depositor_info.key.as_ref(),
-                    &[bump],
-                ],
+            Self::create_user_info_account(
+                program_id,
+                farm_id_info.key,
+                depositor_info.key,
+                position_index,
+                user_info_account_info,
+                rent_info,
+                system_info,
+                depositor_info,
             )?;
         }
 
         msg!("getting user data ... ");
 
         // borrow user info for this pool
-        let mut user_info = try_from_slice_unchecked::<UserInfo>(&user_info_account_info.data.borrow())?;
+        let mut user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
 
         msg!("validating user & farm ... ");
 
@@ -654,6 +979,9 @@ impl Processor {
 
             // save user's farm account address
             user_info.farm_id = *farm_id_info.key;
+
+            // save index of user's position
+            user_info.position_index = position_index;
         }
 
         // user info account - check if this is for given farm account
@@ -666,11 +994,34 @@ impl Processor {
             return Err(FarmError::InvalidOwner.into());
         }
 
+        // user info account - check if this is the given position
+        if user_info.position_index != position_index {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // user info account - check if this is the PDA of the position
+        Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+
+        // reward token accounts - create missing associated token accounts of depositor and fee owner
+        Self::create_reward_token_accounts(
+            &farm_pool,
+            &program_data,
+            depositor_info,
+            user_reward_token_account_info,
+            reward_ata_info,
+            reward_mint_info,
+            reward_token_program_info,
+            system_info,
+            rent_info,
+            associated_token_program_info,
+            fee_owner_info,
+        )?;
+
         // token account - check if owner is saved token program
         if  *user_lp_token_account_info.owner != farm_pool.token_program_id ||
             *pool_lp_token_account_info.owner != farm_pool.token_program_id ||
-            *user_reward_token_account_info.owner != farm_pool.token_program_id ||
-            *pool_reward_token_account_info.owner != farm_pool.token_program_id {
+            *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
                 return Err(FarmError::InvalidOwner.into());
         }
 
@@ -682,11 +1033,11 @@ impl Processor {
 
         msg!("getting token informations ... ");
 
-        let user_lp_token_data = Account::unpack_from_slice(&user_lp_token_account_info.data.borrow())?;
-        let pool_lp_token_data = Account::unpack_from_slice(&pool_lp_token_account_info.data.borrow())?;
-        let user_reward_token_data = Account::unpack_from_slice(&user_reward_token_account_info.data.borrow())?;
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
-        let reward_ata_data = Account::unpack_from_slice(&reward_ata_info.data.borrow())?;
+        let user_lp_token_data = Self::unpack_spl_token_account(user_lp_token_account_info)?;
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+        let reward_ata_data = Self::unpack_token_account(reward_ata_info)?;
 
         // farm account - check fee owner
         if program_data.fee_owner != reward_ata_data.owner {
@@ -712,10 +1063,32 @@ impl Processor {
         }
 
         // token program - check if given token program is correct
-        if *token_program_info.key != farm_pool.token_program_id {
+        if  *token_program_info.key != farm_pool.token_program_id ||
+            *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
             return Err(FarmError::InvalidProgramAddress.into());
         }
 
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // receipt - mint and depositor's receipt token account are required if this farm issues receipts
+        let receipt_infos = if farm_pool.has_receipt() {
+            let receipt_mint_info = next_account_info(account_info_iter)?;
+            let user_receipt_token_account_info = next_account_info(account_info_iter)?;
+            Self::assert_receipt_account(&farm_pool, receipt_mint_info, user_receipt_token_account_info, depositor_info.key)?;
+            Some((receipt_mint_info, user_receipt_token_account_info))
+        } else {
+            None
+        };
+
+        // emitter - emitter accounts are required if this farm is registered to the emitter
+        let emitter_infos = Self::next_emitter_infos(&farm_pool, account_info_iter)?;
+
+        // boost - lock accounts are required if this farm boosts rewards
+        let lock_infos = Self::next_lock_infos(&farm_pool, account_info_iter)?;
+
         msg!("updating pool ... ");
 
         //update this pool with up-to-date, distribute reward token 
@@ -726,18 +1099,31 @@ impl Processor {
             pool_reward_token_data.amount,
         )?;
 
+        // pull emission of this farm before deposited balance changes
+        Self::pull_emission(
+            program_id,
+            emitter_infos,
+            reward_mint_info,
+            reward_token_program_info,
+            pool_reward_token_account_info,
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+        )?;
+
         // harvest user's pending rewards
-        if user_info.deposit_balance > 0 {
+        if user_info.has_rewards_to_harvest() {
             msg!("harvesting ... ");
             Self::harvest(
                 &farm_id_info.clone(), 
-                &token_program_info.clone(), 
+                &reward_token_program_info.clone(), 
                 &pool_reward_token_account_info.clone(), 
+                &reward_mint_info.clone(),
                 &reward_ata_info.clone(), 
                 &user_reward_token_account_info.clone(), 
                 &authority_info.clone(), 
                 &program_data, 
-                &farm_pool, 
+                &mut farm_pool, 
                 &mut user_info
             )?;
         }
@@ -751,27 +1137,104 @@ impl Processor {
                 farm_id_info.key,
                 token_program_info.clone(), 
                 user_lp_token_account_info.clone(), 
+                pool_lp_mint_info.clone(),
                 pool_lp_token_account_info.clone(), 
                 depositor_info.clone(), 
                 farm_pool.nonce, 
                 amount
             )?;
 
+            // token extensions like transfer fee can change the amount, so credit what the pool received
+            let amount = Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount
+                .checked_sub(pool_lp_token_data.amount)
+                .ok_or(FarmError::CalculationFailure)?;
+
+            // mint receipt tokens 1:1 with staked lp, receipt account is frozen again after minting
+            if let Some((receipt_mint_info, user_receipt_token_account_info)) = receipt_infos {
+                Self::thaw_receipt_account(
+                    farm_id_info.key,
+                    token_program_info,
+                    user_receipt_token_account_info,
+                    receipt_mint_info,
+                    authority_info,
+                    farm_pool.nonce,
+                )?;
+                Self::token_mint_to(
+                    farm_id_info.key,
+                    token_program_info.clone(),
+                    receipt_mint_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    authority_info.clone(),
+                    farm_pool.nonce,
+                    amount
+                )?;
+                Self::token_freeze_account(
+                    farm_id_info.key,
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    authority_info.clone(),
+                    farm_pool.nonce,
+                )?;
+            }
+
             // update user's deposited balance
+            let old_balance = user_info.deposit_balance;
             user_info.deposit_balance += amount;
+
+            // loyalty clock starts with the first deposit, top-ups keep it
+            if old_balance == 0 {
+                farm_pool.restart_loyalty(&mut user_info, cur_timestamp);
+            }
+
+            // keep balance history for governance snapshots
+            user_info.write_checkpoint(old_balance, cur_timestamp);
+
+            // minimum stake duration restarts from the latest deposit
+            user_info.deposit_timestamp = cur_timestamp;
+
+            // update farm statistics
+            farm_pool.update_staker_count(old_balance, user_info.deposit_balance);
+            farm_pool.total_lp_deposited = farm_pool.total_lp_deposited.saturating_add(amount);
+            farm_pool.last_activity_timestamp = cur_timestamp;
         }
+
+        // refresh effective stake with depositor's CRP lock
+        Self::update_boost(
+            program_id,
+            lock_infos,
+            &mut farm_pool,
+            &mut user_info,
+            Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount,
+            cur_timestamp,
+        )?;
         
         // update reward debt
         user_info.reward_debt = farm_pool.get_new_reward_debt(&user_info)?;
 
+        // extend user info account if it was created with an older layout
+        realloc_account(
+            user_info_account_info,
+            rent_info,
+            system_info,
+            depositor_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+
         // save user's new info to network
-        user_info
-            .serialize(&mut *user_info_account_info.data.borrow_mut())?;
+        user_info.pack_into_slice(&mut user_info_account_info.data.borrow_mut())?;
+
+        // extend farm account to store statistics if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            depositor_info,
+            get_packed_len::<FarmPool>(),
+        )?;
 
         // save new farm account data to network
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
         
     }
 
@@ -824,6 +1287,32 @@ impl Processor {
         // clock account information to use timestamp
         let clock_sysvar_info = next_account_info(account_info_iter)?;
 
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // rent sysvar and system program to extend user info created with an older layout
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // associated token account program address to create missing reward token accounts
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // fee owner wallet account information, owner of fee reward ata
+        let fee_owner_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
         // check if clock sysvar program id is correct
         if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
             return Err(FarmError::InvalidClockSysvarId.into());
@@ -837,7 +1326,7 @@ impl Processor {
             return Err(FarmError::NotInitializedProgramData.into());
         }
 
-        let program_data = try_from_slice_unchecked::<FarmProgram>(&farm_program_info.data.borrow())?;
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
 
         // get clock from clock sysvar account information
         let clock = &Clock::from_account_info(clock_sysvar_info)?;
@@ -845,13 +1334,13 @@ impl Processor {
         // get current timestamp(second)
         let cur_timestamp: u64 = clock.unix_timestamp as u64;
         // borrow farm pool account data
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
         if user_info_account_info.data_is_empty() {
             return Err(FarmError::InvalidProgramAddress.into());
         }
         // borrow user info for this pool
-        let mut user_info = try_from_slice_unchecked::<UserInfo>(&user_info_account_info.data.borrow())?;
+        let mut user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
 
         //singers - check if depositor is signer
         if !withdrawer_info.is_signer {
@@ -885,11 +1374,28 @@ impl Processor {
         if user_info.wallet != *withdrawer_info.key {
             return Err(FarmError::InvalidOwner.into());
         }
+        // user info account - check if this is the PDA of the position
+        Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+        // reward token accounts - create missing associated token accounts of withdrawer and fee owner
+        Self::create_reward_token_accounts(
+            &farm_pool,
+            &program_data,
+            withdrawer_info,
+            user_reward_token_account_info,
+            reward_ata_info,
+            reward_mint_info,
+            reward_token_program_info,
+            system_info,
+            rent_info,
+            associated_token_program_info,
+            fee_owner_info,
+        )?;
+
         // token account - check if owner is saved token program
         if  *user_lp_token_account_info.owner != farm_pool.token_program_id ||
             *pool_lp_token_account_info.owner != farm_pool.token_program_id ||
-            *user_reward_token_account_info.owner != farm_pool.token_program_id ||
-            *pool_reward_token_account_info.owner != farm_pool.token_program_id {
+            *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
                 return Err(FarmError::InvalidOwner.into());
         }
 
@@ -899,11 +1405,11 @@ impl Processor {
                 return Err(FarmError::InvalidOwner.into());
         }
 
-        let user_lp_token_data = Account::unpack_from_slice(&user_lp_token_account_info.data.borrow())?;
-        let pool_lp_token_data = Account::unpack_from_slice(&pool_lp_token_account_info.data.borrow())?;
-        let user_reward_token_data = Account::unpack_from_slice(&user_reward_token_account_info.data.borrow())?;
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
-        let reward_ata_data = Account::unpack_from_slice(&reward_ata_info.data.borrow())?;
+        let user_lp_token_data = Self::unpack_spl_token_account(user_lp_token_account_info)?;
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+        let reward_ata_data = Self::unpack_token_account(reward_ata_info)?;
 
         // farm account - check fee owner
         if program_data.fee_owner != reward_ata_data.owner {
@@ -934,9 +1440,15 @@ impl Processor {
         }
 
         // token program - check if given token program is correct
-        if *token_program_info.key != farm_pool.token_program_id {
+        if  *token_program_info.key != farm_pool.token_program_id ||
+            *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
             return Err(FarmError::InvalidProgramAddress.into());
         }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
         
         // if amount > deposited balance, amount is deposited balance
         let mut _amount = amount;
@@ -949,6 +1461,45 @@ impl Processor {
             return Err(FarmError::ZeroDepositBalance.into());
         }
 
+        // receipt - withdrawer has to burn the same amount of receipt tokens
+        let receipt_infos = if farm_pool.has_receipt() {
+            let receipt_mint_info = next_account_info(account_info_iter)?;
+            let user_receipt_token_account_info = next_account_info(account_info_iter)?;
+            Self::assert_receipt_account(&farm_pool, receipt_mint_info, user_receipt_token_account_info, withdrawer_info.key)?;
+            let user_receipt_token_data = Self::unpack_spl_token_account(user_receipt_token_account_info)?;
+            if user_receipt_token_data.amount < _amount {
+                return Err(FarmError::NotEnoughBalance.into());
+            }
+            Some((receipt_mint_info, user_receipt_token_account_info))
+        } else {
+            None
+        };
+
+        // early withdraw fee - fee owner's lp token account is required if fee is charged
+        let early_withdraw_fee = farm_pool.get_early_withdraw_fee(_amount, &user_info, cur_timestamp)?;
+        let fee_lp_token_account_info = if early_withdraw_fee > 0 {
+            let fee_lp_token_account_info = next_account_info(account_info_iter)?;
+            if *fee_lp_token_account_info.owner != farm_pool.token_program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            let fee_lp_token_data = Self::unpack_spl_token_account(fee_lp_token_account_info)?;
+            if program_data.fee_owner != fee_lp_token_data.owner {
+                return Err(FarmError::InvalidFeeAccount.into());
+            }
+            if fee_lp_token_data.mint != farm_pool.pool_mint_address {
+                return Err(FarmError::WrongAccountMint.into());
+            }
+            Some(fee_lp_token_account_info)
+        } else {
+            None
+        };
+
+        // emitter - emitter accounts are required if this farm is registered to the emitter
+        let emitter_infos = Self::next_emitter_infos(&farm_pool, account_info_iter)?;
+
+        // boost - lock accounts are required if this farm boosts rewards
+        let lock_infos = Self::next_lock_infos(&farm_pool, account_info_iter)?;
+
         //borrow pool lp token mint account data
         //let pool_mint = Mint::unpack_from_slice(&pool_lp_mint_info.data.borrow())?;
 
@@ -960,17 +1511,30 @@ impl Processor {
             pool_reward_token_data.amount,
         )?;
 
+        // pull emission of this farm before deposited balance changes
+        Self::pull_emission(
+            program_id,
+            emitter_infos,
+            reward_mint_info,
+            reward_token_program_info,
+            pool_reward_token_account_info,
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+        )?;
+
         // harvest user's pending rewards
         if user_info.deposit_balance > 0 {
             Self::harvest(
                 &farm_id_info.clone(), 
-                &token_program_info.clone(), 
+                &reward_token_program_info.clone(), 
                 &pool_reward_token_account_info.clone(), 
+                &reward_mint_info.clone(),
                 &reward_ata_info.clone(), 
                 &user_reward_token_account_info.clone(), 
                 &authority_info.clone(), 
                 &program_data, 
-                &farm_pool, 
+                &mut farm_pool, 
                 &mut user_info
             )?;
             
@@ -978,15 +1542,65 @@ impl Processor {
 
         // unstake lp token
         if _amount > 0 {
-            Self::token_transfer(
-                farm_id_info.key,
-                token_program_info.clone(), 
-                pool_lp_token_account_info.clone(),
-                user_lp_token_account_info.clone(), 
-                authority_info.clone(), 
-                farm_pool.nonce, 
-                _amount
-            )?;
+            // transfer early withdraw fee to fee owner
+            if let Some(fee_lp_token_account_info) = fee_lp_token_account_info {
+                Self::token_transfer(
+                    farm_id_info.key,
+                    token_program_info.clone(),
+                    pool_lp_token_account_info.clone(),
+                    pool_lp_mint_info.clone(),
+                    fee_lp_token_account_info.clone(),
+                    authority_info.clone(),
+                    farm_pool.nonce,
+                    early_withdraw_fee
+                )?;
+            }
+
+            let unstake_amount = _amount - early_withdraw_fee;
+            if farm_pool.unbonding_period > 0 {
+                // keep lp in the pool until unbonding period is over
+                user_info.unbonding_amount += unstake_amount;
+                user_info.unbonding_timestamp = cur_timestamp + farm_pool.unbonding_period;
+                farm_pool.total_unbonding_amount += unstake_amount;
+            } else {
+                Self::token_transfer(
+                    farm_id_info.key,
+                    token_program_info.clone(), 
+                    pool_lp_token_account_info.clone(),
+                    pool_lp_mint_info.clone(),
+                    user_lp_token_account_info.clone(), 
+                    authority_info.clone(), 
+                    farm_pool.nonce, 
+                    unstake_amount
+                )?;
+            }
+
+            // burn receipt tokens for unstaked lp, receipt account is frozen again after burning
+            if let Some((receipt_mint_info, user_receipt_token_account_info)) = receipt_infos {
+                Self::thaw_receipt_account(
+                    farm_id_info.key,
+                    token_program_info,
+                    user_receipt_token_account_info,
+                    receipt_mint_info,
+                    authority_info,
+                    farm_pool.nonce,
+                )?;
+                Self::token_burn(
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    withdrawer_info.clone(),
+                    _amount
+                )?;
+                Self::token_freeze_account(
+                    farm_id_info.key,
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    authority_info.clone(),
+                    farm_pool.nonce,
+                )?;
+            }
         }
         
         // store user's wallet address
@@ -996,19 +1610,54 @@ impl Processor {
         user_info.farm_id = *farm_id_info.key;
 
         // update deposited balance
+        let old_balance = user_info.deposit_balance;
         user_info.deposit_balance -= _amount;
 
+        // keep balance history for governance snapshots
+        // user info with an older layout is extended before it's stored
+        if _amount > 0 {
+            user_info.write_checkpoint(old_balance, cur_timestamp);
+        }
+
+        // withdrawing interrupts staking and restarts loyalty clock
+        if _amount > 0 {
+            farm_pool.restart_loyalty(&mut user_info, cur_timestamp);
+        }
+
+        // update farm statistics
+        farm_pool.update_staker_count(old_balance, user_info.deposit_balance);
+        if _amount > 0 {
+            farm_pool.last_activity_timestamp = cur_timestamp;
+        }
+
+        // refresh effective stake with withdrawer's CRP lock
+        Self::update_boost(
+            program_id,
+            lock_infos,
+            &mut farm_pool,
+            &mut user_info,
+            Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount,
+            cur_timestamp,
+        )?;
+
         // update reward debt
         user_info.reward_debt = farm_pool.get_new_reward_debt(&user_info)?;
 
+        // extend user info account if it was created with an older layout
+        // unbonding, boost and loyalty fields don't fit in it
+        realloc_account(
+            user_info_account_info,
+            rent_info,
+            system_info,
+            withdrawer_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+
         // store user's information to network
-        user_info
-            .serialize(&mut *user_info_account_info.data.borrow_mut())?;
+        user_info.pack_into_slice(&mut user_info_account_info.data.borrow_mut())?;
 
         // store farm account data to network
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
         
     }
     /// farm creator can add reward token to his farm
@@ -1046,12 +1695,15 @@ impl Processor {
         // farm program data account info
         let farm_program_info = next_account_info(account_info_iter)?;
 
-        // spl-token program address
+        // spl-token program address of reward token
         let token_program_info = next_account_info(account_info_iter)?;
 
         // clock account information to use timestamp
         let clock_sysvar_info = next_account_info(account_info_iter)?;
 
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
         // check if clock sysvar program id is correct
         if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
             return Err(FarmError::InvalidClockSysvarId.into());
@@ -1073,11 +1725,11 @@ impl Processor {
 
         
         // borrow farm pool account data
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
-        // check if given creator is farm owner
+        // check if given creator is farm owner, anyone can add rewards to open funding farm
         // if not, returns WrongManager error
-        if *creator_info.key != farm_pool.owner {
+        if *creator_info.key != farm_pool.owner && !farm_pool.is_open_funding() {
             return Err(FarmError::WrongManager.into());
         }
 
@@ -1096,9 +1748,14 @@ impl Processor {
             return Err(FarmError::FarmEnded.into());
         }
 
+        // rewards of farm in review are refunded to the creator if it is rejected, so sponsors wait
+        if *creator_info.key != farm_pool.owner && farm_pool.is_in_review() {
+            return Err(FarmError::InReview.into());
+        }
+
         // token account - check if owner is saved token program
-        if  *user_reward_token_account_info.owner != farm_pool.token_program_id ||
-            *pool_reward_token_account_info.owner != farm_pool.token_program_id ||
+        if  *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
             *pool_lp_token_account_info.owner != farm_pool.token_program_id {
                 return Err(FarmError::InvalidOwner.into());
         }
@@ -1109,9 +1766,9 @@ impl Processor {
                 return Err(FarmError::InvalidOwner.into());
         }
 
-        let user_reward_token_data = Account::unpack_from_slice(&user_reward_token_account_info.data.borrow())?;
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
-        let pool_lp_token_data = Account::unpack_from_slice(&pool_lp_token_account_info.data.borrow())?;
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
 
         // token account - check if user token's owner is depositor
         if  user_reward_token_data.owner != *creator_info.key ||
@@ -1131,10 +1788,64 @@ impl Processor {
         }
 
         // token program - check if given token program is correct
-        if *token_program_info.key != farm_pool.token_program_id {
+        if *token_program_info.key != farm_pool.get_reward_token_program_id() {
             return Err(FarmError::InvalidProgramAddress.into());
         }
 
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // sponsor account - contributors of open funding farm are recorded for pro rata refunds
+        let mut sponsor = None;
+        if farm_pool.is_open_funding() {
+            let sponsor_info = next_account_info(account_info_iter)?;
+            let rent_info = next_account_info(account_info_iter)?;
+            let system_info = next_account_info(account_info_iter)?;
+
+            // check if rent sysvar program id is correct
+            if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+                return Err(FarmError::InvalidRentSysvarId.into());
+            }
+
+            // check if system program id is correct
+            if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+                return Err(FarmError::InvalidSystemProgramId.into());
+            }
+
+            // create sponsor account if this wallet contributes first time
+            let seeds = [
+                PREFIX.as_bytes(),
+                SPONSOR_SEED.as_bytes(),
+                farm_id_info.key.as_ref(),
+                creator_info.key.as_ref(),
+            ];
+            let bump = Self::assert_seeds_account(program_id, &seeds, sponsor_info.key)?;
+            if sponsor_info.data_is_empty() {
+                create_or_allocate_account_raw(
+                    *program_id,
+                    sponsor_info,
+                    rent_info,
+                    system_info,
+                    creator_info,
+                    get_packed_len::<Sponsor>(),
+                    &[
+                        PREFIX.as_bytes(),
+                        SPONSOR_SEED.as_bytes(),
+                        farm_id_info.key.as_ref(),
+                        creator_info.key.as_ref(),
+                        &[bump],
+                    ],
+                )?;
+                Sponsor {
+                    farm_id: *farm_id_info.key,
+                    wallet: *creator_info.key,
+                    ..Sponsor::default()
+                }.serialize(&mut *sponsor_info.data.borrow_mut())?;
+            }
+            sponsor = Some((sponsor_info, Self::get_sponsor(program_id, sponsor_info, farm_id_info.key)?));
+        }
 
         // add reward
         if amount > 0 {
@@ -1152,19 +1863,32 @@ impl Processor {
                 farm_id_info.key,
                 token_program_info.clone(), 
                 user_reward_token_account_info.clone(), 
+                reward_mint_info.clone(),
                 pool_reward_token_account_info.clone(), 
                 creator_info.clone(), 
                 farm_pool.nonce, 
                 amount
             )?;
 
+            // token extensions like transfer fee can change the amount, so add what the pool received
+            let amount = Self::unpack_token_account(pool_reward_token_account_info)?.amount
+                .checked_sub(pool_reward_token_data.amount)
+                .ok_or(FarmError::CalculationFailure)?;
+
             farm_pool.remained_reward_amount += amount;
+            farm_pool.total_rewards_added = farm_pool.total_rewards_added.saturating_add(amount);
+
+            // record contribution for pro rata refund
+            if let Some((sponsor_info, sponsor)) = sponsor.as_mut() {
+                sponsor.amount = sponsor.amount.checked_add(amount).ok_or(FarmError::CalculationFailure)?;
+                farm_pool.total_funded = farm_pool.total_funded.saturating_add(amount);
+                farm_pool.total_sponsored = farm_pool.total_sponsored.saturating_add(amount);
+                sponsor.serialize(&mut *sponsor_info.data.borrow_mut())?;
+            }
         }
 
         // store farm pool account data to network
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
         
     }
     /// process PayFarmFee instruction
@@ -1189,7 +1913,7 @@ impl Processor {
         // creator account information who will add reward
         let creator_info = next_account_info(account_info_iter)?;
 
-        // USDC token account in the creator's wallet to pay farm fee as USDC stable coin
+        // token account in the creator's wallet to pay farm fee as USDC stable coin or whitelisted token
         let user_usdc_token_account_info = next_account_info(account_info_iter)?;
 
         // fee owner wallet account to collect all fees
@@ -1198,9 +1922,12 @@ impl Processor {
         // farm program data account info
         let farm_program_info = next_account_info(account_info_iter)?;
 
-        // spl-token program address
+        // spl-token program address of fee token
         let token_program_info = next_account_info(account_info_iter)?;
 
+        // fee token mint account information
+        let fee_mint_info = next_account_info(account_info_iter)?;
+
         // check if given program account is correct
         Self::assert_program_account(program_id, farm_program_info.key)?;
 
@@ -1209,10 +1936,13 @@ impl Processor {
             return Err(FarmError::NotInitializedProgramData.into());
         }
 
-        let program_data = try_from_slice_unchecked::<FarmProgram>(&farm_program_info.data.borrow())?;
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
 
         // borrow farm pool account data
-        let mut farm_pool = try_from_slice_unchecked::<FarmPool>(&farm_id_info.data.borrow())?;
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
 
         // check if given creator is owner of this farm
         // if not, returns WrongManager error
@@ -1231,13 +1961,24 @@ impl Processor {
             return Err(FarmError::InvalidSigner.into());
         }
 
-        // token account - check if owner is saved token program
-        if  *user_usdc_token_account_info.owner != farm_pool.token_program_id {
+        // farm fee of farms created without super owner is escrowed and released by review
+        if farm_pool.is_in_review() {
+            return Err(FarmError::InReview.into());
+        }
+        if farm_pool.listing_state == LISTING_REJECTED {
+            return Err(FarmError::FarmRejected.into());
+        }
+
+        // token program - check if given token program is spl-token or Token-2022 program
+        Self::assert_token_program(token_program_info.key)?;
+
+        // token account - check if owner is given token program
+        if  *user_usdc_token_account_info.owner != *token_program_info.key {
                 return Err(FarmError::InvalidOwner.into());
         }
 
-        let user_usdc_token_data = Account::unpack_from_slice(&user_usdc_token_account_info.data.borrow())?;
-        let usdc_ata_data = Account::unpack_from_slice(&usdc_ata_info.data.borrow())?;
+        let user_usdc_token_data = Self::unpack_spl_token_account(user_usdc_token_account_info)?;
+        let usdc_ata_data = Self::unpack_spl_token_account(usdc_ata_info)?;
 
         // farm account - check fee owner
         if program_data.fee_owner != usdc_ata_data.owner {
@@ -1250,26 +1991,57 @@ impl Processor {
         }
 
         // token account - check if user has enough token amount
-        if user_usdc_token_data.amount < program_data.farm_fee {
+        if user_usdc_token_data.amount < amount {
             return Err(FarmError::NotEnoughBalance.into());
         }
 
-        // token program - check if given token program is correct
-        if *token_program_info.key != farm_pool.token_program_id {
-            return Err(FarmError::InvalidProgramAddress.into());
+        // token account - check if fee mint is the paid token's mint
+        if  user_usdc_token_data.mint != *fee_mint_info.key ||
+            usdc_ata_data.mint != *fee_mint_info.key {
+            return Err(FarmError::WrongAccountMint.into());
         }
 
-        // check if amount is same with FARM FEE
-        // if not, returns InvalidFarmFee error
-        if amount < program_data.farm_fee {
-            return Err(FarmError::InvalidFarmFee.into());
+        if *fee_mint_info.key == Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
+            // check if amount is same with FARM FEE
+            // if not, returns InvalidFarmFee error
+            if amount < program_data.farm_fee {
+                return Err(FarmError::InvalidFarmFee.into());
+            }
+        } else {
+            // whitelisted fee token information
+            let fee_token_info = next_account_info(account_info_iter)?;
+
+            // amm account information to price fee token in USDC
+            let amm_id_info = next_account_info(account_info_iter)?;
+
+            // token a and token b account information of the amm pool
+            let amm_token_a_account_info = next_account_info(account_info_iter)?;
+            let amm_token_b_account_info = next_account_info(account_info_iter)?;
+
+            let fee_token = Self::get_fee_token(program_id, fee_token_info, fee_mint_info.key)?;
+
+            // get USDC value of paid amount from amm pool's reserves
+            let value = Self::get_usdc_value(
+                &program_data,
+                &fee_token,
+                amm_id_info,
+                amm_token_a_account_info,
+                amm_token_b_account_info,
+                amount,
+            )?;
+
+            // check if paid value is in the tolerance band around discounted farm fee
+            fee_token.check_fee_value(value, fee_token.get_discounted_fee(program_data.farm_fee)?)?;
         }
 
+        let old_value = farm_id_info.data.borrow().to_vec();
+
         // transfer fee amount from user's USDC token account to fee owner's account
         Self::token_transfer(
             farm_id_info.key,
             token_program_info.clone(), 
             user_usdc_token_account_info.clone(), 
+            fee_mint_info.clone(),
             usdc_ata_info.clone(), 
             creator_info.clone(), 
             farm_pool.nonce, 
@@ -1280,16 +2052,5225 @@ impl Processor {
         farm_pool.set_allowed(1);
 
         // store farm account data to network
-        farm_pool
-            .serialize(&mut *farm_id_info.data.borrow_mut())
-            .map_err(|e| e.into())
-        
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_PAY_FARM_FEE, creator_info.key, &old_value, &farm_id_info.data.borrow())
     }
 
-    // update pool information with up-to-date, distribute reward token
-    pub fn update_pool<'a>(
-        farm_pool: &mut FarmPool, 
-        cur_timestamp: u64, 
+    /// process EnableReceipt instruction
+    /// farm owner sets receipt token mint before anyone stakes
+    /// after then, deposits mint receipt tokens 1:1 with staked lp and withdrawals burn them
+    /// the farm authority has to be mint and freeze authority of receipt token mint
+    pub fn process_enable_receipt(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("enabling receipt ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to enable receipt
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account needs to be extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // receipt token mint account information
+        let receipt_mint_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // lp token mint account information in the farm pool
+        let pool_lp_mint_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        if farm_pool.has_receipt() {
+            return Err(FarmError::ReceiptAlreadyEnabled.into());
+        }
+
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_mint_address != *pool_lp_mint_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // receipts can be enabled only before anyone stakes,
+        // otherwise existing stakers have no receipt to burn
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        if pool_lp_token_data.amount > 0 {
+            return Err(FarmError::InvalidState.into());
+        }
+
+        // receipt mint - must be a fresh mint under the farm authority
+        if  *receipt_mint_info.owner != farm_pool.token_program_id ||
+            *receipt_mint_info.key == farm_pool.pool_mint_address {
+            return Err(FarmError::InvalidReceiptMint.into());
+        }
+        let pool_mint = Self::unpack_mint(pool_lp_mint_info)?;
+        let receipt_mint = Self::unpack_mint(receipt_mint_info)?;
+        if  receipt_mint.mint_authority != COption::Some(*authority_info.key) ||
+            receipt_mint.decimals != pool_mint.decimals {
+            return Err(FarmError::InvalidReceiptMint.into());
+        }
+        // receipt accounts are kept frozen by the farm authority,
+        // so receipts can move only by TransferReceipt together with the deposit balance
+        if receipt_mint.freeze_authority != COption::Some(*authority_info.key) {
+            return Err(FarmError::InvalidFreezeAuthority.into());
+        }
+        if receipt_mint.supply != 0 {
+            return Err(FarmError::InvalidSupply.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.receipt_mint = *receipt_mint_info.key;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process TransferReceipt instruction
+    /// moves receipt tokens together with the deposit balance,
+    /// pending rewards of both users are settled so reward debt follows the receipt
+    pub fn process_transfer_receipt(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        receiver_position_index: u64,
+    ) -> ProgramResult {
+        msg!("transferring receipt ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // sender's wallet account information
+        let sender_info = next_account_info(account_info_iter)?;
+
+        // sender's user account information
+        let sender_user_info_account_info = next_account_info(account_info_iter)?;
+
+        // receipt token account information in the sender's wallet
+        let sender_receipt_token_account_info = next_account_info(account_info_iter)?;
+
+        // receiver's wallet account information
+        let receiver_info = next_account_info(account_info_iter)?;
+
+        // receiver's user account information, created if empty
+        let receiver_user_info_account_info = next_account_info(account_info_iter)?;
+
+        // receipt token account information in the receiver's wallet
+        let receiver_receipt_token_account_info = next_account_info(account_info_iter)?;
+
+        // receipt token mint account information
+        let receipt_mint_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // emitter - emitter accounts, reward mint and reward token program are required if this farm is registered to the emitter
+        let emitter_infos = Self::next_emitter_infos(&farm_pool, account_info_iter)?;
+        let emission_infos = match emitter_infos {
+            Some(_) => {
+                let reward_mint_info = next_account_info(account_info_iter)?;
+                let reward_token_program_info = next_account_info(account_info_iter)?;
+                Some((reward_mint_info, reward_token_program_info))
+            }
+            None => None,
+        };
+
+        // boost - voters of both users and gauge controller are required if this farm boosts rewards
+        let (sender_lock_infos, receiver_lock_infos) = if farm_pool.is_boosted() {
+            let sender_voter_info = next_account_info(account_info_iter)?;
+            let receiver_voter_info = next_account_info(account_info_iter)?;
+            let gauge_controller_info = next_account_info(account_info_iter)?;
+            (Some((sender_voter_info, gauge_controller_info)), Some((receiver_voter_info, gauge_controller_info)))
+        } else {
+            (None, None)
+        };
+
+        //singers - check if sender is signer
+        if !sender_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // sender and receiver must be different users
+        if *sender_info.key == *receiver_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // farm account - check if this farm was allowed already
+        if !farm_pool.is_allowed() {
+            return Err(FarmError::NotAllowed.into());
+        }
+
+        if !farm_pool.has_receipt() {
+            return Err(FarmError::ReceiptNotEnabled.into());
+        }
+
+        if receiver_user_info_account_info.data_is_empty() {
+            Self::create_user_info_account(
+                program_id,
+                farm_id_info.key,
+                receiver_info.key,
+                receiver_position_index,
+                receiver_user_info_account_info,
+                rent_info,
+                system_info,
+                sender_info,
+            )?;
+        }
+
+        // user info account - check if user info account's owner is program id
+        if  sender_user_info_account_info.owner != program_id ||
+            receiver_user_info_account_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let mut sender_user_info = UserInfo::unpack_from_slice(&sender_user_info_account_info.data.borrow())?;
+        let mut receiver_user_info = UserInfo::unpack_from_slice(&receiver_user_info_account_info.data.borrow())?;
+
+        // user info account - check if receiver is new user
+        if Self::is_zero_account(receiver_user_info_account_info) {
+            receiver_user_info.wallet = *receiver_info.key;
+            receiver_user_info.farm_id = *farm_id_info.key;
+            receiver_user_info.position_index = receiver_position_index;
+        }
+
+        // user info account - check if these user infos are for given farm and users
+        if  sender_user_info.farm_id != *farm_id_info.key ||
+            receiver_user_info.farm_id != *farm_id_info.key ||
+            sender_user_info.wallet != *sender_info.key ||
+            receiver_user_info.wallet != *receiver_info.key ||
+            receiver_user_info.position_index != receiver_position_index {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // user info account - check if these are the PDAs of the positions
+        Self::assert_user_info_account(program_id, sender_user_info_account_info, &sender_user_info)?;
+        Self::assert_user_info_account(program_id, receiver_user_info_account_info, &receiver_user_info)?;
+
+        Self::assert_receipt_account(&farm_pool, receipt_mint_info, sender_receipt_token_account_info, sender_info.key)?;
+        Self::assert_receipt_account(&farm_pool, receipt_mint_info, receiver_receipt_token_account_info, receiver_info.key)?;
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token program is correct
+        if *token_program_info.key != farm_pool.token_program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let sender_receipt_token_data = Self::unpack_spl_token_account(sender_receipt_token_account_info)?;
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        // check if sender has enough staked balance and receipt tokens
+        if  amount == 0 ||
+            sender_user_info.deposit_balance < amount ||
+            sender_receipt_token_data.amount < amount {
+            return Err(FarmError::NotEnoughBalance.into());
+        }
+
+        //update this pool with up-to-date, distribute reward token
+        Self::update_pool(
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+            pool_reward_token_data.amount,
+        )?;
+
+        // pull emission of this farm before deposit balances change
+        if let Some((reward_mint_info, reward_token_program_info)) = emission_infos {
+            Self::pull_emission(
+                program_id,
+                emitter_infos,
+                reward_mint_info,
+                reward_token_program_info,
+                pool_reward_token_account_info,
+                &mut farm_pool,
+                cur_timestamp,
+                pool_lp_token_data.amount,
+            )?;
+        }
+
+        // settle - each user keeps pending rewards earned until now as reward credit
+        let sender_pending = farm_pool.pending_rewards(&mut sender_user_info)?;
+        let receiver_pending = farm_pool.pending_rewards(&mut receiver_user_info)?;
+
+        let sender_old_balance = sender_user_info.deposit_balance;
+        let receiver_old_balance = receiver_user_info.deposit_balance;
+        sender_user_info.deposit_balance -= amount;
+        receiver_user_info.deposit_balance += amount;
+
+        // keep balance history for governance snapshots
+        sender_user_info.write_checkpoint(sender_old_balance, cur_timestamp);
+        receiver_user_info.write_checkpoint(receiver_old_balance, cur_timestamp);
+
+        // sending stake restarts sender's loyalty clock, receiver keeps his clock if he staked already
+        farm_pool.restart_loyalty(&mut sender_user_info, cur_timestamp);
+        if receiver_old_balance == 0 {
+            farm_pool.restart_loyalty(&mut receiver_user_info, cur_timestamp);
+        }
+
+        farm_pool.update_staker_count(sender_old_balance, sender_user_info.deposit_balance);
+        farm_pool.update_staker_count(receiver_old_balance, receiver_user_info.deposit_balance);
+
+        // refresh effective stakes with CRP locks of both users
+        Self::update_boost(
+            program_id,
+            sender_lock_infos,
+            &mut farm_pool,
+            &mut sender_user_info,
+            pool_lp_token_data.amount,
+            cur_timestamp,
+        )?;
+        Self::update_boost(
+            program_id,
+            receiver_lock_infos,
+            &mut farm_pool,
+            &mut receiver_user_info,
+            pool_lp_token_data.amount,
+            cur_timestamp,
+        )?;
+
+        farm_pool.keep_pending_rewards(&mut sender_user_info, sender_pending)?;
+        farm_pool.keep_pending_rewards(&mut receiver_user_info, receiver_pending)?;
+
+        // move receipt tokens from sender to receiver
+        // receipt accounts are frozen outside the farm, thaw both only for this transfer
+        for receipt_token_account_info in [sender_receipt_token_account_info, receiver_receipt_token_account_info] {
+            Self::thaw_receipt_account(
+                farm_id_info.key,
+                token_program_info,
+                receipt_token_account_info,
+                receipt_mint_info,
+                authority_info,
+                farm_pool.nonce,
+            )?;
+        }
+        Self::token_transfer(
+            farm_id_info.key,
+            token_program_info.clone(),
+            sender_receipt_token_account_info.clone(),
+            receipt_mint_info.clone(),
+            receiver_receipt_token_account_info.clone(),
+            sender_info.clone(),
+            farm_pool.nonce,
+            amount
+        )?;
+        for receipt_token_account_info in [sender_receipt_token_account_info, receiver_receipt_token_account_info] {
+            Self::token_freeze_account(
+                farm_id_info.key,
+                token_program_info.clone(),
+                receipt_token_account_info.clone(),
+                receipt_mint_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+            )?;
+        }
+
+        // extend user info accounts to store checkpoints if they were created with an older layout
+        realloc_account(
+            sender_user_info_account_info,
+            rent_info,
+            system_info,
+            sender_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+        realloc_account(
+            receiver_user_info_account_info,
+            rent_info,
+            system_info,
+            sender_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+
+        // save users' new info to network
+        sender_user_info.pack_into_slice(&mut sender_user_info_account_info.data.borrow_mut())?;
+        receiver_user_info.pack_into_slice(&mut receiver_user_info_account_info.data.borrow_mut())?;
+
+        // save new farm account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process HarvestMany instruction
+    /// harvests user's pending rewards from several farms at once
+    /// farms which fail validation are skipped and reported with their error code
+    /// failed token transfers and invalid emitter or lock accounts abort the whole batch
+    pub fn process_harvest_many(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("harvesting many ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // harvester's wallet account information
+        let harvester_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // rent sysvar and system program to extend user infos created with an older layout
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // associated token account program address to create missing reward token accounts
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // fee owner wallet account information, owner of fee reward ata
+        let fee_owner_info = next_account_info(account_info_iter)?;
+
+        // remaining accounts are grouped by farm
+        let mut farm_accounts = account_info_iter.as_slice();
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        //singers - check if harvester is signer
+        if !harvester_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        if farm_accounts.is_empty() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        let mut results: Vec<HarvestResult> = Vec::with_capacity(MAX_HARVEST_MANY_FARMS);
+
+        while !farm_accounts.is_empty() {
+            if results.len() == MAX_HARVEST_MANY_FARMS {
+                return Err(FarmError::TooManyFarms.into());
+            }
+
+            if farm_accounts.len() < HARVEST_MANY_ACCOUNTS {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+
+            let (farm_account_infos, rest) = farm_accounts.split_at(HARVEST_MANY_ACCOUNTS);
+            let farm_id_info = &farm_account_infos[0];
+            let authority_info = &farm_account_infos[1];
+            let user_info_account_info = &farm_account_infos[2];
+            let pool_lp_token_account_info = &farm_account_infos[3];
+            let pool_reward_token_account_info = &farm_account_infos[4];
+            let user_reward_token_account_info = &farm_account_infos[5];
+            let reward_ata_info = &farm_account_infos[6];
+            let reward_mint_info = &farm_account_infos[7];
+            let token_program_info = &farm_account_infos[8];
+
+            msg!("harvesting farm {} ...", farm_id_info.key);
+
+            // farm account - optional accounts of this farm depend on farm account data,
+            // so remaining accounts can't be read if it is not a farm account
+            if farm_id_info.owner != program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            let layout_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+            // emitter and lock accounts follow fixed accounts of this farm as in Deposit instruction
+            let optional_info_iter = &mut rest.iter();
+            let emitter_infos = Self::next_emitter_infos(&layout_pool, optional_info_iter)?;
+            let lock_infos = Self::next_lock_infos(&layout_pool, optional_info_iter)?;
+            farm_accounts = optional_info_iter.as_slice();
+
+            // nothing is transferred or written before this validation passes,
+            // so an invalid farm can be skipped safely
+            // missing reward token accounts are created only for the farm's reward mint
+            let prepared = Self::create_reward_token_accounts(
+                &layout_pool,
+                &program_data,
+                harvester_info,
+                user_reward_token_account_info,
+                reward_ata_info,
+                reward_mint_info,
+                token_program_info,
+                system_info,
+                rent_info,
+                associated_token_program_info,
+                fee_owner_info,
+            ).and_then(|_| Self::prepare_harvest(
+                program_id,
+                &program_data,
+                harvester_info.key,
+                cur_timestamp,
+                farm_account_infos,
+                true,
+            ));
+            let (mut farm_pool, mut user_info) = match prepared {
+                Ok(prepared) => prepared,
+                Err(error) => {
+                    msg!("skipped farm {}", farm_id_info.key);
+                    results.push(HarvestResult {
+                        farm_id: *farm_id_info.key,
+                        status: u64::from(error),
+                        amount: 0,
+                        fee: 0,
+                    });
+                    continue;
+                }
+            };
+
+            let lp_balance = Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount;
+
+            // pull emission of this farm before harvesting
+            Self::pull_emission(
+                program_id,
+                emitter_infos,
+                reward_mint_info,
+                token_program_info,
+                pool_reward_token_account_info,
+                &mut farm_pool,
+                cur_timestamp,
+                lp_balance,
+            )?;
+
+            let (amount, fee) = Self::harvest(
+                farm_id_info,
+                token_program_info,
+                pool_reward_token_account_info,
+                reward_mint_info,
+                reward_ata_info,
+                user_reward_token_account_info,
+                authority_info,
+                &program_data,
+                &mut farm_pool,
+                &mut user_info
+            )?;
+
+            // refresh effective stake with harvester's CRP lock
+            Self::update_boost(
+                program_id,
+                lock_infos,
+                &mut farm_pool,
+                &mut user_info,
+                lp_balance,
+                cur_timestamp,
+            )?;
+
+            // update reward debt
+            user_info.reward_debt = farm_pool.get_new_reward_debt(&user_info)?;
+
+            // extend user info account if it was created with an older layout
+            realloc_account(
+                user_info_account_info,
+                rent_info,
+                system_info,
+                harvester_info,
+                get_packed_len::<UserInfo>(),
+            )?;
+
+            // save user's new info to network
+            user_info.pack_into_slice(&mut user_info_account_info.data.borrow_mut())?;
+
+            // save new farm account data to network
+            farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())?;
+
+            results.push(HarvestResult {
+                farm_id: *farm_id_info.key,
+                status: 0,
+                amount,
+                fee,
+            });
+        }
+
+        // report per-farm results
+        set_return_data(&results.try_to_vec()?);
+        Ok(())
+    }
+
+    /// validate accounts of a farm in HarvestMany instruction and update the farm pool
+    /// returns up-to-date farm pool and user info of the harvester
+    fn prepare_harvest(
+        program_id: &Pubkey,
+        program_data: &FarmProgram,
+        harvester: &Pubkey,
+        cur_timestamp: u64,
+        farm_account_infos: &[AccountInfo],
+        require_deposit: bool,
+    ) -> Result<(FarmPool, UserInfo), ProgramError> {
+        let farm_id_info = &farm_account_infos[0];
+        let authority_info = &farm_account_infos[1];
+        let user_info_account_info = &farm_account_infos[2];
+        let pool_lp_token_account_info = &farm_account_infos[3];
+        let pool_reward_token_account_info = &farm_account_infos[4];
+        let user_reward_token_account_info = &farm_account_infos[5];
+        let reward_ata_info = &farm_account_infos[6];
+        let reward_mint_info = &farm_account_infos[7];
+        let token_program_info = &farm_account_infos[8];
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // farm account - check if this farm was allowed already
+        if !farm_pool.is_allowed() {
+            return Err(FarmError::NotAllowed.into());
+        }
+
+        // farm account - This farm was not started yet
+        if cur_timestamp < farm_pool.start_timestamp {
+            return Err(FarmError::NotStarted.into());
+        }
+
+        // user info account - check if user info account's owner is program id
+        if user_info_account_info.owner != program_id || user_info_account_info.data_is_empty() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow user info for this pool
+        let user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
+
+        // user info account - check if this user info is for given farm and harvester
+        if  user_info.farm_id != *farm_id_info.key ||
+            user_info.wallet != *harvester {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // user info account - check if this is the PDA of the position
+        Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+
+        if require_deposit && !user_info.has_rewards_to_harvest() {
+            return Err(FarmError::ZeroDepositBalance.into());
+        }
+
+        // token program - check if given token program is reward token's program
+        if *token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token account - check if owner is saved token program
+        if  *pool_lp_token_account_info.owner != farm_pool.token_program_id ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *reward_ata_info.owner != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let reward_ata_data = Self::unpack_token_account(reward_ata_info)?;
+
+        // farm account - check fee owner
+        if program_data.fee_owner != reward_ata_data.owner {
+            return Err(FarmError::InvalidFeeAccount.into());
+        }
+
+        // token account - check if user reward token account is harvester's
+        if user_reward_token_data.owner != *harvester {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token account - check reward token mint
+        if  user_reward_token_data.mint != farm_pool.reward_mint_address ||
+            reward_ata_data.mint != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        //update this pool with up-to-date, distribute reward token
+        Self::update_pool(
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+            pool_reward_token_data.amount,
+        )?;
+
+        Ok((farm_pool, user_info))
+    }
+
+    /// process ViewPendingRewards instruction
+    /// runs update_pool on a copy of the farm at current time and returns the result without any writes
+    pub fn process_view_pending_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("viewing pending rewards ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to view
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // user account information to include deposited balance, reward debt
+        let user_info_account_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // copy of farm pool account data, never stored
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        //update the copy of this pool with up-to-date
+        Self::update_pool(
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+            pool_reward_token_data.amount,
+        )?;
+
+        // emitter - emitter accounts are optional, emission of the emitter is included if they are given
+        let mut pool_reward_amount = pool_reward_token_data.amount;
+        let emitter_infos = if account_info_iter.as_slice().is_empty() {
+            None
+        } else {
+            Self::next_emitter_infos(&farm_pool, account_info_iter)?
+        };
+        if let Some((emitter_info, _, emitter_vault_info)) = emitter_infos {
+            let mut emitter = Self::get_emitter(program_id, emitter_info)?;
+
+            // emitter account - check emitter vault and reward token
+            if  emitter.reward_vault != *emitter_vault_info.key ||
+                emitter.reward_mint != farm_pool.reward_mint_address {
+                return Err(FarmError::InvalidEmitter.into());
+            }
+
+            // the copy of this pool gets emission as if it was pulled now
+            emitter.update(cur_timestamp)?;
+            let emission = emitter.get_farm_emission(&farm_pool)?;
+            let lp_balance = farm_pool.get_total_stake(pool_lp_token_data.amount);
+            let vault_amount = Self::unpack_spl_token_account(emitter_vault_info)?.amount;
+            let delivered = farm_pool.take_emission(emission, lp_balance, vault_amount);
+            if delivered > 0 {
+                farm_pool.add_emission(delivered, lp_balance)?;
+                pool_reward_amount = pool_reward_amount.saturating_add(delivered);
+            }
+        }
+
+        // user may not have deposited yet
+        let mut pending_rewards = 0;
+        if !user_info_account_info.data_is_empty() {
+            // user info account - check if user info account's owner is program id
+            if user_info_account_info.owner != program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+
+            let mut user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
+
+            // user info account - check if this is for given farm account
+            if user_info.farm_id != *farm_id_info.key {
+                return Err(FarmError::InvalidOwner.into());
+            }
+
+            // user info account - check if this is the PDA of the position
+            Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+
+            if user_info.has_rewards_to_harvest() {
+                // harvest can't pay more than the reward token account has
+                pending_rewards = farm_pool.pending_rewards(&mut user_info)?.min(pool_reward_amount);
+            }
+        }
+
+        let view = PendingRewardsView {
+            pending_rewards,
+            reward_per_second: farm_pool.get_reward_per_second(),
+            remained_reward_amount: farm_pool.remained_reward_amount,
+            total_lp_amount: pool_lp_token_data.amount.saturating_sub(farm_pool.total_unbonding_amount),
+            reward_per_share_net: farm_pool.reward_per_share_net,
+            timestamp: cur_timestamp,
+        };
+
+        set_return_data(&view.try_to_vec()?);
+        Ok(())
+    }
+
+    /// process ViewStakeAt instruction
+    /// returns user's deposit balance at a past time from checkpoints without any writes
+    pub fn process_view_stake_at(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        timestamp: u64,
+    ) -> ProgramResult {
+        msg!("viewing stake snapshot ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to view
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // user account information to read checkpoints
+        let user_info_account_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // balance in current second can still change by deposit in the same block
+        if timestamp >= cur_timestamp {
+            return Err(FarmError::SnapshotNotFinalized.into());
+        }
+
+        // user may not have deposited yet
+        let mut view = StakeSnapshotView {
+            farm_id: *farm_id_info.key,
+            timestamp,
+            ..StakeSnapshotView::default()
+        };
+        if !user_info_account_info.data_is_empty() {
+            // user info account - check if user info account's owner is program id
+            if user_info_account_info.owner != program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+
+            let user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
+
+            // user info account - check if this is for given farm account
+            if user_info.farm_id != *farm_id_info.key {
+                return Err(FarmError::InvalidOwner.into());
+            }
+
+            // user info account - check if this is the PDA of the position
+            Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+
+            view.wallet = user_info.wallet;
+            view.balance = user_info.get_stake_at(timestamp)?;
+        }
+
+        set_return_data(&view.try_to_vec()?);
+        Ok(())
+    }
+
+    /// process ExtendFarm instruction
+    /// reallocates a farm account created with an older layout to the current size
+    /// anyone can pay the rent for it
+    pub fn process_extend_farm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("extending farm ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to extend
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // payer account information who pays rent
+        let payer_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        //singers - check if payer is signer
+        if !payer_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // check if given farm was initialized already
+        if Self::is_zero_account(farm_id_info) {
+            return Err(FarmError::NotInitialized.into());
+        }
+
+        // check if this is farm account data
+        FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            payer_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        // remaining accounts are user info accounts of this farm to extend
+        for user_info_account_info in account_info_iter {
+            if user_info_account_info.owner != program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            let user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
+            if user_info.farm_id != *farm_id_info.key {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+            realloc_account(
+                user_info_account_info,
+                rent_info,
+                system_info,
+                payer_info,
+                get_packed_len::<UserInfo>(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// process SetLockupOptions instruction
+    /// farm owner sets minimum stake duration with early withdraw fee and unbonding period
+    pub fn process_set_lockup_options(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_stake_duration: u64,
+        early_withdraw_fee_numerator: u64,
+        early_withdraw_fee_denominator: u64,
+        unbonding_period: u64,
+    ) -> ProgramResult {
+        msg!("setting lockup options ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to set options
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // early withdraw fee must be less than withdrawn amount
+        if early_withdraw_fee_numerator > 0 && early_withdraw_fee_numerator >= early_withdraw_fee_denominator {
+            return Err(FarmError::FeeTooHigh.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.min_stake_duration = min_stake_duration;
+        farm_pool.early_withdraw_fee_numerator = early_withdraw_fee_numerator;
+        farm_pool.early_withdraw_fee_denominator = early_withdraw_fee_denominator;
+        farm_pool.unbonding_period = unbonding_period;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process ClaimUnbonded instruction
+    /// transfers withdrawn lp to user after unbonding period
+    pub fn process_claim_unbonded(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("claiming unbonded lp ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to claim
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // withdrawer's wallet account information
+        let withdrawer_info = next_account_info(account_info_iter)?;
+
+        // withdrawer's user account information
+        let user_info_account_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the withdrawer's wallet
+        let user_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // lp token mint account information
+        let pool_lp_mint_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // pool mint - check if pool mint is current program's mint address
+        if *pool_lp_mint_info.key != farm_pool.pool_mint_address {
+            return Err(FarmError::WrongPoolMint.into());
+        }
+
+        //singers - check if withdrawer is signer
+        if !withdrawer_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // user info account - check if user info account's owner is program id
+        if user_info_account_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let mut user_info = UserInfo::unpack_from_slice(&user_info_account_info.data.borrow())?;
+
+        // user info account - check if this user info is for given farm and withdrawer
+        if  user_info.farm_id != *farm_id_info.key ||
+            user_info.wallet != *withdrawer_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // user info account - check if this is the PDA of the position
+        Self::assert_user_info_account(program_id, user_info_account_info, &user_info)?;
+
+        if user_info.unbonding_amount == 0 {
+            return Err(FarmError::NothingToClaim.into());
+        }
+
+        if cur_timestamp < user_info.unbonding_timestamp {
+            return Err(FarmError::StillUnbonding.into());
+        }
+
+        // token account - check if owner is saved token program
+        if  *user_lp_token_account_info.owner != farm_pool.token_program_id ||
+            *pool_lp_token_account_info.owner != farm_pool.token_program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token account - check if pool lp token account is for given farm account
+        if farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        let user_lp_token_data = Self::unpack_spl_token_account(user_lp_token_account_info)?;
+        if user_lp_token_data.owner != *withdrawer_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token program - check if given token program is correct
+        if *token_program_info.key != farm_pool.token_program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let amount = user_info.unbonding_amount;
+        Self::token_transfer(
+            farm_id_info.key,
+            token_program_info.clone(),
+            pool_lp_token_account_info.clone(),
+            pool_lp_mint_info.clone(),
+            user_lp_token_account_info.clone(),
+            authority_info.clone(),
+            farm_pool.nonce,
+            amount
+        )?;
+
+        user_info.unbonding_amount = 0;
+        user_info.unbonding_timestamp = 0;
+        farm_pool.total_unbonding_amount = farm_pool.total_unbonding_amount.saturating_sub(amount);
+
+        // store user's information to network
+        user_info.pack_into_slice(&mut user_info_account_info.data.borrow_mut())?;
+
+        // store farm account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// escrow farm fee and minimum reward deposit of a farm created without super owner
+    /// the farm is not allowed until review window is over
+    fn escrow_listing<'a>(
+        program_data: &FarmProgram,
+        farm_pool: &mut FarmPool,
+        farm_id_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        creator_info: &AccountInfo<'a>,
+        pool_reward_token_account_info: &AccountInfo<'a>,
+        reward_mint_info: &AccountInfo<'a>,
+        nonce: u8,
+        listing_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        let account_info_iter = &mut listing_accounts.iter();
+
+        // USDC token account in the creator's wallet to pay farm fee
+        let user_usdc_token_account_info = next_account_info(account_info_iter)?;
+
+        // USDC token account owned by farm authority to escrow farm fee
+        let escrow_fee_account_info = next_account_info(account_info_iter)?;
+
+        // USDC mint account information
+        let usdc_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of USDC
+        let fee_token_program_info = next_account_info(account_info_iter)?;
+
+        // reward token account in the creator's wallet to deposit minimum reward
+        let user_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // token program - check if given token programs are owners of the mints
+        if  *usdc_mint_info.owner != *fee_token_program_info.key ||
+            *reward_mint_info.owner != *reward_token_program_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // fee mint - farm fee is escrowed in USDC
+        if *usdc_mint_info.key != Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token account - check if owner is given token program
+        if  *escrow_fee_account_info.owner != *fee_token_program_info.key ||
+            *user_usdc_token_account_info.owner != *fee_token_program_info.key ||
+            *user_reward_token_account_info.owner != *reward_token_program_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let escrow_fee_data = Self::unpack_spl_token_account(escrow_fee_account_info)?;
+
+        // token account - check if escrow account is owned by farm authority only
+        if  escrow_fee_data.owner != *authority_info.key ||
+            escrow_fee_data.mint != *usdc_mint_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if escrow_fee_data.delegate.is_some() {
+            return Err(FarmError::InvalidDelegate.into());
+        }
+        if escrow_fee_data.close_authority.is_some() {
+            return Err(FarmError::InvalidCloseAuthority.into());
+        }
+
+        msg!("escrowing farm fee ... ");
+        Self::token_transfer(
+            farm_id_info.key,
+            fee_token_program_info.clone(),
+            user_usdc_token_account_info.clone(),
+            usdc_mint_info.clone(),
+            escrow_fee_account_info.clone(),
+            creator_info.clone(),
+            nonce,
+            program_data.farm_fee
+        )?;
+
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        msg!("depositing minimum reward ... ");
+        Self::token_transfer(
+            farm_id_info.key,
+            reward_token_program_info.clone(),
+            user_reward_token_account_info.clone(),
+            reward_mint_info.clone(),
+            pool_reward_token_account_info.clone(),
+            creator_info.clone(),
+            nonce,
+            program_data.min_reward_deposit
+        )?;
+
+        // token extensions like transfer fee can change the amount, so add what the pool received
+        let reward_amount = Self::unpack_token_account(pool_reward_token_account_info)?.amount
+            .checked_sub(pool_reward_token_data.amount)
+            .ok_or(FarmError::CalculationFailure)?;
+
+        farm_pool.remained_reward_amount = reward_amount;
+        farm_pool.total_rewards_added = reward_amount;
+
+        // this farm is not allowed until review window is over
+        farm_pool.set_allowed(0);
+        farm_pool.listing_state = LISTING_PENDING;
+        farm_pool.escrow_fee_account = *escrow_fee_account_info.key;
+        farm_pool.escrow_fee_amount = Self::unpack_spl_token_account(escrow_fee_account_info)?.amount
+            .checked_sub(escrow_fee_data.amount)
+            .ok_or(FarmError::CalculationFailure)?;
+        farm_pool.review_end_timestamp = cur_timestamp
+            .checked_add(program_data.review_period)
+            .ok_or(FarmError::CalculationFailure)?;
+
+        Ok(())
+    }
+
+    /// process SetListingOptions instruction
+    /// super owner sets review window and minimum reward deposit of farms created without him
+    pub fn process_set_listing_options(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        review_period: u64,
+        min_reward_deposit: u64,
+    ) -> ProgramResult {
+        msg!("setting listing options ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm program data account info
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent if program data account is extended
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, program_data_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(program_data_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let mut program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let old_value = program_data_info.data.borrow().to_vec();
+
+        // extend program data account if it was created with an older layout
+        realloc_account(
+            program_data_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<FarmProgram>(),
+        )?;
+
+        program_data.review_period = review_period;
+        program_data.min_reward_deposit = min_reward_deposit;
+
+        // store program data to network
+        program_data.pack_into_slice(&mut program_data_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_SET_LISTING_OPTIONS, super_owner_info.key, &old_value, &program_data_info.data.borrow())
+    }
+
+    /// process RejectFarm instruction
+    /// super owner rejects a farm in review window, escrowed fee and rewards are refunded to the creator
+    pub fn process_reject_farm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("rejecting farm ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to reject
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // super owner account information
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // USDC token account escrowing farm fee
+        let escrow_fee_account_info = next_account_info(account_info_iter)?;
+
+        // USDC token account in the creator's wallet to refund farm fee
+        let user_usdc_token_account_info = next_account_info(account_info_iter)?;
+
+        // USDC mint account information
+        let usdc_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of USDC
+        let fee_token_program_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account in the creator's wallet to refund rewards
+        let user_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        if !farm_pool.is_in_review() {
+            return Err(FarmError::NotInReview.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+        if cur_timestamp > farm_pool.review_end_timestamp {
+            return Err(FarmError::ReviewWindowClosed.into());
+        }
+
+        // token account - check if escrow and reward vault are this farm's
+        if  farm_pool.escrow_fee_account != *escrow_fee_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token programs are saved ones
+        if  *escrow_fee_account_info.owner != *fee_token_program_info.key ||
+            *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        let escrow_fee_data = Self::unpack_spl_token_account(escrow_fee_account_info)?;
+        let user_usdc_token_data = Self::unpack_spl_token_account(user_usdc_token_account_info)?;
+        let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        // token account - check if refund accounts are the creator's
+        if  user_usdc_token_data.owner != farm_pool.owner ||
+            user_reward_token_data.owner != farm_pool.owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token account - check token mints
+        if  escrow_fee_data.mint != *usdc_mint_info.key ||
+            user_usdc_token_data.mint != *usdc_mint_info.key ||
+            user_reward_token_data.mint != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // refund escrowed farm fee
+        Self::token_transfer(
+            farm_id_info.key,
+            fee_token_program_info.clone(),
+            escrow_fee_account_info.clone(),
+            usdc_mint_info.clone(),
+            user_usdc_token_account_info.clone(),
+            authority_info.clone(),
+            farm_pool.nonce,
+            escrow_fee_data.amount
+        )?;
+
+        // nobody staked in review, so all rewards in the vault are the creator's
+        Self::token_transfer(
+            farm_id_info.key,
+            reward_token_program_info.clone(),
+            pool_reward_token_account_info.clone(),
+            reward_mint_info.clone(),
+            user_reward_token_account_info.clone(),
+            authority_info.clone(),
+            farm_pool.nonce,
+            pool_reward_token_data.amount
+        )?;
+
+        let old_value = farm_id_info.data.borrow().to_vec();
+
+        farm_pool.remained_reward_amount = 0;
+        farm_pool.escrow_fee_amount = 0;
+        farm_pool.listing_state = LISTING_REJECTED;
+        farm_pool.set_allowed(0);
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_REJECT_FARM, super_owner_info.key, &old_value, &farm_id_info.data.borrow())
+    }
+
+    /// process FinalizeListing instruction
+    /// after review window, anyone can release escrowed fee to fee owner and allow the farm
+    pub fn process_finalize_listing(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("finalizing listing ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to list
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // USDC token account escrowing farm fee
+        let escrow_fee_account_info = next_account_info(account_info_iter)?;
+
+        // fee owner's USDC token account
+        let usdc_ata_info = next_account_info(account_info_iter)?;
+
+        // USDC mint account information
+        let usdc_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of USDC
+        let fee_token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        if !farm_pool.is_in_review() {
+            return Err(FarmError::NotInReview.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+        if cur_timestamp <= farm_pool.review_end_timestamp {
+            return Err(FarmError::InReview.into());
+        }
+
+        // token account - check if escrow is this farm's
+        if farm_pool.escrow_fee_account != *escrow_fee_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token program is escrow's owner
+        if *escrow_fee_account_info.owner != *fee_token_program_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let escrow_fee_data = Self::unpack_spl_token_account(escrow_fee_account_info)?;
+        let usdc_ata_data = Self::unpack_spl_token_account(usdc_ata_info)?;
+
+        // farm account - check fee owner
+        if program_data.fee_owner != usdc_ata_data.owner {
+            return Err(FarmError::InvalidFeeAccount.into());
+        }
+
+        // token account - check token mints
+        if  escrow_fee_data.mint != *usdc_mint_info.key ||
+            usdc_ata_data.mint != *usdc_mint_info.key {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // release escrowed farm fee to fee owner
+        Self::token_transfer(
+            farm_id_info.key,
+            fee_token_program_info.clone(),
+            escrow_fee_account_info.clone(),
+            usdc_mint_info.clone(),
+            usdc_ata_info.clone(),
+            authority_info.clone(),
+            farm_pool.nonce,
+            escrow_fee_data.amount
+        )?;
+
+        farm_pool.escrow_fee_amount = 0;
+        farm_pool.listing_state = LISTING_LISTED;
+
+        // allow this farm to stake/unstake/harvest
+        farm_pool.set_allowed(1);
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process SetFeeToken instruction
+    /// super owner whitelists a token to pay farm fee with the amm pool to price it
+    pub fn process_set_fee_token(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        mut fee_token: FeeToken,
+    ) -> ProgramResult {
+        msg!("setting fee token ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // whitelisted fee token account information
+        let fee_token_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent if fee token account is created or extended
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // fee token mint account information
+        let fee_mint_info = next_account_info(account_info_iter)?;
+
+        // amm account information to price fee token in USDC
+        let amm_id_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // check if tolerance and discount are less than farm fee
+        if  (fee_token.tolerance_numerator > 0 && fee_token.tolerance_numerator >= fee_token.tolerance_denominator) ||
+            (fee_token.discount_numerator > 0 && fee_token.discount_numerator >= fee_token.discount_denominator) {
+            return Err(FarmError::FeeTooHigh.into());
+        }
+
+        // enabled fee token needs reference price to bound amm pool price
+        if fee_token.is_enabled != 0 {
+            fee_token.validate_reference_price()?;
+        }
+
+        // USDC is paid as it is, other tokens need an amm pool pairing them with USDC
+        let usdc_mint = Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+        if *fee_mint_info.key == usdc_mint {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        // check if given amm id is for correct amm program id
+        if *amm_id_info.owner != program_data.amm_program_id {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        // check if this amm pool pairs fee token with USDC
+        let amm_swap = SwapVersion::unpack(&amm_id_info.data.borrow())?;
+        let is_usdc_pair =
+            (*amm_swap.token_a_mint() == *fee_mint_info.key && *amm_swap.token_b_mint() == usdc_mint) ||
+            (*amm_swap.token_b_mint() == *fee_mint_info.key && *amm_swap.token_a_mint() == usdc_mint);
+        if !is_usdc_pair {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        let seeds = [
+            PREFIX.as_bytes(),
+            FEE_TOKEN_SEED.as_bytes(),
+            fee_mint_info.key.as_ref(),
+        ];
+        let (fee_token_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if fee_token_key != *fee_token_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let old_value = fee_token_info.data.borrow().to_vec();
+
+        // create fee token account if this token is whitelisted first time
+        if fee_token_info.data_is_empty() {
+            create_or_allocate_account_raw(
+                *program_id,
+                fee_token_info,
+                rent_info,
+                system_info,
+                super_owner_info,
+                get_packed_len::<FeeToken>(),
+                &[
+                    PREFIX.as_bytes(),
+                    FEE_TOKEN_SEED.as_bytes(),
+                    fee_mint_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+        } else {
+            // extend fee token account if it was created with an older layout
+            realloc_account(
+                fee_token_info,
+                rent_info,
+                system_info,
+                super_owner_info,
+                get_packed_len::<FeeToken>(),
+            )?;
+        }
+
+        fee_token.mint = *fee_mint_info.key;
+        fee_token.amm_id = *amm_id_info.key;
+
+        // store fee token account data to network
+        fee_token.serialize(&mut *fee_token_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_SET_FEE_TOKEN, super_owner_info.key, &old_value, &fee_token_info.data.borrow())
+    }
+
+    /// process CloseFarm instruction
+    /// owner closes an ended farm without staked lp, remaining rewards and all rent are returned to the owner
+    pub fn process_close_farm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("closing farm ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to close
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, receives rent of closed accounts
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account in the owner's wallet to sweep remaining rewards
+        let user_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of lp token
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // escrowed farm fee has to be released or refunded first
+        if farm_pool.is_in_review() {
+            return Err(FarmError::InReview.into());
+        }
+
+        // allocation points of registered farm would dilute other farms' emission
+        if farm_pool.is_emitter_farm() {
+            return Err(FarmError::FarmRegistered.into());
+        }
+
+        // unspent rewards of sponsors would be swept to the owner
+        if farm_pool.total_sponsored > 0 {
+            return Err(FarmError::SponsorsNotRefunded.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+        if cur_timestamp <= farm_pool.end_timestamp {
+            return Err(FarmError::FarmNotEnded.into());
+        }
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token programs are correct
+        if  *token_program_info.key != farm_pool.token_program_id ||
+            *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        // lp token account - nobody has staked or unbonding lp in this farm
+        if pool_lp_token_data.amount > 0 {
+            return Err(FarmError::FarmNotEmpty.into());
+        }
+
+        // sweep remaining rewards to the owner
+        if pool_reward_token_data.amount > 0 {
+            if *user_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            let user_reward_token_data = Self::unpack_token_account(user_reward_token_account_info)?;
+            if user_reward_token_data.mint != farm_pool.reward_mint_address {
+                return Err(FarmError::WrongAccountMint.into());
+            }
+            Self::token_transfer(
+                farm_id_info.key,
+                reward_token_program_info.clone(),
+                pool_reward_token_account_info.clone(),
+                reward_mint_info.clone(),
+                user_reward_token_account_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+                pool_reward_token_data.amount
+            )?;
+        }
+
+        // close lp token account and reward token account of this farm
+        Self::token_close_account(
+            farm_id_info.key,
+            token_program_info.clone(),
+            pool_lp_token_account_info.clone(),
+            owner_info.clone(),
+            authority_info.clone(),
+            farm_pool.nonce,
+        )?;
+        if farm_pool.is_native_reward() {
+            // native SOL rewards are held by farm authority which isn't a token account
+            // its remaining rent reserve goes to the owner instead of closing it
+            let remaining_lamports = pool_reward_token_account_info.lamports();
+            if remaining_lamports > 0 {
+                Self::token_transfer(
+                    farm_id_info.key,
+                    reward_token_program_info.clone(),
+                    pool_reward_token_account_info.clone(),
+                    reward_mint_info.clone(),
+                    owner_info.clone(),
+                    authority_info.clone(),
+                    farm_pool.nonce,
+                    remaining_lamports
+                )?;
+            }
+        } else {
+            Self::token_close_account(
+                farm_id_info.key,
+                reward_token_program_info.clone(),
+                pool_reward_token_account_info.clone(),
+                owner_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+            )?;
+        }
+
+        // zero farm account and refund its lamports to the owner
+        farm_id_info.data.borrow_mut().fill(0);
+        let farm_lamports = farm_id_info.lamports();
+        **farm_id_info.lamports.borrow_mut() = 0;
+        **owner_info.lamports.borrow_mut() = owner_info
+            .lamports()
+            .checked_add(farm_lamports)
+            .ok_or(FarmError::CalculationFailure)?;
+
+        Ok(())
+    }
+
+    /// process CreateMultisig instruction
+    /// initializes M-of-N multisig account with same rules as spl-token multisig
+    /// super owner can be set to the multisig authority so admin instructions are executed by proposals
+    pub fn process_create_multisig(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        m: u8,
+    ) -> ProgramResult {
+        msg!("creating multisig ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // multisig account information to initialize
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        // rent sysvar account information
+        let rent_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // multisig account - check if multisig account's owner is program id
+        if multisig_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let rent = &Rent::from_account_info(rent_info)?;
+        if !rent.is_exempt(multisig_info.lamports(), multisig_info.data_len()) {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+
+        let mut multisig = Multisig::unpack_unchecked(&multisig_info.data.borrow())?;
+        if multisig.is_initialized() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        // remaining accounts are signers of this multisig
+        let signer_infos = account_info_iter.as_slice();
+        multisig.m = m;
+        multisig.n = signer_infos.len() as u8;
+        if !Self::is_valid_signer_index(multisig.n as usize) {
+            return Err(FarmError::InvalidMultisig.into());
+        }
+        if !Self::is_valid_signer_index(multisig.m as usize) || multisig.m > multisig.n {
+            return Err(FarmError::InvalidMultisig.into());
+        }
+        for (i, signer_info) in signer_infos.iter().enumerate() {
+            multisig.signers[i] = *signer_info.key;
+        }
+        multisig.is_initialized = true;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())
+    }
+
+    /// process CreateProposal instruction
+    /// a multisig signer proposes an instruction of this program, proposer's approval is recorded
+    pub fn process_create_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_accounts: Vec<ProposalAccount>,
+        instruction_data: Vec<u8>,
+    ) -> ProgramResult {
+        msg!("creating proposal ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // proposal account information to initialize
+        let proposal_info = next_account_info(account_info_iter)?;
+
+        // multisig account information
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        // proposer account information, one of multisig signers
+        let proposer_info = next_account_info(account_info_iter)?;
+
+        // proposal account - check if proposal account's owner is program id
+        if proposal_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // check if given proposal was initialized already
+        if !Self::is_zero_account(proposal_info) {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        let multisig = Self::get_multisig(program_id, multisig_info)?;
+
+        //singers - check if proposer is signer
+        if !proposer_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut proposal = Proposal {
+            multisig: *multisig_info.key,
+            proposer: *proposer_info.key,
+            approvals: 0,
+            is_executed: 0,
+            accounts: instruction_accounts,
+            data: instruction_data,
+        };
+
+        // proposer approves the proposal at once
+        if !proposal.approve(&multisig.signers[..multisig.n as usize], proposer_info.key) {
+            return Err(FarmError::NotMultisigSigner.into());
+        }
+
+        // store proposal account data to network
+        proposal
+            .serialize(&mut *proposal_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process ApproveProposal instruction
+    pub fn process_approve_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("approving proposal ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // proposal account information to approve
+        let proposal_info = next_account_info(account_info_iter)?;
+
+        // multisig account information
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        // approver account information, one of multisig signers
+        let approver_info = next_account_info(account_info_iter)?;
+
+        let mut proposal = Self::get_proposal(program_id, proposal_info, multisig_info.key)?;
+        let multisig = Self::get_multisig(program_id, multisig_info)?;
+
+        //singers - check if approver is signer
+        if !approver_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        if !proposal.approve(&multisig.signers[..multisig.n as usize], approver_info.key) {
+            return Err(FarmError::NotMultisigSigner.into());
+        }
+
+        // store proposal account data to network
+        proposal
+            .serialize(&mut *proposal_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process ExecuteProposal instruction
+    /// anyone can execute a proposal with M approvals, multisig authority signs the proposed instruction
+    pub fn process_execute_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("executing proposal ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // proposal account information to execute
+        let proposal_info = next_account_info(account_info_iter)?;
+
+        // multisig account information
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        // multisig authority account information
+        let multisig_authority_info = next_account_info(account_info_iter)?;
+
+        // remaining accounts are accounts of proposed instruction and this program to invoke
+        let instruction_account_infos = account_info_iter.as_slice();
+
+        let mut proposal = Self::get_proposal(program_id, proposal_info, multisig_info.key)?;
+        let multisig = Self::get_multisig(program_id, multisig_info)?;
+
+        let (multisig_authority, bump) = Self::multisig_authority_id(program_id, multisig_info.key);
+        if multisig_authority != *multisig_authority_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        if proposal.approval_count() < multisig.m {
+            return Err(FarmError::NotEnoughApprovals.into());
+        }
+
+        // check if given accounts are proposed ones
+        if instruction_account_infos.len() != proposal.accounts.len() + 1 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        if instruction_account_infos[proposal.accounts.len()].key != program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        for (account_info, account) in instruction_account_infos.iter().zip(proposal.accounts.iter()) {
+            if *account_info.key != account.pubkey {
+                return Err(FarmError::InvalidProgramAddress.into());
+            }
+        }
+
+        // mark executed before invoking, so this proposal can't run again
+        proposal.is_executed = 1;
+        proposal.serialize(&mut *proposal_info.data.borrow_mut())?;
+
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: proposal.accounts.iter().map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            }).collect(),
+            data: proposal.data,
+        };
+
+        invoke_signed(
+            &ix,
+            instruction_account_infos,
+            &[&[
+                PREFIX.as_bytes(),
+                MULTISIG_SEED.as_bytes(),
+                multisig_info.key.as_ref(),
+                &[bump],
+            ]],
+        )
+    }
+
+    /// process QueueProgramData instruction
+    /// super owner queues new fees and amm program id, they can be executed after ETA
+    pub fn process_queue_program_data(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        change: ProgramDataChange,
+    ) -> ProgramResult {
+        msg!("queueing program data ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm program data account info
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        // timelock account information to store queued change
+        let timelock_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent of timelock account
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // new amm program account information
+        let amm_program_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, program_data_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(program_data_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // harvest fee must be in bounds
+        FarmProgram::validate_harvest_fee(change.harvest_fee_numerator, change.harvest_fee_denominator)?;
+
+        // new amm program must be the given executable program
+        Self::assert_amm_program(amm_program_info, &change.amm_program_id)?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // check if ETA is later than minimum delay
+        if change.eta < cur_timestamp.saturating_add(program_data.get_min_delay()) {
+            return Err(FarmError::InvalidEta.into());
+        }
+
+        let bump = Self::assert_timelock_account(program_id, timelock_info.key)?;
+        let old_value = timelock_info.data.borrow().to_vec();
+
+        if timelock_info.data_is_empty() {
+            create_or_allocate_account_raw(
+                *program_id,
+                timelock_info,
+                rent_info,
+                system_info,
+                super_owner_info,
+                get_packed_len::<ProgramDataChange>(),
+                &[
+                    PREFIX.as_bytes(),
+                    TIMELOCK_SEED.as_bytes(),
+                    program_id.as_ref(),
+                    &[bump],
+                ],
+            )?;
+        } else if try_from_slice_unchecked::<ProgramDataChange>(&timelock_info.data.borrow())?.is_queued != 0 {
+            return Err(FarmError::AlreadyQueued.into());
+        }
+
+        // extend program data account now, so anyone can execute this change without paying rent
+        realloc_account(
+            program_data_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<FarmProgram>(),
+        )?;
+
+        msg!("queued until {}", change.eta);
+
+        // store queued change to network
+        change.serialize(&mut *timelock_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_QUEUE_PROGRAM_DATA, super_owner_info.key, &old_value, &timelock_info.data.borrow())
+    }
+
+    /// process ExecuteProgramData instruction
+    /// anyone can apply queued change after ETA
+    pub fn process_execute_program_data(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("executing program data ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm program data account info
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        // timelock account information
+        let timelock_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, program_data_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(program_data_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let mut program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        let mut change = Self::get_queued_change(program_id, timelock_info)?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+        if cur_timestamp < change.eta {
+            return Err(FarmError::TimelockNotReady.into());
+        }
+
+        let old_value = program_data_info.data.borrow().to_vec();
+        program_data.amm_program_id = change.amm_program_id;
+        program_data.farm_fee = change.farm_fee;
+        program_data.harvest_fee_numerator = change.harvest_fee_numerator;
+        program_data.harvest_fee_denominator = change.harvest_fee_denominator;
+        program_data.min_delay = change.min_delay;
+
+        // clear queue
+        change.is_queued = 0;
+        change.serialize(&mut *timelock_info.data.borrow_mut())?;
+
+        // store program data to network
+        program_data.pack_into_slice(&mut program_data_info.data.borrow_mut())?;
+
+        // record this call in audit log, nobody signs it so the super owner who queued the change is recorded
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_EXECUTE_PROGRAM_DATA, &program_data.super_owner, &old_value, &program_data_info.data.borrow())
+    }
+
+    /// process CancelProgramData instruction
+    /// super owner cancels queued change before it is executed
+    pub fn process_cancel_program_data(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("cancelling program data ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm program data account info
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        // timelock account information
+        let timelock_info = next_account_info(account_info_iter)?;
+
+        // super owner account information
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, program_data_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(program_data_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut change = Self::get_queued_change(program_id, timelock_info)?;
+        let old_value = timelock_info.data.borrow().to_vec();
+
+        // clear queue
+        change.is_queued = 0;
+        change.serialize(&mut *timelock_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_CANCEL_PROGRAM_DATA, super_owner_info.key, &old_value, &timelock_info.data.borrow())
+    }
+
+    /// process MigrateStake instruction
+    /// harvests both farms and moves staked lp from source farm vault to destination farm vault
+    pub fn process_migrate_stake(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        destination_position_index: u64,
+    ) -> ProgramResult {
+        msg!("migrating stake ...");
+
+        if accounts.len() < HARVEST_MANY_ACCOUNTS * 2 + 9 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // source and destination farm accounts are grouped as in HarvestMany instruction
+        let (source_infos, rest) = accounts.split_at(HARVEST_MANY_ACCOUNTS);
+        let (destination_infos, rest) = rest.split_at(HARVEST_MANY_ACCOUNTS);
+
+        // get account informations
+        let account_info_iter = &mut rest.iter();
+
+        // user's wallet account information
+        let user_wallet_info = next_account_info(account_info_iter)?;
+
+        // lp token mint account information of both farms
+        let pool_lp_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of lp token
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // associated token account program address to create missing reward token accounts
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+
+        // fee owner wallet account information, owner of fee reward ata
+        let fee_owner_info = next_account_info(account_info_iter)?;
+
+        let source_farm_info = &source_infos[0];
+        let source_authority_info = &source_infos[1];
+        let source_user_info_account_info = &source_infos[2];
+        let source_pool_lp_token_account_info = &source_infos[3];
+
+        let destination_farm_info = &destination_infos[0];
+        let destination_authority_info = &destination_infos[1];
+        let destination_user_info_account_info = &destination_infos[2];
+        let destination_pool_lp_token_account_info = &destination_infos[3];
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        //singers - check if user is signer
+        if !user_wallet_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - stake can't be migrated to the same farm
+        if source_farm_info.key == destination_farm_info.key {
+            return Err(FarmError::MigrationNotAllowed.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // reward token accounts - create missing associated token accounts of user and fee owner in both farms
+        for farm_infos in [source_infos, destination_infos] {
+            if farm_infos[0].owner != program_id {
+                return Err(FarmError::InvalidOwner.into());
+            }
+            Self::create_reward_token_accounts(
+                &FarmPool::unpack_from_slice(&farm_infos[0].data.borrow())?,
+                &program_data,
+                user_wallet_info,
+                &farm_infos[5],
+                &farm_infos[6],
+                &farm_infos[7],
+                &farm_infos[8],
+                system_info,
+                rent_info,
+                associated_token_program_info,
+                fee_owner_info,
+            )?;
+        }
+
+        // validate source farm and update it with up-to-date
+        let (mut source_pool, mut source_user_info) = Self::prepare_harvest(
+            program_id,
+            &program_data,
+            user_wallet_info.key,
+            cur_timestamp,
+            source_infos,
+            true,
+        )?;
+
+        // create user info account of destination position if user didn't stake there yet
+        if destination_user_info_account_info.data_is_empty() {
+            Self::create_user_info_account(
+                program_id,
+                destination_farm_info.key,
+                user_wallet_info.key,
+                destination_position_index,
+                destination_user_info_account_info,
+                rent_info,
+                system_info,
+                user_wallet_info,
+            )?;
+            UserInfo {
+                wallet: *user_wallet_info.key,
+                farm_id: *destination_farm_info.key,
+                position_index: destination_position_index,
+                ..UserInfo::default()
+            }.pack_into_slice(&mut destination_user_info_account_info.data.borrow_mut())?;
+        }
+
+        // validate destination farm and update it with up-to-date
+        let (mut destination_pool, mut destination_user_info) = Self::prepare_harvest(
+            program_id,
+            &program_data,
+            user_wallet_info.key,
+            cur_timestamp,
+            destination_infos,
+            false,
+        )?;
+
+        // user info account - check if this is the given destination position
+        if destination_user_info.position_index != destination_position_index {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // farm account - The period of destination farm was ended
+        if cur_timestamp > destination_pool.end_timestamp {
+            return Err(FarmError::FarmEnded.into());
+        }
+
+        // pool mint - check if both farms stake the same lp token
+        if  source_pool.pool_mint_address != destination_pool.pool_mint_address ||
+            *pool_lp_mint_info.key != source_pool.pool_mint_address {
+            return Err(FarmError::WrongPoolMint.into());
+        }
+
+        // token program - check if given token program is lp token's program
+        if  *token_program_info.key != source_pool.token_program_id ||
+            *token_program_info.key != destination_pool.token_program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // if amount > deposited balance, amount is deposited balance
+        let amount = amount.min(source_user_info.deposit_balance);
+
+        // lockup - migration can't be used to skip early withdraw fee or unbonding period
+        if  source_pool.get_early_withdraw_fee(amount, &source_user_info, cur_timestamp)? > 0 ||
+            destination_pool.unbonding_period < source_pool.unbonding_period {
+            return Err(FarmError::MigrationNotAllowed.into());
+        }
+
+        // receipt - source receipt tokens are burnt and destination receipt tokens are minted
+        let source_receipt_infos = if source_pool.has_receipt() {
+            let receipt_mint_info = next_account_info(account_info_iter)?;
+            let user_receipt_token_account_info = next_account_info(account_info_iter)?;
+            Self::assert_receipt_account(&source_pool, receipt_mint_info, user_receipt_token_account_info, user_wallet_info.key)?;
+            let user_receipt_token_data = Self::unpack_spl_token_account(user_receipt_token_account_info)?;
+            if user_receipt_token_data.amount < amount {
+                return Err(FarmError::NotEnoughBalance.into());
+            }
+            Some((receipt_mint_info, user_receipt_token_account_info))
+        } else {
+            None
+        };
+        let destination_receipt_infos = if destination_pool.has_receipt() {
+            let receipt_mint_info = next_account_info(account_info_iter)?;
+            let user_receipt_token_account_info = next_account_info(account_info_iter)?;
+            Self::assert_receipt_account(&destination_pool, receipt_mint_info, user_receipt_token_account_info, user_wallet_info.key)?;
+            Some((receipt_mint_info, user_receipt_token_account_info))
+        } else {
+            None
+        };
+
+        // emitter - pull emission of both farms before deposited balances change
+        let source_emitter_infos = Self::next_emitter_infos(&source_pool, account_info_iter)?;
+        let destination_emitter_infos = Self::next_emitter_infos(&destination_pool, account_info_iter)?;
+
+        // boost - lock accounts of both farms are required if they boost rewards
+        let source_lock_infos = Self::next_lock_infos(&source_pool, account_info_iter)?;
+        let destination_lock_infos = Self::next_lock_infos(&destination_pool, account_info_iter)?;
+        Self::pull_emission(
+            program_id,
+            source_emitter_infos,
+            &source_infos[7],
+            &source_infos[8],
+            &source_infos[4],
+            &mut source_pool,
+            cur_timestamp,
+            Self::unpack_spl_token_account(source_pool_lp_token_account_info)?.amount,
+        )?;
+        Self::pull_emission(
+            program_id,
+            destination_emitter_infos,
+            &destination_infos[7],
+            &destination_infos[8],
+            &destination_infos[4],
+            &mut destination_pool,
+            cur_timestamp,
+            Self::unpack_spl_token_account(destination_pool_lp_token_account_info)?.amount,
+        )?;
+
+        // harvest user's pending rewards in both farms
+        Self::harvest(
+            source_farm_info,
+            &source_infos[8],
+            &source_infos[4],
+            &source_infos[7],
+            &source_infos[6],
+            &source_infos[5],
+            source_authority_info,
+            &program_data,
+            &mut source_pool,
+            &mut source_user_info
+        )?;
+        if destination_user_info.has_rewards_to_harvest() {
+            Self::harvest(
+                destination_farm_info,
+                &destination_infos[8],
+                &destination_infos[4],
+                &destination_infos[7],
+                &destination_infos[6],
+                &destination_infos[5],
+                destination_authority_info,
+                &program_data,
+                &mut destination_pool,
+                &mut destination_user_info
+            )?;
+        }
+
+        if amount > 0 {
+            let destination_lp_amount = Self::unpack_spl_token_account(destination_pool_lp_token_account_info)?.amount;
+
+            // move lp token from source farm vault to destination farm vault
+            Self::token_transfer(
+                source_farm_info.key,
+                token_program_info.clone(),
+                source_pool_lp_token_account_info.clone(),
+                pool_lp_mint_info.clone(),
+                destination_pool_lp_token_account_info.clone(),
+                source_authority_info.clone(),
+                source_pool.nonce,
+                amount
+            )?;
+
+            // token extensions like transfer fee can change the amount, so credit what the destination received
+            let received_amount = Self::unpack_spl_token_account(destination_pool_lp_token_account_info)?.amount
+                .checked_sub(destination_lp_amount)
+                .ok_or(FarmError::CalculationFailure)?;
+
+            // burn source receipt tokens for moved lp
+            if let Some((receipt_mint_info, user_receipt_token_account_info)) = source_receipt_infos {
+                Self::thaw_receipt_account(
+                    source_farm_info.key,
+                    token_program_info,
+                    user_receipt_token_account_info,
+                    receipt_mint_info,
+                    source_authority_info,
+                    source_pool.nonce,
+                )?;
+                Self::token_burn(
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    user_wallet_info.clone(),
+                    amount
+                )?;
+                Self::token_freeze_account(
+                    source_farm_info.key,
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    source_authority_info.clone(),
+                    source_pool.nonce,
+                )?;
+            }
+
+            // mint destination receipt tokens 1:1 with credited lp
+            if let Some((receipt_mint_info, user_receipt_token_account_info)) = destination_receipt_infos {
+                Self::thaw_receipt_account(
+                    destination_farm_info.key,
+                    token_program_info,
+                    user_receipt_token_account_info,
+                    receipt_mint_info,
+                    destination_authority_info,
+                    destination_pool.nonce,
+                )?;
+                Self::token_mint_to(
+                    destination_farm_info.key,
+                    token_program_info.clone(),
+                    receipt_mint_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    destination_authority_info.clone(),
+                    destination_pool.nonce,
+                    received_amount
+                )?;
+                Self::token_freeze_account(
+                    destination_farm_info.key,
+                    token_program_info.clone(),
+                    user_receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    destination_authority_info.clone(),
+                    destination_pool.nonce,
+                )?;
+            }
+
+            // update deposited balances
+            let old_balance = source_user_info.deposit_balance;
+            source_user_info.deposit_balance -= amount;
+            source_user_info.write_checkpoint(old_balance, cur_timestamp);
+            source_pool.restart_loyalty(&mut source_user_info, cur_timestamp);
+            source_pool.update_staker_count(old_balance, source_user_info.deposit_balance);
+            source_pool.last_activity_timestamp = cur_timestamp;
+
+            let old_balance = destination_user_info.deposit_balance;
+            destination_user_info.deposit_balance += received_amount;
+            destination_user_info.write_checkpoint(old_balance, cur_timestamp);
+            if old_balance == 0 {
+                destination_pool.restart_loyalty(&mut destination_user_info, cur_timestamp);
+            }
+
+            // minimum stake duration restarts from the migration
+            destination_user_info.deposit_timestamp = cur_timestamp;
+
+            destination_pool.update_staker_count(old_balance, destination_user_info.deposit_balance);
+            destination_pool.total_lp_deposited = destination_pool.total_lp_deposited.saturating_add(received_amount);
+            destination_pool.last_activity_timestamp = cur_timestamp;
+        }
+
+        // refresh effective stake of both positions with CRP lock share
+        let source_lp_amount = Self::unpack_spl_token_account(source_pool_lp_token_account_info)?.amount;
+        let destination_lp_amount = Self::unpack_spl_token_account(destination_pool_lp_token_account_info)?.amount;
+        Self::update_boost(
+            program_id,
+            source_lock_infos,
+            &mut source_pool,
+            &mut source_user_info,
+            source_lp_amount,
+            cur_timestamp,
+        )?;
+        Self::update_boost(
+            program_id,
+            destination_lock_infos,
+            &mut destination_pool,
+            &mut destination_user_info,
+            destination_lp_amount,
+            cur_timestamp,
+        )?;
+
+        // update reward debts
+        source_user_info.reward_debt = source_pool.get_new_reward_debt(&source_user_info)?;
+        destination_user_info.reward_debt = destination_pool.get_new_reward_debt(&destination_user_info)?;
+
+        // extend source user info to store checkpoints if it was created with an older layout
+        realloc_account(
+            source_user_info_account_info,
+            rent_info,
+            system_info,
+            user_wallet_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+
+        // store source farm and user's information to network
+        source_user_info.pack_into_slice(&mut source_user_info_account_info.data.borrow_mut())?;
+        source_pool.pack_into_slice(&mut source_farm_info.data.borrow_mut())?;
+
+        // extend destination accounts if they were created with an older layout
+        realloc_account(
+            destination_user_info_account_info,
+            rent_info,
+            system_info,
+            user_wallet_info,
+            get_packed_len::<UserInfo>(),
+        )?;
+        destination_user_info.pack_into_slice(&mut destination_user_info_account_info.data.borrow_mut())?;
+
+        realloc_account(
+            destination_farm_info,
+            rent_info,
+            system_info,
+            user_wallet_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        // store destination farm data to network
+        destination_pool.pack_into_slice(&mut destination_farm_info.data.borrow_mut())
+    }
+
+    /// process InitializeEmitter instruction
+    /// super owner creates the global emitter which splits CRP emission across registered farms
+    pub fn process_initialize_emitter(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reward_per_second: u64,
+    ) -> ProgramResult {
+        msg!("initializing emitter ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // emitter account information to create
+        let emitter_info = next_account_info(account_info_iter)?;
+
+        // authority information of emitter vault
+        let emitter_authority_info = next_account_info(account_info_iter)?;
+
+        // reward token account information of emitter
+        let reward_vault_info = next_account_info(account_info_iter)?;
+
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent of emitter account
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // emitter account - check if this is emitter PDA which is not created yet
+        let bump = Self::assert_emitter_account(program_id, emitter_info.key)?;
+        if !emitter_info.data_is_empty() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        // reward mint - emitter emits CRP only
+        if *reward_mint_info.key != Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token program - check if given token program is reward token's program
+        Self::assert_token_program(token_program_info.key)?;
+        if reward_mint_info.owner != token_program_info.key || reward_vault_info.owner != token_program_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // emitter authority - check if given authority is emitter's
+        let (emitter_authority, nonce) = Pubkey::find_program_address(&[&emitter_info.key.to_bytes()[..32]], program_id);
+        if emitter_authority != *emitter_authority_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // token account - check if reward vault is owned by emitter authority
+        let reward_vault_data = Self::unpack_spl_token_account(reward_vault_info)?;
+        if reward_vault_data.owner != emitter_authority {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if reward_vault_data.mint != *reward_mint_info.key {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        create_or_allocate_account_raw(
+            *program_id,
+            emitter_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<Emitter>(),
+            &[
+                PREFIX.as_bytes(),
+                EMITTER_SEED.as_bytes(),
+                program_id.as_ref(),
+                &[bump],
+            ],
+        )?;
+
+        let emitter = Emitter {
+            nonce,
+            reward_mint: *reward_mint_info.key,
+            reward_vault: *reward_vault_info.key,
+            token_program_id: *token_program_info.key,
+            reward_per_second,
+            last_timestamp: Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64,
+            ..Emitter::default()
+        };
+
+        // store emitter account data to network
+        emitter.serialize(&mut *emitter_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_INITIALIZE_EMITTER, super_owner_info.key, &[], &emitter_info.data.borrow())
+    }
+
+    /// process SetEmitterRate instruction
+    /// emission so far is accumulated with the old rate before the new rate is set
+    pub fn process_set_emitter_rate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reward_per_second: u64,
+    ) -> ProgramResult {
+        msg!("setting emitter rate ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // emitter account information
+        let emitter_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // super owner account information
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut emitter = Self::get_emitter(program_id, emitter_info)?;
+        let old_value = emitter_info.data.borrow().to_vec();
+
+        // accumulate emission until now with the old rate
+        emitter.update(Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64)?;
+        emitter.reward_per_second = reward_per_second;
+
+        // store emitter account data to network
+        emitter.serialize(&mut *emitter_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_SET_EMITTER_RATE, super_owner_info.key, &old_value, &emitter_info.data.borrow())
+    }
+
+    /// process SetAllocPoint instruction
+    /// farm pulls its emission so far, then its allocation points and emitter's total are updated
+    pub fn process_set_alloc_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        alloc_point: u64,
+    ) -> ProgramResult {
+        msg!("setting allocation points ...");
+
+        // get account informations
+        // emitter and farm accounts come first and are validated by update_alloc_point
+        let account_info_iter = &mut accounts.iter().skip(SET_ALLOC_POINT_ACCOUNTS);
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent if farm account is extended
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // farm account information whose allocation points are set
+        let farm_id_info = &accounts[3];
+        let old_value = farm_id_info.data.borrow().to_vec();
+
+        Self::update_alloc_point(
+            program_id,
+            &accounts[..SET_ALLOC_POINT_ACCOUNTS],
+            super_owner_info,
+            rent_info,
+            system_info,
+            cur_timestamp,
+            alloc_point,
+        )?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_SET_ALLOC_POINT, super_owner_info.key, &old_value, &farm_id_info.data.borrow())
+    }
+
+    /// process InitializeGaugeController instruction
+    /// super owner creates gauge controller which locks CRP for voting power
+    pub fn process_initialize_gauge_controller(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        epoch_duration: u64,
+        max_lock_duration: u64,
+    ) -> ProgramResult {
+        msg!("initializing gauge controller ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // gauge controller account information to create
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // authority information of lock vault
+        let gauge_controller_authority_info = next_account_info(account_info_iter)?;
+
+        // CRP token account information which stores locked CRP
+        let lock_vault_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent of gauge controller account
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of CRP
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        if epoch_duration == 0 || max_lock_duration == 0 {
+            return Err(FarmError::InvalidLockDuration.into());
+        }
+
+        // gauge controller account - check if this is gauge controller PDA which is not created yet
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_CONTROLLER_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        let bump = Self::assert_seeds_account(program_id, &seeds, gauge_controller_info.key)?;
+        if !gauge_controller_info.data_is_empty() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        // token program - check if given token program is lock vault's program
+        Self::assert_token_program(token_program_info.key)?;
+        if lock_vault_info.owner != token_program_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // gauge controller authority - check if given authority is gauge controller's
+        let (authority, nonce) = Pubkey::find_program_address(&[&gauge_controller_info.key.to_bytes()[..32]], program_id);
+        if authority != *gauge_controller_authority_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // token account - check if lock vault is CRP account owned by gauge controller authority
+        let lock_vault_data = Self::unpack_spl_token_account(lock_vault_info)?;
+        if lock_vault_data.owner != authority {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if lock_vault_data.mint != Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        create_or_allocate_account_raw(
+            *program_id,
+            gauge_controller_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<GaugeController>(),
+            &[
+                PREFIX.as_bytes(),
+                GAUGE_CONTROLLER_SEED.as_bytes(),
+                program_id.as_ref(),
+                &[bump],
+            ],
+        )?;
+
+        let gauge_controller = GaugeController {
+            nonce,
+            lock_vault: *lock_vault_info.key,
+            token_program_id: *token_program_info.key,
+            epoch_duration,
+            max_lock_duration,
+            start_timestamp: Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64,
+            ..GaugeController::default()
+        };
+
+        // store gauge controller account data to network
+        gauge_controller.serialize(&mut *gauge_controller_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_INITIALIZE_GAUGE_CONTROLLER, super_owner_info.key, &[], &gauge_controller_info.data.borrow())
+    }
+
+    /// process CreateGauge instruction
+    /// super owner creates gauge of a farm which rewards emitter's token
+    pub fn process_create_gauge(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("creating gauge ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // gauge account information to create
+        let gauge_info = next_account_info(account_info_iter)?;
+
+        // farm account information of this gauge
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // emitter account information
+        let emitter_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent of gauge account
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // audit log account follows fixed accounts once audit log is created
+        let audit_log_info = Self::next_audit_log_info(&program_data, account_info_iter)?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+        let emitter = Self::get_emitter(program_id, emitter_info)?;
+        let gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+
+        // reward mint - gauge directs emission, so farm has to reward emitter's token
+        if farm_pool.reward_mint_address != emitter.reward_mint {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // gauge account - check if this is gauge PDA of given farm which is not created yet
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_SEED.as_bytes(),
+            farm_id_info.key.as_ref(),
+        ];
+        let bump = Self::assert_seeds_account(program_id, &seeds, gauge_info.key)?;
+        if !gauge_info.data_is_empty() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        create_or_allocate_account_raw(
+            *program_id,
+            gauge_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<Gauge>(),
+            &[
+                PREFIX.as_bytes(),
+                GAUGE_SEED.as_bytes(),
+                farm_id_info.key.as_ref(),
+                &[bump],
+            ],
+        )?;
+
+        let epoch = gauge_controller.get_epoch(Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64);
+        let gauge = Gauge {
+            farm_id: *farm_id_info.key,
+            weight: 0,
+            next_epoch: epoch,
+            epoch_weight: 0,
+            weight_epoch: epoch,
+        };
+
+        // store gauge account data to network
+        gauge.serialize(&mut *gauge_info.data.borrow_mut())?;
+
+        // record this call in audit log
+        Self::write_audit_log(program_id, audit_log_info, AUDIT_CREATE_GAUGE, super_owner_info.key, &[], &gauge_info.data.borrow())
+    }
+
+    /// process LockVotingPower instruction
+    /// voter locks CRP and gets voting power proportional to lock duration
+    pub fn process_lock_voting_power(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        duration: u64,
+    ) -> ProgramResult {
+        msg!("locking voting power ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // voter account information
+        let voter_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // CRP token account information which stores locked CRP
+        let lock_vault_info = next_account_info(account_info_iter)?;
+
+        // CRP token account information in the voter's wallet
+        let user_token_account_info = next_account_info(account_info_iter)?;
+
+        // CRP mint account information
+        let crp_mint_info = next_account_info(account_info_iter)?;
+
+        // voter's wallet account information
+        let wallet_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of CRP
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        //singers - check if voter is signer
+        if !wallet_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+
+        // token account - check if lock vault is gauge controller's
+        if *lock_vault_info.key != gauge_controller.lock_vault {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token program is CRP's program
+        if *token_program_info.key != gauge_controller.token_program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // CRP mint - check if given mint is CRP
+        if *crp_mint_info.key != Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        if duration == 0 || duration > gauge_controller.max_lock_duration {
+            return Err(FarmError::InvalidLockDuration.into());
+        }
+
+        // token account - check if user token account is voter's
+        let user_token_data = Self::unpack_spl_token_account(user_token_account_info)?;
+        if user_token_data.owner != *wallet_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if user_token_data.amount < amount {
+            return Err(FarmError::NotEnoughBalance.into());
+        }
+
+        // voter account - create voter account if this wallet locks first time
+        let seeds = [
+            PREFIX.as_bytes(),
+            VOTER_SEED.as_bytes(),
+            wallet_info.key.as_ref(),
+        ];
+        let bump = Self::assert_seeds_account(program_id, &seeds, voter_info.key)?;
+        if voter_info.data_is_empty() {
+            create_or_allocate_account_raw(
+                *program_id,
+                voter_info,
+                rent_info,
+                system_info,
+                wallet_info,
+                get_packed_len::<Voter>(),
+                &[
+                    PREFIX.as_bytes(),
+                    VOTER_SEED.as_bytes(),
+                    wallet_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            Voter {
+                wallet: *wallet_info.key,
+                ..Voter::default()
+            }.serialize(&mut *voter_info.data.borrow_mut())?;
+        }
+        let mut voter = Self::get_voter(program_id, voter_info, wallet_info.key)?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        if amount > 0 {
+            let lock_vault_amount = Self::unpack_spl_token_account(lock_vault_info)?.amount;
+
+            // transfer CRP from voter's token account to lock vault
+            Self::token_transfer(
+                gauge_controller_info.key,
+                token_program_info.clone(),
+                user_token_account_info.clone(),
+                crp_mint_info.clone(),
+                lock_vault_info.clone(),
+                wallet_info.clone(),
+                gauge_controller.nonce,
+                amount
+            )?;
+
+            // token extensions like transfer fee can change the amount, so lock what the vault received
+            let amount = Self::unpack_spl_token_account(lock_vault_info)?.amount
+                .checked_sub(lock_vault_amount)
+                .ok_or(FarmError::CalculationFailure)?;
+
+            voter.locked_amount = voter.locked_amount.checked_add(amount).ok_or(FarmError::CalculationFailure)?;
+            gauge_controller.total_locked = gauge_controller.total_locked.saturating_add(amount);
+        }
+
+        // lock end can be extended, but never shortened
+        voter.lock_end_timestamp = voter.lock_end_timestamp.max(cur_timestamp.saturating_add(duration));
+
+        // voting power never decreases while votes are using it
+        voter.voting_power = voter.voting_power.max(
+            gauge_controller.get_voting_power(voter.locked_amount, voter.lock_end_timestamp, cur_timestamp)?
+        );
+
+        msg!("voting power {}", voter.voting_power);
+
+        // store voter account data to network
+        voter.serialize(&mut *voter_info.data.borrow_mut())?;
+
+        // store gauge controller account data to network
+        gauge_controller
+            .serialize(&mut *gauge_controller_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process VoteGauge instruction
+    /// voter sets voting power given to a gauge
+    pub fn process_vote_gauge(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        power: u64,
+    ) -> ProgramResult {
+        msg!("voting gauge ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // gauge vote account information of this voter and gauge
+        let gauge_vote_info = next_account_info(account_info_iter)?;
+
+        // voter account information
+        let voter_info = next_account_info(account_info_iter)?;
+
+        // gauge account information to vote
+        let gauge_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // voter's wallet account information
+        let wallet_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        //singers - check if voter is signer
+        if !wallet_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+        let mut voter = Self::get_voter(program_id, voter_info, wallet_info.key)?;
+        let mut gauge = Self::get_gauge(program_id, gauge_info)?;
+
+        // gauge vote account - create gauge vote account if this voter votes this gauge first time
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_VOTE_SEED.as_bytes(),
+            wallet_info.key.as_ref(),
+            gauge.farm_id.as_ref(),
+        ];
+        let bump = Self::assert_seeds_account(program_id, &seeds, gauge_vote_info.key)?;
+        if gauge_vote_info.data_is_empty() {
+            create_or_allocate_account_raw(
+                *program_id,
+                gauge_vote_info,
+                rent_info,
+                system_info,
+                wallet_info,
+                get_packed_len::<GaugeVote>(),
+                &[
+                    PREFIX.as_bytes(),
+                    GAUGE_VOTE_SEED.as_bytes(),
+                    wallet_info.key.as_ref(),
+                    gauge.farm_id.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            GaugeVote {
+                wallet: *wallet_info.key,
+                farm_id: gauge.farm_id,
+                power: 0,
+            }.serialize(&mut *gauge_vote_info.data.borrow_mut())?;
+        }
+
+        // gauge vote account - check if gauge vote account's owner is program id
+        if gauge_vote_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let mut gauge_vote = try_from_slice_unchecked::<GaugeVote>(&gauge_vote_info.data.borrow())?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // ended lock has no voting power, votes can be removed only
+        if power > 0 && cur_timestamp >= voter.lock_end_timestamp {
+            return Err(FarmError::NotEnoughVotingPower.into());
+        }
+
+        voter.used_power = voter.used_power
+            .checked_sub(gauge_vote.power).ok_or(FarmError::CalculationFailure)?
+            .checked_add(power).ok_or(FarmError::CalculationFailure)?;
+        if voter.used_power > voter.voting_power {
+            return Err(FarmError::NotEnoughVotingPower.into());
+        }
+
+        // snapshot weight before this vote changes it
+        gauge.roll_epoch(gauge_controller.get_epoch(cur_timestamp));
+
+        gauge.weight = gauge.weight
+            .checked_sub(gauge_vote.power).ok_or(FarmError::CalculationFailure)?
+            .checked_add(power).ok_or(FarmError::CalculationFailure)?;
+        gauge_controller.total_weight = gauge_controller.total_weight
+            .checked_sub(gauge_vote.power).ok_or(FarmError::CalculationFailure)?
+            .checked_add(power).ok_or(FarmError::CalculationFailure)?;
+        gauge_vote.power = power;
+
+        // store vote, voter, gauge and gauge controller to network
+        gauge_vote.serialize(&mut *gauge_vote_info.data.borrow_mut())?;
+        voter.serialize(&mut *voter_info.data.borrow_mut())?;
+        gauge.serialize(&mut *gauge_info.data.borrow_mut())?;
+        gauge_controller
+            .serialize(&mut *gauge_controller_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process UnlockVotingPower instruction
+    /// voter withdraws locked CRP after lock end when all votes are removed
+    pub fn process_unlock_voting_power(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("unlocking voting power ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // voter account information
+        let voter_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // authority information of lock vault
+        let gauge_controller_authority_info = next_account_info(account_info_iter)?;
+
+        // CRP token account information which stores locked CRP
+        let lock_vault_info = next_account_info(account_info_iter)?;
+
+        // CRP token account information in the voter's wallet
+        let user_token_account_info = next_account_info(account_info_iter)?;
+
+        // CRP mint account information
+        let crp_mint_info = next_account_info(account_info_iter)?;
+
+        // voter's wallet account information
+        let wallet_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of CRP
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        //singers - check if voter is signer
+        if !wallet_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let mut gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+        let mut voter = Self::get_voter(program_id, voter_info, wallet_info.key)?;
+
+        // gauge controller authority - check if given authority is gauge controller's
+        if *gauge_controller_authority_info.key != Self::authority_id(program_id, gauge_controller_info.key, gauge_controller.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // token account - check if lock vault is gauge controller's
+        if *lock_vault_info.key != gauge_controller.lock_vault {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token program is CRP's program
+        if *token_program_info.key != gauge_controller.token_program_id {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // lock has to be ended and all votes have to be removed
+        if cur_timestamp < voter.lock_end_timestamp || voter.used_power > 0 {
+            return Err(FarmError::StillLocked.into());
+        }
+
+        if voter.locked_amount > 0 {
+            // transfer locked CRP back to voter
+            Self::token_transfer(
+                gauge_controller_info.key,
+                token_program_info.clone(),
+                lock_vault_info.clone(),
+                crp_mint_info.clone(),
+                user_token_account_info.clone(),
+                gauge_controller_authority_info.clone(),
+                gauge_controller.nonce,
+                voter.locked_amount
+            )?;
+        }
+
+        gauge_controller.total_locked = gauge_controller.total_locked.saturating_sub(voter.locked_amount);
+        voter.locked_amount = 0;
+        voter.voting_power = 0;
+
+        // store voter account data to network
+        voter.serialize(&mut *voter_info.data.borrow_mut())?;
+
+        // store gauge controller account data to network
+        gauge_controller
+            .serialize(&mut *gauge_controller_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process CheckpointGauge instruction
+    /// gauge weight at the start of current epoch becomes allocation points of the farm, once per epoch
+    pub fn process_checkpoint_gauge(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("checkpointing gauge ...");
+
+        // get account informations
+        // emitter and farm accounts come first and are validated by update_alloc_point
+        let account_info_iter = &mut accounts.iter().skip(SET_ALLOC_POINT_ACCOUNTS);
+
+        // gauge account information of the farm
+        let gauge_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // payer account information, pays rent if farm account is extended
+        let payer_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        //singers - check if payer is signer
+        if !payer_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+        let mut gauge = Self::get_gauge(program_id, gauge_info)?;
+
+        // gauge account - check if gauge is for given farm
+        if gauge.farm_id != *accounts[3].key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // gauge is checkpointed once per epoch
+        let epoch = gauge_controller.get_epoch(cur_timestamp);
+        if epoch < gauge.next_epoch {
+            return Err(FarmError::EpochNotEnded.into());
+        }
+
+        // votes changed in this epoch are counted from the next epoch
+        gauge.roll_epoch(epoch);
+
+        msg!("epoch {}, weight {}", epoch, gauge.epoch_weight);
+
+        Self::update_alloc_point(
+            program_id,
+            &accounts[..SET_ALLOC_POINT_ACCOUNTS],
+            payer_info,
+            rent_info,
+            system_info,
+            cur_timestamp,
+            gauge.epoch_weight,
+        )?;
+
+        gauge.next_epoch = epoch + 1;
+
+        // store gauge account data to network
+        gauge
+            .serialize(&mut *gauge_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process RemoveExpiredVote instruction
+    /// anyone removes a vote whose lock is ended, expired locks have no voting power
+    pub fn process_remove_expired_vote(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("removing expired vote ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // gauge vote account information to remove
+        let gauge_vote_info = next_account_info(account_info_iter)?;
+
+        // voter account information
+        let voter_info = next_account_info(account_info_iter)?;
+
+        // gauge account information of voted farm
+        let gauge_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // gauge vote account - check if gauge vote account's owner is program id
+        if gauge_vote_info.owner != program_id || gauge_vote_info.data_is_empty() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let mut gauge_vote = try_from_slice_unchecked::<GaugeVote>(&gauge_vote_info.data.borrow())?;
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_VOTE_SEED.as_bytes(),
+            gauge_vote.wallet.as_ref(),
+            gauge_vote.farm_id.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, gauge_vote_info.key)?;
+
+        let mut gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+        let mut voter = Self::get_voter(program_id, voter_info, &gauge_vote.wallet)?;
+        let mut gauge = Self::get_gauge(program_id, gauge_info)?;
+
+        // gauge account - check if gauge is voted one
+        if gauge.farm_id != gauge_vote.farm_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // votes of active lock can be changed by voter only
+        if cur_timestamp < voter.lock_end_timestamp {
+            return Err(FarmError::StillLocked.into());
+        }
+
+        // snapshot weight before this removal changes it
+        gauge.roll_epoch(gauge_controller.get_epoch(cur_timestamp));
+
+        voter.used_power = voter.used_power.saturating_sub(gauge_vote.power);
+        gauge.weight = gauge.weight.saturating_sub(gauge_vote.power);
+        gauge_controller.total_weight = gauge_controller.total_weight.saturating_sub(gauge_vote.power);
+        gauge_vote.power = 0;
+
+        // expired lock has no voting power once all its votes are removed
+        if voter.used_power == 0 {
+            voter.voting_power = 0;
+        }
+
+        // store vote, voter, gauge and gauge controller to network
+        gauge_vote.serialize(&mut *gauge_vote_info.data.borrow_mut())?;
+        voter.serialize(&mut *voter_info.data.borrow_mut())?;
+        gauge.serialize(&mut *gauge_info.data.borrow_mut())?;
+        gauge_controller
+            .serialize(&mut *gauge_controller_info.data.borrow_mut())
+            .map_err(|e| e.into())
+    }
+
+    /// process SetBoostOptions instruction
+    /// farm owner sets base share of effective stake before anyone stakes
+    pub fn process_set_boost_options(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        boost_base_numerator: u64,
+        boost_base_denominator: u64,
+    ) -> ProgramResult {
+        msg!("setting boost options ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to set options
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // token account - check if pool lp token account is for given farm account
+        if farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // effective stakes of existing users can't be switched, so nobody has to stake in this farm
+        if Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount > 0 {
+            return Err(FarmError::FarmNotEmpty.into());
+        }
+
+        // base share must not be more than deposit
+        if boost_base_numerator > boost_base_denominator {
+            return Err(FarmError::FeeTooHigh.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.boost_base_numerator = boost_base_numerator;
+        farm_pool.boost_base_denominator = boost_base_denominator;
+        farm_pool.total_effective_balance = 0;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process SetLoyaltyOptions instruction
+    /// farm owner sets loyalty period and max multiplier before anyone stakes
+    pub fn process_set_loyalty_options(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        loyalty_period: u64,
+        loyalty_max_numerator: u64,
+        loyalty_max_denominator: u64,
+    ) -> ProgramResult {
+        msg!("setting loyalty options ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to set options
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // token account - check if pool lp token account is for given farm account
+        if farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // effective stakes of existing users can't be switched, so nobody has to stake in this farm
+        if Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount > 0 {
+            return Err(FarmError::FarmNotEmpty.into());
+        }
+
+        // max multiplier must not be less than 1
+        if loyalty_period > 0 && (loyalty_max_denominator == 0 || loyalty_max_numerator < loyalty_max_denominator) {
+            return Err(FarmError::InvalidMultiplier.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.loyalty_period = loyalty_period;
+        farm_pool.loyalty_max_numerator = loyalty_max_numerator;
+        farm_pool.loyalty_max_denominator = loyalty_max_denominator;
+        farm_pool.total_effective_balance = 0;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process Poke instruction
+    /// harvests with old effective stake and refreshes it with user's current CRP lock and loyalty
+    pub fn process_poke(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("poking ...");
+
+        if accounts.len() < HARVEST_MANY_ACCOUNTS + 5 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // farm accounts are grouped as in HarvestMany instruction
+        let (farm_account_infos, rest) = accounts.split_at(HARVEST_MANY_ACCOUNTS);
+
+        // get account informations
+        let account_info_iter = &mut rest.iter();
+
+        // user's wallet account information
+        let user_wallet_info = next_account_info(account_info_iter)?;
+
+        // voter account information of the user
+        let voter_info = next_account_info(account_info_iter)?;
+
+        // gauge controller account information
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let farm_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        let farm_id_info = &farm_account_infos[0];
+        let authority_info = &farm_account_infos[1];
+        let user_info_account_info = &farm_account_infos[2];
+        let pool_lp_token_account_info = &farm_account_infos[3];
+        let pool_reward_token_account_info = &farm_account_infos[4];
+        let user_reward_token_account_info = &farm_account_infos[5];
+        let reward_ata_info = &farm_account_infos[6];
+        let reward_mint_info = &farm_account_infos[7];
+        let token_program_info = &farm_account_infos[8];
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, farm_program_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(farm_program_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let program_data = FarmProgram::unpack_from_slice(&farm_program_info.data.borrow())?;
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // validate farm and update it with up-to-date
+        let (mut farm_pool, mut user_info) = Self::prepare_harvest(
+            program_id,
+            &program_data,
+            user_wallet_info.key,
+            cur_timestamp,
+            farm_account_infos,
+            false,
+        )?;
+
+        if !farm_pool.has_effective_stake() {
+            return Err(FarmError::NotBoosted.into());
+        }
+
+        let lp_balance = Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount;
+
+        // pull emission of this farm before effective stake changes
+        let emitter_infos = Self::next_emitter_infos(&farm_pool, account_info_iter)?;
+        Self::pull_emission(
+            program_id,
+            emitter_infos,
+            reward_mint_info,
+            token_program_info,
+            pool_reward_token_account_info,
+            &mut farm_pool,
+            cur_timestamp,
+            lp_balance,
+        )?;
+
+        // harvest user's pending rewards with old effective stake
+        Self::harvest(
+            farm_id_info,
+            token_program_info,
+            pool_reward_token_account_info,
+            reward_mint_info,
+            reward_ata_info,
+            user_reward_token_account_info,
+            authority_info,
+            &program_data,
+            &mut farm_pool,
+            &mut user_info
+        )?;
+
+        // refresh effective stake with user's current CRP lock and loyalty
+        Self::update_boost(
+            program_id,
+            Some((voter_info, gauge_controller_info)),
+            &mut farm_pool,
+            &mut user_info,
+            lp_balance,
+            cur_timestamp,
+        )?;
+
+        msg!("effective stake {}", user_info.effective_balance);
+
+        // update reward debt
+        user_info.reward_debt = farm_pool.get_new_reward_debt(&user_info)?;
+
+        // save user's new info to network
+        user_info.pack_into_slice(&mut user_info_account_info.data.borrow_mut())?;
+
+        // save new farm account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process EnableOpenFunding instruction
+    /// farm owner lets anyone add rewards, funded amount so far is the basis of pro rata refunds
+    pub fn process_enable_open_funding(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("enabling open funding ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to enable open funding
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // funded amount must not be reset once sponsors are counted
+        if farm_pool.is_open_funding() {
+            return Ok(());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.open_funding = 1;
+        farm_pool.total_funded = farm_pool.total_rewards_added;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process RefundSponsor instruction
+    /// after the farm ends, unspent rewards are fixed once and each sponsor gets his pro rata share
+    pub fn process_refund_sponsor(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("refunding sponsor ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to refund from
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // sponsor account information to refund
+        let sponsor_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the sponsor's wallet
+        let sponsor_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let mut sponsor = Self::get_sponsor(program_id, sponsor_info, farm_id_info.key)?;
+        if sponsor.is_refunded > 0 {
+            return Err(FarmError::AlreadyRefunded.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+        if cur_timestamp <= farm_pool.end_timestamp {
+            return Err(FarmError::FarmNotEnded.into());
+        }
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token program - check if given token program is correct
+        if *token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token account - check if refund goes to sponsor's reward token account
+        if *sponsor_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let sponsor_reward_token_data = Self::unpack_token_account(sponsor_reward_token_account_info)?;
+        if sponsor_reward_token_data.owner != sponsor.wallet {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if sponsor_reward_token_data.mint != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // first refund distributes rewards until the end and fixes unspent rewards
+        if farm_pool.is_refunding == 0 {
+            let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+            let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+            Self::update_pool(
+                &mut farm_pool,
+                cur_timestamp,
+                pool_lp_token_data.amount,
+                pool_reward_token_data.amount,
+            )?;
+            farm_pool.refund_unspent = farm_pool.remained_reward_amount;
+            farm_pool.is_refunding = 1;
+        }
+
+        let refund = farm_pool.get_sponsor_refund(&sponsor)?;
+
+        msg!("refund {}", refund);
+
+        if refund > 0 {
+            // transfer sponsor's share from pool's reward token account
+            Self::token_transfer(
+                farm_id_info.key,
+                token_program_info.clone(),
+                pool_reward_token_account_info.clone(),
+                reward_mint_info.clone(),
+                sponsor_reward_token_account_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+                refund
+            )?;
+        }
+
+        farm_pool.remained_reward_amount -= refund;
+        farm_pool.total_sponsored = farm_pool.total_sponsored.saturating_sub(sponsor.amount);
+        sponsor.is_refunded = 1;
+        sponsor.refunded_amount = refund;
+
+        // store sponsor account data to network
+        sponsor.serialize(&mut *sponsor_info.data.borrow_mut())?;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process RotateVault instruction
+    /// farm owner moves LP or reward vault to a new token account of farm authority
+    pub fn process_rotate_vault(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("rotating vault ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to rotate vault
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // current lp or reward token account information in the farm pool
+        let current_vault_info = next_account_info(account_info_iter)?;
+
+        // new token account information to move the vault to
+        let new_vault_info = next_account_info(account_info_iter)?;
+
+        // token mint account information of the vault
+        let mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of the vault
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // find which vault is rotated
+        let is_lp_vault = *current_vault_info.key == farm_pool.pool_lp_token_account;
+        if !is_lp_vault && *current_vault_info.key != farm_pool.pool_reward_token_account {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+        let (vault_mint, vault_token_program) = if is_lp_vault {
+            (farm_pool.pool_mint_address, farm_pool.token_program_id)
+        } else {
+            (farm_pool.reward_mint_address, farm_pool.get_reward_token_program_id())
+        };
+
+        // token program - check if given token program is the vault's
+        if *token_program_info.key != vault_token_program {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // mint - check if given mint is the vault's
+        if *mint_info.key != vault_mint {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token account - check if new vault is another account under same token program
+        if  *new_vault_info.key == *current_vault_info.key ||
+            *new_vault_info.owner != vault_token_program {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let current_vault_data = Self::unpack_spl_token_account(current_vault_info)?;
+        let new_vault_data = Self::unpack_spl_token_account(new_vault_info)?;
+
+        // token account - check new vault as the farm initialization does
+        if new_vault_data.owner != *authority_info.key {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        if new_vault_data.mint != vault_mint {
+            return Err(FarmError::WrongPoolMint.into());
+        }
+        if new_vault_data.delegate.is_some() {
+            return Err(FarmError::InvalidDelegate.into());
+        }
+        if new_vault_data.state != AccountState::Initialized {
+            return Err(FarmError::NotInitialized.into());
+        }
+        if new_vault_data.close_authority.is_some() {
+            return Err(FarmError::InvalidCloseAuthority.into());
+        }
+
+        // carry the balance over
+        let amount = current_vault_data.amount;
+        if amount > 0 {
+            Self::token_transfer(
+                farm_id_info.key,
+                token_program_info.clone(),
+                current_vault_info.clone(),
+                mint_info.clone(),
+                new_vault_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+                amount
+            )?;
+        }
+
+        // token extensions like transfer fee can change the amount
+        let received_amount = Self::unpack_spl_token_account(new_vault_info)?.amount
+            .checked_sub(new_vault_data.amount)
+            .ok_or(FarmError::CalculationFailure)?;
+
+        if is_lp_vault {
+            // staked and unbonding balances of users must stay in the vault
+            if received_amount != amount {
+                return Err(FarmError::VaultAmountMismatch.into());
+            }
+            farm_pool.pool_lp_token_account = *new_vault_info.key;
+        } else {
+            // lost amount isn't distributed anymore
+            farm_pool.remained_reward_amount = farm_pool.remained_reward_amount
+                .saturating_sub(amount - received_amount);
+            farm_pool.pool_reward_token_account = *new_vault_info.key;
+        }
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process ProposeOwner instruction
+    /// farm owner proposes new owner, ownership moves when it is accepted
+    pub fn process_propose_owner(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("proposing owner ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to transfer ownership
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        // proposed owner account information
+        let new_owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.pending_owner = *new_owner_info.key;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process AcceptOwner instruction
+    /// proposed owner takes ownership of the farm
+    pub fn process_accept_owner(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("accepting owner ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to transfer ownership
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // proposed owner account information
+        let new_owner_info = next_account_info(account_info_iter)?;
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given wallet is proposed owner
+        if  farm_pool.pending_owner == Pubkey::default() ||
+            *new_owner_info.key != farm_pool.pending_owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if proposed owner is signer
+        if !new_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        farm_pool.owner = farm_pool.pending_owner;
+        farm_pool.pending_owner = Pubkey::default();
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process OpenPosition instruction
+    /// creates user info of another independent position of a wallet in the farm
+    pub fn process_open_position(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        position_index: u64,
+    ) -> ProgramResult {
+        msg!("opening position ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to open position in
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // wallet account information who owns the position, pays rent
+        let wallet_info = next_account_info(account_info_iter)?;
+
+        // user account information of the position
+        let user_info_account_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if wallet is signer
+        if !wallet_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // user info account - check if this position is opened already
+        if !user_info_account_info.data_is_empty() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        Self::create_user_info_account(
+            program_id,
+            farm_id_info.key,
+            wallet_info.key,
+            position_index,
+            user_info_account_info,
+            rent_info,
+            system_info,
+            wallet_info,
+        )?;
+
+        // store user info account data to network
+        UserInfo {
+            wallet: *wallet_info.key,
+            farm_id: *farm_id_info.key,
+            position_index,
+            ..UserInfo::default()
+        }.pack_into_slice(&mut user_info_account_info.data.borrow_mut())
+    }
+
+    /// process SetCrankOptions instruction
+    /// farm owner sets bounty paid to keepers from rewards not distributed yet
+    pub fn process_set_crank_options(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        crank_bounty: u64,
+        crank_interval: u64,
+    ) -> ProgramResult {
+        msg!("setting crank options ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to set options
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // farm owner account information, pays rent if farm account is extended
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // check if given owner is farm owner
+        if *owner_info.key != farm_pool.owner {
+            return Err(FarmError::WrongManager.into());
+        }
+
+        //singers - check if owner is signer
+        if !owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // paid cranks must be capped per interval
+        if crank_bounty > 0 && crank_interval == 0 {
+            return Err(FarmError::WrongPeriod.into());
+        }
+
+        // extend farm account if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            owner_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        farm_pool.crank_bounty = crank_bounty;
+        farm_pool.crank_interval = crank_interval;
+
+        // store farm pool account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process Crank instruction
+    /// anyone updates the farm up to current time, keeper gets bounty at most once per crank interval
+    pub fn process_crank(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("cranking ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // farm account information to crank
+        let farm_id_info = next_account_info(account_info_iter)?;
+
+        // authority information of this farm account
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // keeper's wallet account information
+        let keeper_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the keeper's wallet to receive bounty
+        let keeper_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // lp token account information in the farm pool
+        let pool_lp_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token account information in the farm pool
+        let pool_reward_token_account_info = next_account_info(account_info_iter)?;
+
+        // reward token mint account information
+        let reward_mint_info = next_account_info(account_info_iter)?;
+
+        // spl-token program address of reward token
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+
+        // clock account information to use timestamp
+        let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+        // check if clock sysvar program id is correct
+        if *clock_sysvar_info.key != Pubkey::from_str(CLOCK_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidClockSysvarId.into());
+        }
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        // farm account - check if the given program address and farm account are correct
+        if *authority_info.key != Self::authority_id(program_id, farm_id_info.key, farm_pool.nonce)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        //singers - check if keeper is signer
+        if !keeper_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        // get current timestamp(second)
+        let cur_timestamp: u64 = Clock::from_account_info(clock_sysvar_info)?.unix_timestamp as u64;
+
+        // farm account - check if this farm was allowed already
+        if !farm_pool.is_allowed() {
+            return Err(FarmError::NotAllowed.into());
+        }
+
+        // farm account - This farm was not started yet
+        if cur_timestamp < farm_pool.start_timestamp {
+            return Err(FarmError::NotStarted.into());
+        }
+
+        // token program - check if given token program is reward token's program
+        if *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        // token account - check if owner is saved token program
+        if  *pool_lp_token_account_info.owner != farm_pool.token_program_id ||
+            *pool_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() ||
+            *keeper_reward_token_account_info.owner != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        // token account - check keeper's reward token mint
+        if Self::unpack_token_account(keeper_reward_token_account_info)?.mint != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        let lp_balance = Self::unpack_spl_token_account(pool_lp_token_account_info)?.amount;
+        let reward_balance = Self::unpack_token_account(pool_reward_token_account_info)?.amount;
+
+        //update this pool with up-to-date, distribute reward token
+        Self::update_pool(
+            &mut farm_pool,
+            cur_timestamp,
+            lp_balance,
+            reward_balance,
+        )?;
+
+        // pull emission of this farm
+        let emitter_infos = Self::next_emitter_infos(&farm_pool, account_info_iter)?;
+        Self::pull_emission(
+            program_id,
+            emitter_infos,
+            reward_mint_info,
+            reward_token_program_info,
+            pool_reward_token_account_info,
+            &mut farm_pool,
+            cur_timestamp,
+            lp_balance,
+        )?;
+
+        // pay bounty from rewards not distributed yet, so rewards of stakers are kept
+        let bounty = farm_pool.get_crank_bounty(cur_timestamp).min(reward_balance);
+        if bounty > 0 {
+            msg!("paying crank bounty {}", bounty);
+            Self::token_transfer(
+                farm_id_info.key,
+                reward_token_program_info.clone(),
+                pool_reward_token_account_info.clone(),
+                reward_mint_info.clone(),
+                keeper_reward_token_account_info.clone(),
+                authority_info.clone(),
+                farm_pool.nonce,
+                bounty
+            )?;
+            farm_pool.remained_reward_amount -= bounty;
+            farm_pool.last_crank_timestamp = cur_timestamp;
+            farm_pool.total_crank_bounties = farm_pool.total_crank_bounties.saturating_add(bounty);
+        }
+
+        // save new farm account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
+    }
+
+    /// process InitializeAuditLog instruction
+    /// super owner creates audit log, privileged instructions have to record themselves in it after then
+    pub fn process_initialize_audit_log(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        msg!("initializing audit log ...");
+
+        // get account informations
+        let account_info_iter = &mut accounts.iter();
+
+        // audit log account information to create
+        let audit_log_info = next_account_info(account_info_iter)?;
+
+        // farm program data account info
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        // super owner account information, pays rent of audit log and extended program data
+        let super_owner_info = next_account_info(account_info_iter)?;
+
+        let rent_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+
+        // check if rent sysvar program id is correct
+        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidRentSysvarId.into());
+        }
+
+        // check if system program id is correct
+        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidSystemProgramId.into());
+        }
+
+        // check if given program account is correct
+        Self::assert_program_account(program_id, program_data_info.key)?;
+
+        // check if given program data is initialized
+        if Self::is_zero_account(program_data_info) {
+            return Err(FarmError::NotInitializedProgramData.into());
+        }
+
+        let mut program_data = FarmProgram::unpack_from_slice(&program_data_info.data.borrow())?;
+
+        // check if given super owner is program's super owner
+        if *super_owner_info.key != program_data.super_owner {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        //singers - check if super owner is signer
+        if !super_owner_info.is_signer {
+            return Err(FarmError::InvalidSigner.into());
+        }
+
+        let seeds = [
+            PREFIX.as_bytes(),
+            AUDIT_LOG_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        let bump = Self::assert_seeds_account(program_id, &seeds, audit_log_info.key)?;
+
+        // audit log account - check if audit log is created already
+        if !audit_log_info.data_is_empty() {
+            return Err(FarmError::AlreadyInUse.into());
+        }
+
+        create_or_allocate_account_raw(
+            *program_id,
+            audit_log_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<AuditLog>(),
+            &[
+                PREFIX.as_bytes(),
+                AUDIT_LOG_SEED.as_bytes(),
+                program_id.as_ref(),
+                &[bump],
+            ],
+        )?;
+
+        // extend program data account if it was created with an older layout
+        realloc_account(
+            program_data_info,
+            rent_info,
+            system_info,
+            super_owner_info,
+            get_packed_len::<FarmProgram>(),
+        )?;
+
+        program_data.audit_log = 1;
+
+        // store program data to network
+        program_data.pack_into_slice(&mut program_data_info.data.borrow_mut())
+    }
+
+    // update pool information with up-to-date, distribute reward token
+    pub fn update_pool(
+        farm_pool: &mut FarmPool, 
+        cur_timestamp: u64, 
         lp_balance: u64, 
         reward_balance: u64, 
     ) -> Result<(), ProgramError>{
@@ -1298,165 +7279,1095 @@ impl Processor {
             return Ok(());
         }
 
-        if lp_balance == 0 {
-            farm_pool.last_timestamp = cur_timestamp;
-            return Ok(());
+        // unbonding lp doesn't earn rewards, boosted farm distributes rewards by effective stake
+        let lp_balance = farm_pool.get_total_stake(lp_balance);
+
+        if lp_balance == 0 {
+            farm_pool.last_timestamp = cur_timestamp;
+            return Ok(());
+        }
+        // update reward per share net and last distributed timestamp
+        farm_pool.update_share(cur_timestamp, lp_balance, reward_balance)?;
+        farm_pool.last_timestamp = cur_timestamp;
+        Ok(())
+    }
+    pub fn harvest<'a>(
+        farm_id_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        pool_reward_token_account_info: &AccountInfo<'a>,
+        reward_mint_info: &AccountInfo<'a>,
+        reward_ata_info: &AccountInfo<'a>,
+        user_reward_token_account_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        program_data:&FarmProgram,
+        farm_pool:&mut FarmPool,
+        user_info:&mut UserInfo
+    )->Result<(u64, u64), ProgramError>{
+        // get pending amount
+        let mut pending: u64 = farm_pool.pending_rewards(user_info)?;
+
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        if pool_reward_token_data.amount < pending {
+            pending = pool_reward_token_data.amount;
+        }
+        
+        // harvest
+        let mut harvest_fee = 0;
+        if pending > 0 {
+            // harvest fee
+            harvest_fee = farm_pool.get_harvest_fee(pending, program_data)?;
+            
+            // transfer harvest fee to fee owner wallet
+            Self::token_transfer(
+                farm_id_info.key,
+                token_program_info.clone(), 
+                pool_reward_token_account_info.clone(), 
+                reward_mint_info.clone(),
+                reward_ata_info.clone(), 
+                authority_info.clone(), 
+                farm_pool.nonce, 
+                harvest_fee
+            )?;
+
+            // real pending amount except fee
+            let _pending = pending - harvest_fee;
+
+            // transfer real pending amount from reward pool to user reward token account
+            Self::token_transfer(
+                farm_id_info.key,
+                token_program_info.clone(), 
+                pool_reward_token_account_info.clone(), 
+                reward_mint_info.clone(),
+                user_reward_token_account_info.clone(), 
+                authority_info.clone(), 
+                farm_pool.nonce, 
+                _pending
+            )?;
+
+            user_info.reward_debt += pending;
+
+            farm_pool.total_rewards_harvested = farm_pool.total_rewards_harvested.saturating_add(_pending);
+            farm_pool.total_harvest_fees = farm_pool.total_harvest_fees.saturating_add(harvest_fee);
+        }
+
+        // kept rewards are paid by this harvest
+        user_info.reward_credit = 0;
+
+        // harvested amount to user and harvest fee
+        Ok((pending - harvest_fee, harvest_fee))
+    }
+    /// create user info account for given wallet and farm
+    pub fn create_user_info_account<'a>(
+        program_id: &Pubkey,
+        farm_id: &Pubkey,
+        wallet: &Pubkey,
+        position_index: u64,
+        user_info_account_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        payer_info: &AccountInfo<'a>,
+    ) -> Result<(), ProgramError> {
+        msg!("creating user info account ... ");
+
+        let (found_user_info_key, bump) = find_user_info_address(program_id, farm_id, wallet, position_index);
+
+        if found_user_info_key != *user_info_account_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        let size = get_packed_len::<UserInfo>();
+        let index = position_index.to_le_bytes();
+        let bump = [bump];
+        // default position keeps the seeds of user infos created before positions
+        let mut seeds: Vec<&[u8]> = vec![
+            PREFIX.as_bytes(),
+            farm_id.as_ref(),
+            wallet.as_ref(),
+        ];
+        if position_index > 0 {
+            seeds.push(&index);
+        }
+        seeds.push(&bump);
+
+        // Create account with enough space
+        create_or_allocate_account_raw(
+            *program_id,
+            user_info_account_info,
+            rent_info,
+            system_info,
+            payer_info,
+            size,
+            &seeds,
+        )
+    }
+    /// check if given account is the PDA of the user info's position
+    pub fn assert_user_info_account(
+        program_id: &Pubkey,
+        user_info_account_info: &AccountInfo,
+        user_info: &UserInfo,
+    ) -> Result<(), ProgramError> {
+        let (user_info_key, _) = find_user_info_address(
+            program_id,
+            &user_info.farm_id,
+            &user_info.wallet,
+            user_info.position_index,
+        );
+        if user_info_key != *user_info_account_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        Ok(())
+    }
+    /// check if given token account holds receipt tokens of this farm for given wallet
+    pub fn assert_receipt_account(
+        farm_pool: &FarmPool,
+        receipt_mint_info: &AccountInfo,
+        receipt_token_account_info: &AccountInfo,
+        wallet: &Pubkey,
+    ) -> Result<(), ProgramError> {
+        if *receipt_mint_info.key != farm_pool.receipt_mint {
+            return Err(FarmError::InvalidReceiptMint.into());
+        }
+        if *receipt_token_account_info.owner != farm_pool.token_program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let receipt_token_data = Self::unpack_spl_token_account(receipt_token_account_info)?;
+        if receipt_token_data.mint != farm_pool.receipt_mint {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+        if receipt_token_data.owner != *wallet {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        Ok(())
+    }
+    /// get authority by given program address.
+    pub fn authority_id(
+        program_id: &Pubkey,
+        my_info: &Pubkey,
+        nonce: u8,
+    ) -> Result<Pubkey, FarmError> {
+        Pubkey::create_program_address(&[&my_info.to_bytes()[..32], &[nonce]], program_id)
+            .or(Err(FarmError::InvalidProgramAddress))
+    }
+    pub fn is_zero_account(account_info:&AccountInfo)->bool{
+        let account_data: &[u8] = &account_info.data.borrow();
+        let len = account_data.len();
+        account_data[..len-1].iter().all(|b| *b == 0)
+    }
+    pub fn is_allowed(token_a_mint:&Pubkey, token_b_mint:&Pubkey)->Result<u8, ProgramError> {
+        let mut is_allowed = 0;
+        if  *token_a_mint == Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?  ||
+            *token_b_mint == Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?  ||
+            *token_a_mint == Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? || 
+            *token_b_mint == Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? ||
+            *token_a_mint == Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? || 
+            *token_b_mint == Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?
+        {
+            is_allowed = 1;
+        }
+        Ok(is_allowed)
+    }
+    pub fn is_locked_farm(token_a_mint:&Pubkey, token_b_mint:&Pubkey)->Result<bool, ProgramError> {
+        let mut result = false;
+        let sol_mint = Pubkey::from_str(SOL_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+        let eth_mint = Pubkey::from_str(ETH_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+        let crp_mint = Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+        let usdc_mint = Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+        let usdt_mint = Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+
+        if  (*token_a_mint == sol_mint && *token_b_mint == usdc_mint) ||
+            (*token_a_mint == usdc_mint && *token_b_mint == sol_mint) || //SOL-USDC
+            (*token_a_mint == sol_mint && *token_b_mint == usdt_mint) ||
+            (*token_a_mint == usdt_mint && *token_b_mint == sol_mint) || //SOL-USDT
+            (*token_a_mint == eth_mint && *token_b_mint == usdc_mint) ||
+            (*token_a_mint == usdc_mint && *token_b_mint == eth_mint) || //ETH-USDC
+            (*token_a_mint == eth_mint && *token_b_mint == usdt_mint) ||
+            (*token_a_mint == usdt_mint && *token_b_mint == eth_mint) || //ETH-USDT
+            (*token_a_mint == usdc_mint && *token_b_mint == crp_mint) ||
+            (*token_a_mint == crp_mint && *token_b_mint == usdc_mint) || //CRP-USDC
+            (*token_a_mint == usdt_mint && *token_b_mint == crp_mint) ||
+            (*token_a_mint == crp_mint && *token_b_mint == usdt_mint) || //CRP-USDT
+            (*token_a_mint == sol_mint && *token_b_mint == crp_mint) ||
+            (*token_a_mint == crp_mint && *token_b_mint == sol_mint) ||  //SOL-CRP
+            (*token_a_mint == eth_mint && *token_b_mint == crp_mint) ||
+            (*token_a_mint == crp_mint && *token_b_mint == eth_mint) ||  //ETH-CRP
+            (*token_a_mint == eth_mint && *token_b_mint == sol_mint) || 
+            (*token_a_mint == sol_mint && *token_b_mint == eth_mint)     //SOL-ETH
+        {
+            result = true
+        }
+        Ok(result)
+    }
+
+    /// issue a spl_token `TransferChecked` instruction.
+    /// works with both spl-token and Token-2022 programs
+    pub fn token_transfer<'a>(
+        pool: &Pubkey,
+        token_program: AccountInfo<'a>,
+        source: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        destination: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        nonce: u8,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        let pool_bytes = pool.to_bytes();
+        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
+        let signers = &[&authority_signature_seeds[..]];
+
+        // native SOL is moved by system program, source is a wallet or the farm authority
+        if *token_program.key == system_program::id() {
+            return invoke_signed(
+                &system_instruction::transfer(source.key, destination.key, amount),
+                &[source, destination, token_program],
+                signers,
+            );
+        }
+
+        let decimals = Self::unpack_mint(&mint)?.decimals;
+        let ix = spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            source.key,
+            mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+            decimals,
+        )?;
+        invoke_signed(
+            &ix,
+            &[source, mint, destination, authority, token_program],
+            signers,
+        )
+    } 
+    /// create missing associated reward token accounts of a user and fee owner before harvest, payer pays rent
+    /// native SOL rewards are paid to wallets, so nothing is created for them
+    pub fn create_reward_token_accounts<'a>(
+        farm_pool: &FarmPool,
+        program_data: &FarmProgram,
+        payer_info: &AccountInfo<'a>,
+        user_reward_token_account_info: &AccountInfo<'a>,
+        reward_ata_info: &AccountInfo<'a>,
+        reward_mint_info: &AccountInfo<'a>,
+        reward_token_program_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        associated_token_program_info: &AccountInfo<'a>,
+        fee_owner_info: &AccountInfo<'a>,
+    ) -> Result<(), ProgramError> {
+        // check if associated token account program id is correct
+        if *associated_token_program_info.key != Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // farm account - check fee owner
+        if *fee_owner_info.key != program_data.fee_owner {
+            return Err(FarmError::InvalidFeeAccount.into());
+        }
+
+        if  farm_pool.is_native_reward() ||
+            (!user_reward_token_account_info.data_is_empty() && !reward_ata_info.data_is_empty()) {
+            return Ok(());
+        }
+
+        // token program - check if given token program is correct
+        if *reward_token_program_info.key != farm_pool.get_reward_token_program_id() {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+
+        // reward mint - check if reward mint is this farm's reward mint
+        if *reward_mint_info.key != farm_pool.reward_mint_address {
+            return Err(FarmError::WrongAccountMint.into());
+        }
+
+        if user_reward_token_account_info.data_is_empty() {
+            Self::create_associated_token_account(
+                payer_info,
+                user_reward_token_account_info,
+                payer_info,
+                reward_mint_info,
+                system_info,
+                reward_token_program_info,
+                rent_info,
+                associated_token_program_info,
+            )?;
+        }
+
+        if reward_ata_info.data_is_empty() {
+            Self::create_associated_token_account(
+                payer_info,
+                reward_ata_info,
+                fee_owner_info,
+                reward_mint_info,
+                system_info,
+                reward_token_program_info,
+                rent_info,
+                associated_token_program_info,
+            )?;
+        }
+        Ok(())
+    }
+    /// create associated token account of given wallet by associated token account program
+    pub fn create_associated_token_account<'a>(
+        payer_info: &AccountInfo<'a>,
+        associated_account_info: &AccountInfo<'a>,
+        wallet_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        associated_token_program_info: &AccountInfo<'a>,
+    ) -> Result<(), ProgramError> {
+        // check if associated token account program id is correct
+        if *associated_token_program_info.key != Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        let ix = Instruction {
+            program_id: *associated_token_program_info.key,
+            accounts: vec![
+                AccountMeta::new(*payer_info.key, true),
+                AccountMeta::new(*associated_account_info.key, false),
+                AccountMeta::new_readonly(*wallet_info.key, false),
+                AccountMeta::new_readonly(*mint_info.key, false),
+                AccountMeta::new_readonly(*system_info.key, false),
+                AccountMeta::new_readonly(*token_program_info.key, false),
+                AccountMeta::new_readonly(*rent_info.key, false),
+            ],
+            data: vec![],
+        };
+        invoke(
+            &ix,
+            &[
+                payer_info.clone(),
+                associated_account_info.clone(),
+                wallet_info.clone(),
+                mint_info.clone(),
+                system_info.clone(),
+                token_program_info.clone(),
+                rent_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )
+    }
+    /// issue a spl_token `MintTo` instruction.
+    pub fn token_mint_to<'a>(
+        pool: &Pubkey,
+        token_program: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        destination: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        nonce: u8,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        let pool_bytes = pool.to_bytes();
+        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
+        let signers = &[&authority_signature_seeds[..]];
+        let ix = spl_token_2022::instruction::mint_to(
+            token_program.key,
+            mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+        )?;
+        invoke_signed(
+            &ix,
+            &[mint, destination, authority, token_program],
+            signers,
+        )
+    }
+
+    /// issue a spl_token `Burn` instruction.
+    /// the owner of source account has to sign this transaction
+    pub fn token_burn<'a>(
+        token_program: AccountInfo<'a>,
+        source: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        owner: AccountInfo<'a>,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        let ix = spl_token_2022::instruction::burn(
+            token_program.key,
+            source.key,
+            mint.key,
+            owner.key,
+            &[],
+            amount,
+        )?;
+        invoke(
+            &ix,
+            &[source, mint, owner, token_program],
+        )
+    }
+    /// issue a spl_token `CloseAccount` instruction signed by farm authority
+    pub fn token_close_account<'a>(
+        pool: &Pubkey,
+        token_program: AccountInfo<'a>,
+        account: AccountInfo<'a>,
+        destination: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        nonce: u8,
+    ) -> Result<(), ProgramError> {
+        let pool_bytes = pool.to_bytes();
+        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
+        let signers = &[&authority_signature_seeds[..]];
+        let ix = spl_token_2022::instruction::close_account(
+            token_program.key,
+            account.key,
+            destination.key,
+            authority.key,
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[account, destination, authority, token_program],
+            signers,
+        )
+    }
+    /// issue a spl_token `FreezeAccount` instruction signed by farm authority
+    pub fn token_freeze_account<'a>(
+        pool: &Pubkey,
+        token_program: AccountInfo<'a>,
+        account: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        nonce: u8,
+    ) -> Result<(), ProgramError> {
+        let pool_bytes = pool.to_bytes();
+        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
+        let signers = &[&authority_signature_seeds[..]];
+        let ix = spl_token_2022::instruction::freeze_account(
+            token_program.key,
+            account.key,
+            mint.key,
+            authority.key,
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[account, mint, authority, token_program],
+            signers,
+        )
+    }
+    /// issue a spl_token `ThawAccount` instruction signed by farm authority
+    pub fn token_thaw_account<'a>(
+        pool: &Pubkey,
+        token_program: AccountInfo<'a>,
+        account: AccountInfo<'a>,
+        mint: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        nonce: u8,
+    ) -> Result<(), ProgramError> {
+        let pool_bytes = pool.to_bytes();
+        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
+        let signers = &[&authority_signature_seeds[..]];
+        let ix = spl_token_2022::instruction::thaw_account(
+            token_program.key,
+            account.key,
+            mint.key,
+            authority.key,
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[account, mint, authority, token_program],
+            signers,
+        )
+    }
+    /// thaw receipt token account if it was frozen by farm authority
+    /// new receipt accounts are not frozen until the farm mints to them first
+    pub fn thaw_receipt_account<'a>(
+        pool: &Pubkey,
+        token_program: &AccountInfo<'a>,
+        receipt_token_account: &AccountInfo<'a>,
+        receipt_mint: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        nonce: u8,
+    ) -> Result<(), ProgramError> {
+        if Self::unpack_spl_token_account(receipt_token_account)?.state != AccountState::Frozen {
+            return Ok(());
+        }
+        Self::token_thaw_account(
+            pool,
+            token_program.clone(),
+            receipt_token_account.clone(),
+            receipt_mint.clone(),
+            authority.clone(),
+            nonce,
+        )
+    }
+    /// read whitelisted fee token account of given mint
+    pub fn get_fee_token(
+        program_id: &Pubkey,
+        fee_token_info: &AccountInfo,
+        mint: &Pubkey,
+    ) -> Result<FeeToken, ProgramError> {
+        let seeds = [
+            PREFIX.as_bytes(),
+            FEE_TOKEN_SEED.as_bytes(),
+            mint.as_ref(),
+        ];
+        let (fee_token_key, _bump) = Pubkey::find_program_address(&seeds, program_id);
+        if fee_token_key != *fee_token_info.key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        if fee_token_info.owner != program_id || fee_token_info.data_is_empty() {
+            return Err(FarmError::FeeTokenNotWhitelisted.into());
+        }
+        let fee_token = try_from_slice_unchecked::<FeeToken>(&fee_token_info.data.borrow())?;
+        if fee_token.is_enabled == 0 || fee_token.mint != *mint {
+            return Err(FarmError::FeeTokenNotWhitelisted.into());
+        }
+        Ok(fee_token)
+    }
+    /// compute USDC value of fee token amount from reserves of the whitelisted amm pool
+    pub fn get_usdc_value(
+        program_data: &FarmProgram,
+        fee_token: &FeeToken,
+        amm_id_info: &AccountInfo,
+        amm_token_a_account_info: &AccountInfo,
+        amm_token_b_account_info: &AccountInfo,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        // check if given amm id is the pool whitelisted for this token
+        if  *amm_id_info.key != fee_token.amm_id ||
+            *amm_id_info.owner != program_data.amm_program_id {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        let amm_swap = SwapVersion::unpack(&amm_id_info.data.borrow())?;
+
+        // check if reserve accounts are the amm pool's token accounts
+        if  *amm_swap.token_a_account() != *amm_token_a_account_info.key ||
+            *amm_swap.token_b_account() != *amm_token_b_account_info.key {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        let token_a_data = Self::unpack_spl_token_account(amm_token_a_account_info)?;
+        let token_b_data = Self::unpack_spl_token_account(amm_token_b_account_info)?;
+
+        // find which side of the pool is fee token
+        let (token_reserve, usdc_reserve) = if *amm_swap.token_a_mint() == fee_token.mint {
+            (token_a_data.amount, token_b_data.amount)
+        } else if *amm_swap.token_b_mint() == fee_token.mint {
+            (token_b_data.amount, token_a_data.amount)
+        } else {
+            return Err(FarmError::InvalidPricePool.into());
+        };
+
+        if token_reserve == 0 {
+            return Err(FarmError::InvalidPricePool.into());
+        }
+
+        // reserves can be moved by a flash swap, so pool price has to be close to reference price
+        fee_token.check_pool_price(token_reserve, usdc_reserve)?;
+
+        let value = u128::from(amount)
+            .checked_mul(u128::from(usdc_reserve))
+            .ok_or(FarmError::CalculationFailure)?
+            .checked_div(u128::from(token_reserve))
+            .ok_or(FarmError::CalculationFailure)?;
+        u64::try_from(value).map_err(|_| FarmError::CalculationFailure.into())
+    }
+    /// get multisig authority which signs instructions of executed proposals
+    pub fn multisig_authority_id(program_id: &Pubkey, multisig: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                MULTISIG_SEED.as_bytes(),
+                multisig.as_ref(),
+            ],
+            program_id,
+        )
+    }
+    /// check if signer count or threshold is valid for spl-token multisig
+    pub fn is_valid_signer_index(index: usize) -> bool {
+        (1..=MAX_SIGNERS).contains(&index)
+    }
+    /// read initialized multisig account owned by this program
+    pub fn get_multisig(program_id: &Pubkey, multisig_info: &AccountInfo) -> Result<Multisig, ProgramError> {
+        if multisig_info.owner != program_id {
+            return Err(FarmError::InvalidMultisig.into());
+        }
+        Multisig::unpack(&multisig_info.data.borrow()).map_err(|_| FarmError::InvalidMultisig.into())
+    }
+    /// read proposal account of given multisig which is not executed yet
+    pub fn get_proposal(program_id: &Pubkey, proposal_info: &AccountInfo, multisig: &Pubkey) -> Result<Proposal, ProgramError> {
+        if proposal_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let proposal = try_from_slice_unchecked::<Proposal>(&proposal_info.data.borrow())?;
+        if proposal.multisig != *multisig {
+            return Err(FarmError::InvalidMultisig.into());
+        }
+        if proposal.is_executed != 0 {
+            return Err(FarmError::ProposalExecuted.into());
+        }
+        Ok(proposal)
+    }
+    /// check if given timelock account is correct, returns its bump
+    pub fn assert_timelock_account(program_id: &Pubkey, key: &Pubkey) -> Result<u8, ProgramError> {
+        let seeds = [
+            PREFIX.as_bytes(),
+            TIMELOCK_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        let (timelock_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if timelock_key != *key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        Ok(bump)
+    }
+    /// read queued program data change from timelock account
+    pub fn get_queued_change(program_id: &Pubkey, timelock_info: &AccountInfo) -> Result<ProgramDataChange, ProgramError> {
+        Self::assert_timelock_account(program_id, timelock_info.key)?;
+        if timelock_info.owner != program_id || timelock_info.data_is_empty() {
+            return Err(FarmError::NothingQueued.into());
+        }
+        let change = try_from_slice_unchecked::<ProgramDataChange>(&timelock_info.data.borrow())?;
+        if change.is_queued == 0 {
+            return Err(FarmError::NothingQueued.into());
+        }
+        Ok(change)
+    }
+    /// pull emission of a farm with its current allocation points and set new allocation points
+    /// `alloc_accounts` are emitter, emitter authority, emitter vault, farm, pool lp token account,
+    /// pool reward token account, reward mint and reward token program
+    pub fn update_alloc_point<'a>(
+        program_id: &Pubkey,
+        alloc_accounts: &[AccountInfo<'a>],
+        payer_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        cur_timestamp: u64,
+        alloc_point: u64,
+    ) -> ProgramResult {
+        let emitter_info = &alloc_accounts[0];
+        let emitter_authority_info = &alloc_accounts[1];
+        let emitter_vault_info = &alloc_accounts[2];
+        let farm_id_info = &alloc_accounts[3];
+        let pool_lp_token_account_info = &alloc_accounts[4];
+        let pool_reward_token_account_info = &alloc_accounts[5];
+        let reward_mint_info = &alloc_accounts[6];
+        let reward_token_program_info = &alloc_accounts[7];
+
+        // farm account - check if farm account's owner is program id
+        if farm_id_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        // borrow farm pool account data
+        let mut farm_pool = FarmPool::unpack_from_slice(&farm_id_info.data.borrow())?;
+
+        let emitter = Self::get_emitter(program_id, emitter_info)?;
+
+        // reward mint - farm has to reward emitter's token
+        if  farm_pool.reward_mint_address != emitter.reward_mint ||
+            *reward_mint_info.key != emitter.reward_mint {
+            return Err(FarmError::WrongAccountMint.into());
         }
-        // update reward per share net and last distributed timestamp
-        farm_pool.update_share(cur_timestamp, lp_balance, reward_balance)?;
-        farm_pool.last_timestamp = cur_timestamp;
-        Ok(())
+
+        // token account - check if pool lp token account & pool reward token account is for given farm account
+        if  farm_pool.pool_lp_token_account != *pool_lp_token_account_info.key ||
+            farm_pool.pool_reward_token_account != *pool_reward_token_account_info.key {
+            return Err(FarmError::InvalidTokenAccount.into());
+        }
+
+        let pool_lp_token_data = Self::unpack_spl_token_account(pool_lp_token_account_info)?;
+        let pool_reward_token_data = Self::unpack_token_account(pool_reward_token_account_info)?;
+
+        //update this pool with up-to-date, distribute reward token
+        Self::update_pool(
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+            pool_reward_token_data.amount,
+        )?;
+
+        // pull emission so far with old allocation points
+        let emitter_infos = if farm_pool.is_emitter_farm() {
+            Some((emitter_info, emitter_authority_info, emitter_vault_info))
+        } else {
+            None
+        };
+        Self::pull_emission(
+            program_id,
+            emitter_infos,
+            reward_mint_info,
+            reward_token_program_info,
+            pool_reward_token_account_info,
+            &mut farm_pool,
+            cur_timestamp,
+            pool_lp_token_data.amount,
+        )?;
+
+        // pull_emission stores emitter, so read it again
+        let mut emitter = Self::get_emitter(program_id, emitter_info)?;
+        emitter.update(cur_timestamp)?;
+
+        emitter.total_alloc_point = emitter.total_alloc_point
+            .checked_sub(farm_pool.alloc_point).ok_or(FarmError::CalculationFailure)?
+            .checked_add(alloc_point).ok_or(FarmError::CalculationFailure)?;
+        farm_pool.alloc_point = alloc_point;
+        farm_pool.emitter_reward_debt = emitter.reward_per_point_net;
+
+        // store emitter account data to network
+        emitter.serialize(&mut *emitter_info.data.borrow_mut())?;
+
+        // extend farm account to store allocation points if it was created with an older layout
+        realloc_account(
+            farm_id_info,
+            rent_info,
+            system_info,
+            payer_info,
+            get_packed_len::<FarmPool>(),
+        )?;
+
+        // store farm account data to network
+        farm_pool.pack_into_slice(&mut farm_id_info.data.borrow_mut())
     }
-    pub fn harvest<'a>(
-        farm_id_info: &AccountInfo<'a>,
+    /// check if given emitter account is correct, returns its bump
+    pub fn assert_emitter_account(program_id: &Pubkey, key: &Pubkey) -> Result<u8, ProgramError> {
+        let seeds = [
+            PREFIX.as_bytes(),
+            EMITTER_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        let (emitter_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if emitter_key != *key {
+            return Err(FarmError::InvalidEmitter.into());
+        }
+        Ok(bump)
+    }
+    /// read emitter from emitter account
+    pub fn get_emitter(program_id: &Pubkey, emitter_info: &AccountInfo) -> Result<Emitter, ProgramError> {
+        Self::assert_emitter_account(program_id, emitter_info.key)?;
+        if emitter_info.owner != program_id || emitter_info.data_is_empty() {
+            return Err(FarmError::InvalidEmitter.into());
+        }
+        try_from_slice_unchecked::<Emitter>(&emitter_info.data.borrow()).map_err(|e| e.into())
+    }
+    /// read emitter accounts which follow if given farm is registered to the emitter
+    pub fn next_emitter_infos<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        farm_pool: &FarmPool,
+        account_info_iter: &mut I,
+    ) -> Result<Option<(&'a AccountInfo<'b>, &'a AccountInfo<'b>, &'a AccountInfo<'b>)>, ProgramError> {
+        if !farm_pool.is_emitter_farm() {
+            return Ok(None);
+        }
+        let emitter_info = next_account_info(account_info_iter)?;
+        let emitter_authority_info = next_account_info(account_info_iter)?;
+        let emitter_vault_info = next_account_info(account_info_iter)?;
+        Ok(Some((emitter_info, emitter_authority_info, emitter_vault_info)))
+    }
+    /// pull emission of a registered farm from emitter vault to farm's reward vault
+    /// pulled amount is distributed to current stakers, emission earned while nobody stakes is dropped
+    pub fn pull_emission<'a>(
+        program_id: &Pubkey,
+        emitter_infos: Option<(&AccountInfo<'a>, &AccountInfo<'a>, &AccountInfo<'a>)>,
+        reward_mint_info: &AccountInfo<'a>,
         token_program_info: &AccountInfo<'a>,
         pool_reward_token_account_info: &AccountInfo<'a>,
-        reward_ata_info: &AccountInfo<'a>,
-        user_reward_token_account_info: &AccountInfo<'a>,
-        authority_info: &AccountInfo<'a>,
-        program_data:&FarmProgram,
-        farm_pool:&FarmPool,
-        user_info:&mut UserInfo
-    )->Result<(), ProgramError>{
-        // get pending amount
-        let mut pending: u64 = farm_pool.pending_rewards(user_info)?;
-        msg!("deposit={}", user_info.deposit_balance);
-        msg!("reward_debt={}", user_info.reward_debt);
-        msg!("pending={}", pending);
+        farm_pool: &mut FarmPool,
+        cur_timestamp: u64,
+        lp_balance: u64,
+    ) -> Result<(), ProgramError> {
+        let (emitter_info, emitter_authority_info, emitter_vault_info) = match emitter_infos {
+            Some(emitter_infos) => emitter_infos,
+            None => return Ok(()),
+        };
 
-        let pool_reward_token_data = Account::unpack_from_slice(&pool_reward_token_account_info.data.borrow())?;
+        let mut emitter = Self::get_emitter(program_id, emitter_info)?;
 
-        if pool_reward_token_data.amount < pending {
-            pending = pool_reward_token_data.amount;
+        // emitter account - check emitter vault, authority and reward token
+        if  emitter.reward_vault != *emitter_vault_info.key ||
+            *emitter_authority_info.key != Self::authority_id(program_id, emitter_info.key, emitter.nonce)? {
+            return Err(FarmError::InvalidEmitter.into());
+        }
+        if  emitter.reward_mint != *reward_mint_info.key ||
+            emitter.reward_mint != farm_pool.reward_mint_address ||
+            emitter.token_program_id != *token_program_info.key {
+            return Err(FarmError::InvalidEmitter.into());
         }
-        
-        // harvest
-        if pending > 0 {
-            // harvest fee
-            let harvest_fee = farm_pool.get_harvest_fee(pending, &program_data)?;
-            
-            // transfer harvest fee to fee owner wallet
-            Self::token_transfer(
-                farm_id_info.key,
-                token_program_info.clone(), 
-                pool_reward_token_account_info.clone(), 
-                reward_ata_info.clone(), 
-                authority_info.clone(), 
-                farm_pool.nonce, 
-                harvest_fee
-            )?;
 
-            // real pending amount except fee
-            let _pending = pending - harvest_fee;
+        emitter.update(cur_timestamp)?;
+        let emission = emitter.get_farm_emission(farm_pool)?;
+        farm_pool.emitter_reward_debt = emitter.reward_per_point_net;
 
-            // transfer real pending amount from reward pool to user reward token account
+        // unbonding lp doesn't earn rewards, boosted farm distributes rewards by effective stake
+        let lp_balance = farm_pool.get_total_stake(lp_balance);
+
+        // emitter vault may not be funded enough
+        // emission which can't be delivered now stays pending for the next pull
+        let vault_amount = Self::unpack_spl_token_account(emitter_vault_info)?.amount;
+        let emission = farm_pool.take_emission(emission, lp_balance, vault_amount);
+
+        if emission > 0 {
+            msg!("pulling emission {}", emission);
+            let reward_amount = Self::unpack_token_account(pool_reward_token_account_info)?.amount;
             Self::token_transfer(
-                farm_id_info.key,
-                token_program_info.clone(), 
-                pool_reward_token_account_info.clone(), 
-                user_reward_token_account_info.clone(), 
-                authority_info.clone(), 
-                farm_pool.nonce, 
-                _pending
+                emitter_info.key,
+                token_program_info.clone(),
+                emitter_vault_info.clone(),
+                reward_mint_info.clone(),
+                pool_reward_token_account_info.clone(),
+                emitter_authority_info.clone(),
+                emitter.nonce,
+                emission
             )?;
 
-            user_info.reward_debt += pending;
+            // token extensions like transfer fee can change the amount, so distribute what the pool received
+            let received_amount = Self::unpack_token_account(pool_reward_token_account_info)?.amount
+                .checked_sub(reward_amount)
+                .ok_or(FarmError::CalculationFailure)?;
+            farm_pool.add_emission(received_amount, lp_balance)?;
+            emitter.total_emitted = emitter.total_emitted.saturating_add(emission);
         }
 
-        Ok(())
+        // store emitter account data to network
+        // write through a temporary slice, so the emitter is still readable
+        // by other farms pulling emission in the same instruction like HarvestMany
+        emitter
+            .serialize(&mut &mut emitter_info.data.borrow_mut()[..])
+            .map_err(|e| e.into())
     }
-    /// get authority by given program address.
-    pub fn authority_id(
-        program_id: &Pubkey,
-        my_info: &Pubkey,
-        nonce: u8,
-    ) -> Result<Pubkey, FarmError> {
-        Pubkey::create_program_address(&[&my_info.to_bytes()[..32], &[nonce]], program_id)
-            .or(Err(FarmError::InvalidProgramAddress))
+    /// check if given account is PDA of given seeds, returns its bump
+    pub fn assert_seeds_account(program_id: &Pubkey, seeds: &[&[u8]], key: &Pubkey) -> Result<u8, ProgramError> {
+        let (found_key, bump) = Pubkey::find_program_address(seeds, program_id);
+        if found_key != *key {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        Ok(bump)
     }
-    pub fn is_zero_account(account_info:&AccountInfo)->bool{
-        let account_data: &[u8] = &account_info.data.borrow();
-        let len = account_data.len();
-        let mut is_zero = true;
-        for i in 0..len-1 {
-            if account_data[i] != 0 {
-                is_zero = false;
-            }
+    /// read gauge controller from gauge controller account
+    pub fn get_gauge_controller(program_id: &Pubkey, gauge_controller_info: &AccountInfo) -> Result<GaugeController, ProgramError> {
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_CONTROLLER_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, gauge_controller_info.key)?;
+        if gauge_controller_info.owner != program_id || gauge_controller_info.data_is_empty() {
+            return Err(FarmError::NotInitialized.into());
         }
-        is_zero
+        try_from_slice_unchecked::<GaugeController>(&gauge_controller_info.data.borrow()).map_err(|e| e.into())
     }
-    pub fn is_allowed(token_a_mint:&Pubkey, token_b_mint:&Pubkey)->Result<u8, ProgramError> {
-        let mut is_allowed = 0;
-        if  *token_a_mint == Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?  ||
-            *token_b_mint == Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?  ||
-            *token_a_mint == Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? || 
-            *token_b_mint == Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? ||
-            *token_a_mint == Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)? || 
-            *token_b_mint == Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?
-        {
-            is_allowed = 1;
+    /// read voter of given wallet from voter account
+    pub fn get_voter(program_id: &Pubkey, voter_info: &AccountInfo, wallet: &Pubkey) -> Result<Voter, ProgramError> {
+        let seeds = [
+            PREFIX.as_bytes(),
+            VOTER_SEED.as_bytes(),
+            wallet.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, voter_info.key)?;
+        if voter_info.owner != program_id || voter_info.data_is_empty() {
+            return Err(FarmError::InvalidOwner.into());
         }
-        Ok(is_allowed)
+        let voter = try_from_slice_unchecked::<Voter>(&voter_info.data.borrow())?;
+        if voter.wallet != *wallet {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        Ok(voter)
     }
-    pub fn is_locked_farm(token_a_mint:&Pubkey, token_b_mint:&Pubkey)->Result<bool, ProgramError> {
-        let mut result = false;
-        let sol_mint = Pubkey::from_str(SOL_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
-        let eth_mint = Pubkey::from_str(ETH_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
-        let crp_mint = Pubkey::from_str(CRP_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
-        let usdc_mint = Pubkey::from_str(USDC_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
-        let usdt_mint = Pubkey::from_str(USDT_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?;
+    /// read gauge from gauge account
+    pub fn get_gauge(program_id: &Pubkey, gauge_info: &AccountInfo) -> Result<Gauge, ProgramError> {
+        if gauge_info.owner != program_id || gauge_info.data_is_empty() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let gauge = try_from_slice_unchecked::<Gauge>(&gauge_info.data.borrow())?;
+        let seeds = [
+            PREFIX.as_bytes(),
+            GAUGE_SEED.as_bytes(),
+            gauge.farm_id.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, gauge_info.key)?;
+        Ok(gauge)
+    }
+    /// read sponsor from sponsor account of given farm
+    pub fn get_sponsor(program_id: &Pubkey, sponsor_info: &AccountInfo, farm_id: &Pubkey) -> Result<Sponsor, ProgramError> {
+        if sponsor_info.owner != program_id || sponsor_info.data_is_empty() {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let sponsor = try_from_slice_unchecked::<Sponsor>(&sponsor_info.data.borrow())?;
+        if sponsor.farm_id != *farm_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+        let seeds = [
+            PREFIX.as_bytes(),
+            SPONSOR_SEED.as_bytes(),
+            sponsor.farm_id.as_ref(),
+            sponsor.wallet.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, sponsor_info.key)?;
+        Ok(sponsor)
+    }
+    /// read lock accounts which follow if given farm boosts rewards
+    pub fn next_lock_infos<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        farm_pool: &FarmPool,
+        account_info_iter: &mut I,
+    ) -> Result<Option<(&'a AccountInfo<'b>, &'a AccountInfo<'b>)>, ProgramError> {
+        if !farm_pool.is_boosted() {
+            return Ok(None);
+        }
+        let voter_info = next_account_info(account_info_iter)?;
+        let gauge_controller_info = next_account_info(account_info_iter)?;
+        Ok(Some((voter_info, gauge_controller_info)))
+    }
+    /// refresh user's effective stake with CRP lock share and loyalty multiplier of the user
+    /// ended lock or empty voter account gives no lock share, lock accounts are ignored if the farm doesn't boost
+    pub fn update_boost(
+        program_id: &Pubkey,
+        lock_infos: Option<(&AccountInfo, &AccountInfo)>,
+        farm_pool: &mut FarmPool,
+        user_info: &mut UserInfo,
+        lp_balance: u64,
+        cur_timestamp: u64,
+    ) -> Result<(), ProgramError> {
+        let (voter_info, gauge_controller_info) = match lock_infos {
+            Some(lock_infos) if farm_pool.is_boosted() => lock_infos,
+            _ => return farm_pool.update_effective_balance(user_info, lp_balance, 0, 0, cur_timestamp),
+        };
 
-        if  (*token_a_mint == sol_mint && *token_b_mint == usdc_mint) ||
-            (*token_a_mint == usdc_mint && *token_b_mint == sol_mint) || //SOL-USDC
-            (*token_a_mint == sol_mint && *token_b_mint == usdt_mint) ||
-            (*token_a_mint == usdt_mint && *token_b_mint == sol_mint) || //SOL-USDT
-            (*token_a_mint == eth_mint && *token_b_mint == usdc_mint) ||
-            (*token_a_mint == usdc_mint && *token_b_mint == eth_mint) || //ETH-USDC
-            (*token_a_mint == eth_mint && *token_b_mint == usdt_mint) ||
-            (*token_a_mint == usdt_mint && *token_b_mint == eth_mint) || //ETH-USDT
-            (*token_a_mint == usdc_mint && *token_b_mint == crp_mint) ||
-            (*token_a_mint == crp_mint && *token_b_mint == usdc_mint) || //CRP-USDC
-            (*token_a_mint == usdt_mint && *token_b_mint == crp_mint) ||
-            (*token_a_mint == crp_mint && *token_b_mint == usdt_mint) || //CRP-USDT
-            (*token_a_mint == sol_mint && *token_b_mint == crp_mint) ||
-            (*token_a_mint == crp_mint && *token_b_mint == sol_mint) ||  //SOL-CRP
-            (*token_a_mint == eth_mint && *token_b_mint == crp_mint) ||
-            (*token_a_mint == crp_mint && *token_b_mint == eth_mint) ||  //ETH-CRP
-            (*token_a_mint == eth_mint && *token_b_mint == sol_mint) || 
-            (*token_a_mint == sol_mint && *token_b_mint == eth_mint)     //SOL-ETH
-        {
-            result = true
+        let gauge_controller = Self::get_gauge_controller(program_id, gauge_controller_info)?;
+        let locked_amount = if voter_info.data_is_empty() {
+            let seeds = [
+                PREFIX.as_bytes(),
+                VOTER_SEED.as_bytes(),
+                user_info.wallet.as_ref(),
+            ];
+            Self::assert_seeds_account(program_id, &seeds, voter_info.key)?;
+            0
+        } else {
+            let voter = Self::get_voter(program_id, voter_info, &user_info.wallet)?;
+            if cur_timestamp < voter.lock_end_timestamp {
+                voter.locked_amount
+            } else {
+                0
+            }
+        };
+
+        farm_pool.update_effective_balance(user_info, lp_balance, locked_amount, gauge_controller.total_locked, cur_timestamp)
+    }
+    /// read token account, a wallet is read as its own wSOL account for native SOL rewards
+    /// lamports above rent exemption are the amount of a wallet
+    pub fn unpack_token_account(account_info: &AccountInfo) -> Result<Account, ProgramError> {
+        if *account_info.owner == system_program::id() && account_info.data_is_empty() {
+            return Ok(Account {
+                mint: Pubkey::from_str(SOL_MINT_ADDRESS).map_err(|_| FarmError::InvalidPubkey)?,
+                owner: *account_info.key,
+                amount: account_info.lamports().saturating_sub(Rent::get()?.minimum_balance(0)),
+                state: AccountState::Initialized,
+                ..Account::default()
+            });
         }
-        Ok(result)
+        Self::unpack_spl_token_account(account_info)
+    }
+    /// read spl-token or Token-2022 account, Token-2022 accounts may have extensions after base state
+    pub fn unpack_spl_token_account(account_info: &AccountInfo) -> Result<Account, ProgramError> {
+        if *account_info.owner != Pubkey::from_str(TOKEN_2022_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Account::unpack_from_slice(&account_info.data.borrow());
+        }
+        let data = account_info.data.borrow();
+        let account = StateWithExtensions::<Token2022Account>::unpack(&data)?.base;
+        Ok(Account {
+            mint: account.mint,
+            owner: account.owner,
+            amount: account.amount,
+            delegate: account.delegate,
+            state: match account.state {
+                Token2022AccountState::Uninitialized => AccountState::Uninitialized,
+                Token2022AccountState::Initialized => AccountState::Initialized,
+                Token2022AccountState::Frozen => AccountState::Frozen,
+            },
+            is_native: account.is_native,
+            delegated_amount: account.delegated_amount,
+            close_authority: account.close_authority,
+        })
     }
+    /// read spl-token or Token-2022 mint, Token-2022 mints may have extensions after base state
+    pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+        if *mint_info.owner != Pubkey::from_str(TOKEN_2022_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Mint::unpack_from_slice(&mint_info.data.borrow());
+        }
+        let data = mint_info.data.borrow();
+        let mint = StateWithExtensions::<Token2022Mint>::unpack(&data)?.base;
+        Ok(Mint {
+            mint_authority: mint.mint_authority,
+            supply: mint.supply,
+            decimals: mint.decimals,
+            is_initialized: mint.is_initialized,
+            freeze_authority: mint.freeze_authority,
+        })
+    }
+    /// read audit log account which follows fixed accounts of a privileged instruction once audit log is created
+    pub fn next_audit_log_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        program_data: &FarmProgram,
+        account_info_iter: &mut I,
+    ) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+        if !program_data.has_audit_log() {
+            return Ok(None);
+        }
+        Ok(Some(next_account_info(account_info_iter)?))
+    }
+    /// append a privileged instruction call to audit log which is passed last
+    /// nothing is recorded until super owner creates audit log
+    pub fn write_audit_log(
+        program_id: &Pubkey,
+        audit_log_info: Option<&AccountInfo>,
+        kind: u8,
+        signer: &Pubkey,
+        old_value: &[u8],
+        new_value: &[u8],
+    ) -> ProgramResult {
+        let audit_log_info = match audit_log_info {
+            Some(audit_log_info) => audit_log_info,
+            None => return Ok(()),
+        };
 
-    /// issue a spl_token `Transfer` instruction.
-    pub fn token_transfer<'a>(
-        pool: &Pubkey,
-        token_program: AccountInfo<'a>,
-        source: AccountInfo<'a>,
-        destination: AccountInfo<'a>,
-        authority: AccountInfo<'a>,
-        nonce: u8,
-        amount: u64,
-    ) -> Result<(), ProgramError> {
-        let pool_bytes = pool.to_bytes();
-        let authority_signature_seeds = [&pool_bytes[..32], &[nonce]];
-        let signers = &[&authority_signature_seeds[..]];
-        let ix = spl_token::instruction::transfer(
-            token_program.key,
-            source.key,
-            destination.key,
-            authority.key,
-            &[],
-            amount,
-        )?;
-        invoke_signed(
-            &ix,
-            &[source, destination, authority, token_program],
-            signers,
-        )
-    } 
+        let seeds = [
+            PREFIX.as_bytes(),
+            AUDIT_LOG_SEED.as_bytes(),
+            program_id.as_ref(),
+        ];
+        Self::assert_seeds_account(program_id, &seeds, audit_log_info.key)?;
+
+        // audit log account - check if audit log account's owner is program id
+        if audit_log_info.owner != program_id {
+            return Err(FarmError::InvalidOwner.into());
+        }
+
+        let clock = Clock::get()?;
+        let entry = AuditEntry {
+            kind,
+            signer: *signer,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp as u64,
+            old_hash: hash(old_value).to_bytes(),
+            new_hash: hash(new_value).to_bytes(),
+        };
+
+        // store audit entry to network
+        AuditLog::append_to_slice(&mut audit_log_info.data.borrow_mut(), &entry)
+    }
+    /// check if given account is executable AMM program of given id
+    pub fn assert_amm_program(amm_program_info: &AccountInfo, amm_program_id: &Pubkey) -> Result<(), ProgramError> {
+        if *amm_program_info.key != *amm_program_id {
+            return Err(FarmError::WrongAmmId.into());
+        }
+        if !amm_program_info.executable {
+            return Err(FarmError::AmmProgramNotExecutable.into());
+        }
+        Ok(())
+    }
+    /// check if given program is spl-token or Token-2022 program
+    pub fn assert_token_program(key: &Pubkey)->Result<(), ProgramError>{
+        if  *key != Pubkey::from_str(TOKEN_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? &&
+            *key != Pubkey::from_str(TOKEN_2022_PROGRAM_ID).map_err(|_| FarmError::InvalidPubkey)? {
+            return Err(FarmError::InvalidProgramAddress.into());
+        }
+        Ok(())
+    }
     pub fn assert_program_account(program_id:&Pubkey, key: &Pubkey)->Result<(), ProgramError>{
         let seeds = [
             PREFIX.as_bytes(),
@@ -1465,7 +8376,7 @@ impl Processor {
 
         let (program_data_key, _bump) = Pubkey::find_program_address(&seeds, program_id);
         if program_data_key != *key {
-            return Err(FarmError::InvalidProgramAddress.into());
+            Err(FarmError::InvalidProgramAddress.into())
         }
         else {
             Ok(())
@@ -1518,7 +8429,181 @@ impl PrintProgramError for FarmError {
             FarmError::InvalidSystemProgramId => msg!("Error: Invalid System Program Id"),
             FarmError::InvalidRentSysvarId => msg!("Error: Invalid Rent Sysvar Program Id"),
             FarmError::InvalidClockSysvarId => msg!("Error: Invalid Clock Sysvar Program Id"),
+            FarmError::AccountNotExtended => msg!("Error: Account data is too small for the current layout, extend it first"),
+            FarmError::ReceiptAlreadyEnabled => msg!("Error: Receipt tokens are already enabled for this farm"),
+            FarmError::ReceiptNotEnabled => msg!("Error: This farm doesn't issue receipt tokens"),
+            FarmError::InvalidReceiptMint => msg!("Error: Invalid receipt token mint"),
+            FarmError::TooManyFarms => msg!("Error: Too many farms in one instruction"),
+            FarmError::StillUnbonding => msg!("Error: Unbonding period is not over yet"),
+            FarmError::NothingToClaim => msg!("Error: Nothing to claim"),
+            FarmError::FeeTokenNotWhitelisted => msg!("Error: This token is not whitelisted to pay farm fee"),
+            FarmError::InvalidPricePool => msg!("Error: Invalid AMM pool to price fee token"),
+            FarmError::FeeOutOfTolerance => msg!("Error: Paid fee value is out of tolerance band"),
+            FarmError::InReview => msg!("Error: This farm is waiting for review"),
+            FarmError::NotInReview => msg!("Error: This farm is not waiting for review"),
+            FarmError::ReviewWindowClosed => msg!("Error: Review window is over"),
+            FarmError::FarmRejected => msg!("Error: This farm was rejected"),
+            FarmError::FarmNotEnded => msg!("Error: This farm is not ended yet"),
+            FarmError::FarmNotEmpty => msg!("Error: This farm still has staked LP tokens"),
+            FarmError::InvalidMultisig => msg!("Error: Invalid multisig account"),
+            FarmError::NotMultisigSigner => msg!("Error: Signer is not one of multisig signers"),
+            FarmError::NotEnoughApprovals => msg!("Error: Proposal doesn't have enough approvals"),
+            FarmError::ProposalExecuted => msg!("Error: Proposal was executed already"),
+            FarmError::TimelockRequired => msg!("Error: Fee and AMM program changes have to be queued in timelock"),
+            FarmError::AlreadyQueued => msg!("Error: Another change is queued already, cancel it first"),
+            FarmError::NothingQueued => msg!("Error: No change is queued"),
+            FarmError::InvalidEta => msg!("Error: ETA is earlier than minimum delay"),
+            FarmError::TimelockNotReady => msg!("Error: Queued change can't be executed before ETA"),
+            FarmError::MigrationNotAllowed => msg!("Error: Stake can't be migrated while locked or to a farm with shorter unbonding period"),
+            FarmError::InvalidEmitter => msg!("Error: Invalid emitter account"),
+            FarmError::FarmRegistered => msg!("Error: Farm is registered to emitter, set its allocation points to zero first"),
+            FarmError::InvalidLockDuration => msg!("Error: Lock duration is zero or longer than maximum lock duration"),
+            FarmError::NotEnoughVotingPower => msg!("Error: Voting power is not enough or lock is ended"),
+            FarmError::StillLocked => msg!("Error: Lock is not ended or votes are not removed"),
+            FarmError::EpochNotEnded => msg!("Error: Gauge was checkpointed in this epoch already"),
+            FarmError::NotBoosted => msg!("Error: Farm doesn't boost rewards"),
+            FarmError::SnapshotNotFinalized => msg!("Error: Snapshot time must be before current time"),
+            FarmError::CheckpointOverwritten => msg!("Error: Checkpoint at snapshot time was overwritten"),
+            FarmError::SponsorsNotRefunded => msg!("Error: Sponsors have to be refunded before closing farm"),
+            FarmError::AlreadyRefunded => msg!("Error: Sponsor was refunded already"),
+            FarmError::VaultAmountMismatch => msg!("Error: New vault received different amount"),
+            FarmError::InvalidMultiplier => msg!("Error: Loyalty multiplier must be at least 1"),
+            FarmError::ZeroFeeDenominator => msg!("Error: Fee denominator must not be zero"),
+            FarmError::HarvestFeeTooHigh => msg!("Error: Harvest fee is more than max harvest fee"),
+            FarmError::FeeOwnerNotSet => msg!("Error: Fee owner must be set"),
+            FarmError::AmmProgramNotExecutable => msg!("Error: AMM program is not executable"),
+            FarmError::InvalidReferencePrice => msg!("Error: Reference price of fee token is not set or deviation is too high"),
+            FarmError::PriceOutOfReference => msg!("Error: AMM pool price deviates from reference price of fee token"),
             
         }
     }
 } 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_amm_program_accepts_executable_program() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, true, 0);
+        assert_eq!(Processor::assert_amm_program(&info, &key), Ok(()));
+    }
+
+    #[test]
+    fn assert_amm_program_rejects_non_executable_account() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(
+            Processor::assert_amm_program(&info, &key),
+            Err(FarmError::AmmProgramNotExecutable.into())
+        );
+    }
+
+    #[test]
+    fn assert_amm_program_rejects_other_program() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, true, 0);
+        assert_eq!(
+            Processor::assert_amm_program(&info, &Pubkey::new_unique()),
+            Err(FarmError::WrongAmmId.into())
+        );
+    }
+
+    #[test]
+    fn queue_program_data_rejects_non_executable_amm_program() {
+        let program_id = Pubkey::new_unique();
+        let (program_data_key, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], &program_id);
+        let (timelock_key, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), TIMELOCK_SEED.as_bytes(), program_id.as_ref()], &program_id);
+        let super_owner_key = Pubkey::new_unique();
+        let amm_program_key = Pubkey::new_unique();
+        let clock_key = Pubkey::from_str(CLOCK_SYSVAR_ID).unwrap();
+        let rent_key = Pubkey::from_str(RENT_SYSVAR_ID).unwrap();
+        let system_key = Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap();
+
+        let mut program_data = vec![0; get_packed_len::<FarmProgram>()];
+        FarmProgram {
+            super_owner: super_owner_key,
+            ..FarmProgram::default()
+        }.pack_into_slice(&mut program_data).unwrap();
+
+        let mut lamports = [0; 7];
+        let mut data = [program_data, vec![], vec![], vec![], vec![], vec![], vec![]];
+        let [program_data_lamports, timelock_lamports, super_owner_lamports, clock_lamports, rent_lamports, system_lamports, amm_lamports] = &mut lamports;
+        let [program_data_data, timelock_data, super_owner_data, clock_data, rent_data, system_data, amm_data] = &mut data;
+        let accounts = [
+            AccountInfo::new(&program_data_key, false, true, program_data_lamports, program_data_data, &program_id, false, 0),
+            AccountInfo::new(&timelock_key, false, true, timelock_lamports, timelock_data, &program_id, false, 0),
+            AccountInfo::new(&super_owner_key, true, true, super_owner_lamports, super_owner_data, &system_key, false, 0),
+            AccountInfo::new(&clock_key, false, false, clock_lamports, clock_data, &system_key, false, 0),
+            AccountInfo::new(&rent_key, false, false, rent_lamports, rent_data, &system_key, false, 0),
+            AccountInfo::new(&system_key, false, false, system_lamports, system_data, &system_key, true, 0),
+            AccountInfo::new(&amm_program_key, false, false, amm_lamports, amm_data, &system_key, false, 0),
+        ];
+        let change = ProgramDataChange {
+            amm_program_id: amm_program_key,
+            harvest_fee_denominator: BPS_DENOMINATOR,
+            ..ProgramDataChange::default()
+        };
+        assert_eq!(
+            Processor::process_queue_program_data(&program_id, &accounts, change),
+            Err(FarmError::AmmProgramNotExecutable.into())
+        );
+    }
+
+    fn receipt_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64, state: AccountState) -> Vec<u8> {
+        let mut data = vec![0; Account::LEN];
+        Account::pack(
+            Account {
+                mint: *mint,
+                owner: *owner,
+                amount,
+                state,
+                ..Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        data
+    }
+
+    #[test]
+    fn frozen_receipt_cannot_be_transferred_outside_farm() {
+        let token_program = spl_token::id();
+        let mint_key = Pubkey::new_unique();
+        let wallet_key = Pubkey::new_unique();
+        let source_key = Pubkey::new_unique();
+        let destination_key = Pubkey::new_unique();
+
+        let mut source_lamports = 0;
+        let mut source_data = receipt_account_data(&mint_key, &wallet_key, 100, AccountState::Frozen);
+        let mut destination_lamports = 0;
+        let mut destination_data = receipt_account_data(&mint_key, &Pubkey::new_unique(), 0, AccountState::Initialized);
+        let mut wallet_lamports = 0;
+        let mut wallet_data = vec![];
+        let system_program = system_program::id();
+        let source = AccountInfo::new(&source_key, false, true, &mut source_lamports, &mut source_data, &token_program, false, 0);
+        let destination = AccountInfo::new(&destination_key, false, true, &mut destination_lamports, &mut destination_data, &token_program, false, 0);
+        let wallet = AccountInfo::new(&wallet_key, true, false, &mut wallet_lamports, &mut wallet_data, &system_program, false, 0);
+
+        // a plain token transfer would move receipts away from the deposit balance and strand the position
+        let ix = spl_token::instruction::transfer(&token_program, &source_key, &destination_key, &wallet_key, &[], 100).unwrap();
+        let result = spl_token::processor::Processor::process(
+            &token_program,
+            &[source.clone(), destination.clone(), wallet],
+            &ix.data,
+        );
+        assert_eq!(result, Err(spl_token::error::TokenError::AccountFrozen.into()));
+        assert_eq!(Processor::unpack_spl_token_account(&source).unwrap().amount, 100);
+        assert_eq!(Processor::unpack_spl_token_account(&destination).unwrap().amount, 0);
+    }
+}
@@ -6,10 +6,12 @@ use {
     crate::{
         error::FarmError,
         constant::*,
+        utils::{get_packed_len, pack_extended, unpack_extended},
     },
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     solana_program::{
         pubkey::{Pubkey},
+        system_program,
         program_error::ProgramError,
         msg
     },
@@ -49,9 +51,170 @@ pub struct FarmProgram {
 
     /// reward multipler
     pub reward_multipler: u64,
+
+    /// review window in seconds for farms created without super owner
+    pub review_period: u64,
+
+    /// minimum reward amount deposited when farm is created without super owner
+    pub min_reward_deposit: u64,
+
+    /// minimum delay in seconds of queued program data changes
+    pub min_delay: u64,
+
+    /// 1 if privileged instructions are recorded in audit log
+    pub audit_log: u8,
     
 }
+impl FarmProgram {
+    /// read program data from account data, accounts created before a layout extension are zero padded
+    pub fn unpack_from_slice(data: &[u8]) -> Result<FarmProgram, ProgramError> {
+        unpack_extended::<FarmProgram>(data, get_packed_len::<FarmProgram>())
+    }
+
+    /// write program data to account data
+    pub fn pack_into_slice(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        pack_extended(self, data)
+    }
+
+    /// get minimum delay of queued changes, never shorter than MIN_TIMELOCK_DELAY
+    pub fn get_min_delay(&self) -> u64 {
+        self.min_delay.max(MIN_TIMELOCK_DELAY)
+    }
+
+    /// check if privileged instructions are recorded in audit log
+    pub fn has_audit_log(&self) -> bool {
+        self.audit_log > 0
+    }
+
+    /// check program parameters before they are stored
+    pub fn validate_params(fee_owner: &Pubkey, harvest_fee_numerator: u64, harvest_fee_denominator: u64) -> Result<(), ProgramError> {
+        if *fee_owner == Pubkey::default() {
+            return Err(FarmError::FeeOwnerNotSet.into());
+        }
+        Self::validate_harvest_fee(harvest_fee_numerator, harvest_fee_denominator)
+    }
+
+    /// check harvest fee is at most MAX_HARVEST_FEE_BPS of harvested amount
+    pub fn validate_harvest_fee(harvest_fee_numerator: u64, harvest_fee_denominator: u64) -> Result<(), ProgramError> {
+        if harvest_fee_denominator == 0 {
+            return Err(FarmError::ZeroFeeDenominator.into());
+        }
+        if  (harvest_fee_numerator as u128) * (BPS_DENOMINATOR as u128) >
+            (MAX_HARVEST_FEE_BPS as u128) * (harvest_fee_denominator as u128) {
+            return Err(FarmError::HarvestFeeTooHigh.into());
+        }
+        Ok(())
+    }
+}
+
+/// Program data change queued in timelock
+/// Stakers can see queued fees and amm program id before they take effect
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct ProgramDataChange {
+    /// queued flag
+    pub is_queued: u8,
 
+    /// queued change can be executed from this time
+    pub eta: u64,
+
+    /// new AMM program id
+    pub amm_program_id: Pubkey,
+
+    /// new farm fee
+    pub farm_fee: u64,
+
+    /// new harvest fee numerator
+    pub harvest_fee_numerator: u64,
+
+    /// new harvest fee denominator
+    pub harvest_fee_denominator: u64,
+
+    /// new minimum delay of queued changes
+    pub min_delay: u64,
+}
+
+
+/// Audit log of privileged instructions
+/// Ring buffer keeps latest AUDIT_LOG_ENTRIES entries
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct AuditLog {
+    /// number of entries written so far, next one is stored at count % AUDIT_LOG_ENTRIES
+    pub count: u64,
+
+    /// ring buffer of entries
+    pub entries: [AuditEntry; AUDIT_LOG_ENTRIES],
+}
+impl AuditLog {
+    /// append an entry to audit log account data, overwriting the oldest one when full
+    /// entry is written in place, whole log doesn't fit in program stack
+    pub fn append_to_slice(data: &mut [u8], entry: &AuditEntry) -> Result<(), ProgramError> {
+        if data.len() < get_packed_len::<AuditLog>() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let count = u64::try_from_slice(&data[..8])?;
+        let entry_len = get_packed_len::<AuditEntry>();
+        let offset = 8 + ((count as usize) % AUDIT_LOG_ENTRIES) * entry_len;
+        entry.serialize(&mut &mut data[offset..offset + entry_len])?;
+        (count + 1).serialize(&mut &mut data[..8])?;
+        Ok(())
+    }
+
+    /// get entries kept in the ring buffer, oldest first
+    pub fn get_entries(&self) -> Vec<AuditEntry> {
+        let count = (self.count as usize).min(AUDIT_LOG_ENTRIES);
+        let first = (self.count as usize) - count;
+        (first..self.count as usize)
+            .map(|i| self.entries[i % AUDIT_LOG_ENTRIES])
+            .collect()
+    }
+}
+
+/// Privileged instruction recorded in audit log
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct AuditEntry {
+    /// instruction kind, one of `AUDIT_*`
+    pub kind: u8,
+
+    /// signer who called the instruction, super owner who queued the change for ExecuteProgramData
+    pub signer: Pubkey,
+
+    /// slot when the instruction was called
+    pub slot: u64,
+
+    /// time when the instruction was called
+    pub timestamp: u64,
+
+    /// hash of changed account data before the instruction
+    pub old_hash: [u8; 32],
+
+    /// hash of changed account data after the instruction
+    pub new_hash: [u8; 32],
+}
+impl AuditEntry {
+    /// get instruction name of this entry
+    pub fn get_kind_name(&self) -> &'static str {
+        match self.kind {
+            AUDIT_SET_PROGRAM_DATA => "SetProgramData",
+            AUDIT_REMOVE_REWARDS => "RemoveRewards",
+            AUDIT_PAY_FARM_FEE => "PayFarmFee",
+            AUDIT_SET_LISTING_OPTIONS => "SetListingOptions",
+            AUDIT_REJECT_FARM => "RejectFarm",
+            AUDIT_SET_FEE_TOKEN => "SetFeeToken",
+            AUDIT_QUEUE_PROGRAM_DATA => "QueueProgramData",
+            AUDIT_CANCEL_PROGRAM_DATA => "CancelProgramData",
+            AUDIT_INITIALIZE_EMITTER => "InitializeEmitter",
+            AUDIT_SET_EMITTER_RATE => "SetEmitterRate",
+            AUDIT_SET_ALLOC_POINT => "SetAllocPoint",
+            AUDIT_INITIALIZE_GAUGE_CONTROLLER => "InitializeGaugeController",
+            AUDIT_CREATE_GAUGE => "CreateGauge",
+            AUDIT_EXECUTE_PROGRAM_DATA => "ExecuteProgramData",
+            _ => "Unknown",
+        }
+    }
+}
 
 /// Farm Pool struct
 #[repr(C)]
@@ -96,14 +259,313 @@ pub struct FarmPool {
     /// end time of this farm
     pub end_timestamp: u64,
 
+    /// receipt token's mint address, default pubkey if this farm doesn't issue receipts
+    /// receipt token accounts are frozen, receipts are non-transferable outside this program
+    pub receipt_mint: Pubkey,
+
+    /// total lp amount deposited so far
+    pub total_lp_deposited: u64,
+
+    /// total reward amount added so far
+    pub total_rewards_added: u64,
+
+    /// total reward amount harvested by farmers so far, except harvest fee
+    pub total_rewards_harvested: u64,
+
+    /// total harvest fee collected so far
+    pub total_harvest_fees: u64,
+
+    /// number of users who have deposited balance now
+    pub staker_count: u64,
+
+    /// latest deposit or withdraw time
+    pub last_activity_timestamp: u64,
+
+    /// minimum stake duration in seconds, withdrawing earlier pays early withdraw fee
+    pub min_stake_duration: u64,
+
+    /// early withdraw fee numerator, fee is paid in lp token to fee owner
+    pub early_withdraw_fee_numerator: u64,
+
+    /// early withdraw fee denominator
+    pub early_withdraw_fee_denominator: u64,
+
+    /// unbonding period in seconds, withdrawn lp can be claimed after this period
+    pub unbonding_period: u64,
+
+    /// total lp amount waiting to be claimed, this doesn't earn rewards
+    pub total_unbonding_amount: u64,
+
+    /// token program id of reward token, default pubkey if same with lp token's program
+    pub reward_token_program_id: Pubkey,
+
+    /// listing state of farms created without super owner, one of `LISTING_*`
+    pub listing_state: u8,
+
+    /// token account owned by farm authority escrowing farm fee during review
+    pub escrow_fee_account: Pubkey,
+
+    /// farm fee amount escrowed during review
+    pub escrow_fee_amount: u64,
+
+    /// super owner can reject this farm until this time
+    pub review_end_timestamp: u64,
+
+    /// allocation points of this farm in the emitter, zero if not registered
+    pub alloc_point: u64,
+
+    /// emitter's reward per point at the latest pull of this farm
+    pub emitter_reward_debt: u128,
+
+    /// base share numerator of boosted effective stake, the rest is given by CRP lock share
+    pub boost_base_numerator: u64,
+
+    /// base share denominator of boosted effective stake, zero if this farm doesn't boost rewards
+    pub boost_base_denominator: u64,
+
+    /// sum of effective stake of users, rewards of boosted farm are distributed by this
+    pub total_effective_balance: u64,
+
+    /// 1 if anyone can add rewards to this farm, contributors are recorded in sponsor accounts
+    pub open_funding: u8,
+
+    /// reward amount funded to this farm since open funding, basis of pro rata sponsor refunds
+    pub total_funded: u64,
+
+    /// reward amount added by sponsors which is not refunded yet
+    pub total_sponsored: u64,
+
+    /// 1 after unspent rewards are fixed for sponsor refunds
+    pub is_refunding: u8,
+
+    /// unspent reward amount when sponsor refunds started
+    pub refund_unspent: u64,
+
+    /// wallet proposed as new owner, default pubkey if no transfer is pending
+    pub pending_owner: Pubkey,
+
+    /// seconds of uninterrupted staking to reach max loyalty multiplier, zero if loyalty is disabled
+    pub loyalty_period: u64,
+
+    /// max loyalty multiplier numerator
+    pub loyalty_max_numerator: u64,
+
+    /// max loyalty multiplier denominator
+    pub loyalty_max_denominator: u64,
+
+    /// 1 if rewards are native SOL held as lamports by the farm authority
+    pub native_reward: u8,
+
+    /// reward amount paid to a keeper who cranks this farm, zero if cranking isn't rewarded
+    pub crank_bounty: u64,
+
+    /// minimum seconds between two paid cranks
+    pub crank_interval: u64,
+
+    /// latest time when crank bounty was paid
+    pub last_crank_timestamp: u64,
+
+    /// total crank bounty paid so far
+    pub total_crank_bounties: u64,
+
+    /// emission owed by the emitter which wasn't delivered for lack of emitter vault funds, pulled again with the next emission
+    pub pending_emission: u64,
+
 }
 impl FarmPool {
+    /// read farm pool from account data, accounts created before a layout extension are zero padded
+    pub fn unpack_from_slice(data: &[u8]) -> Result<FarmPool, ProgramError> {
+        unpack_extended::<FarmPool>(data, get_packed_len::<FarmPool>())
+    }
+
+    /// write farm pool to account data
+    pub fn pack_into_slice(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        pack_extended(self, data)
+    }
+
+    /// update staker count when a user's deposit balance changes
+    pub fn update_staker_count(&mut self, old_balance:u64, new_balance:u64) {
+        if old_balance == 0 && new_balance > 0 {
+            self.staker_count += 1;
+        } else if old_balance > 0 && new_balance == 0 {
+            // farms extended after users staked don't count those stakers
+            self.staker_count = self.staker_count.saturating_sub(1);
+        }
+    }
+
+    /// get token program id of reward token
+    /// `token_program_id` is for lp token, farms created before Token-2022 support use it for both
+    pub fn get_reward_token_program_id(&self) -> Pubkey {
+        // native SOL rewards are moved by system program
+        if self.is_native_reward() {
+            return system_program::id();
+        }
+        if self.reward_token_program_id == Pubkey::default() {
+            self.token_program_id
+        } else {
+            self.reward_token_program_id
+        }
+    }
+
+    /// check if this farm is waiting for review
+    pub fn is_in_review(&self) -> bool {
+        self.listing_state == LISTING_PENDING
+    }
+
+    /// check if this farm mints receipt tokens for deposits
+    pub fn has_receipt(&self) -> bool {
+        self.receipt_mint != Pubkey::default()
+    }
+
+    /// check if this farm pulls emission from the emitter
+    pub fn is_emitter_farm(&self) -> bool {
+        self.alloc_point > 0
+    }
+
+    /// check if this farm boosts rewards of CRP lockers
+    pub fn is_boosted(&self) -> bool {
+        self.boost_base_denominator > 0
+    }
+
+    /// check if rewards are native SOL
+    pub fn is_native_reward(&self) -> bool {
+        self.native_reward > 0
+    }
+
+    /// check if anyone can add rewards to this farm
+    pub fn is_open_funding(&self) -> bool {
+        self.open_funding > 0
+    }
+
+    /// get pro rata share of unspent rewards for a sponsor
+    pub fn get_sponsor_refund(&self, sponsor:&Sponsor) -> Result<u64, ProgramError> {
+        if self.total_funded == 0 {
+            return Ok(0);
+        }
+        let refund = (self.refund_unspent as u128)
+            .checked_mul(sponsor.amount as u128).ok_or(FarmError::CalculationFailure)?
+            .checked_div(self.total_funded as u128).ok_or(FarmError::CalculationFailure)?;
+        Ok(u64::try_from(refund).unwrap_or(0).min(self.remained_reward_amount))
+    }
+
+    /// check if rewards grow with continuous staking duration
+    pub fn has_loyalty(&self) -> bool {
+        self.loyalty_period > 0
+    }
+
+    /// check if rewards are distributed by effective stake instead of deposit
+    pub fn has_effective_stake(&self) -> bool {
+        self.is_boosted() || self.has_loyalty()
+    }
+
+    /// restart user's loyalty clock
+    pub fn restart_loyalty(&self, user_info:&mut UserInfo, cur_timestamp:u64) {
+        if self.has_loyalty() {
+            user_info.stake_start_timestamp = cur_timestamp;
+        }
+    }
+
+    /// get user's stake which earns rewards
+    pub fn get_user_stake(&self, user_info:&UserInfo) -> u64 {
+        if self.has_effective_stake() {
+            user_info.effective_balance
+        } else {
+            user_info.deposit_balance
+        }
+    }
+
+    /// get total stake which earns rewards from lp balance of the pool
+    /// unbonding lp stays in the pool but doesn't earn rewards
+    pub fn get_total_stake(&self, lp_balance:u64) -> u64 {
+        if self.has_effective_stake() {
+            self.total_effective_balance
+        } else {
+            lp_balance.saturating_sub(self.total_unbonding_amount)
+        }
+    }
+
+    /// update user's effective stake with CRP lock share and loyalty multiplier
+    /// boosted stake = min(deposit, base * deposit + (1 - base) * total deposit * locked / total locked)
+    /// loyalty multiplier grows linearly from 1 to max over loyalty period of uninterrupted staking
+    pub fn update_effective_balance(&mut self, user_info:&mut UserInfo, lp_balance:u64, locked_amount:u64, total_locked:u64, cur_timestamp:u64) -> Result<(), ProgramError> {
+        if !self.has_effective_stake() {
+            return Ok(());
+        }
+        let deposit = user_info.deposit_balance as u128;
+        // voting power boosts only the default position, splitting a stake must not multiply the boost
+        let locked_amount = if user_info.position_index > 0 { 0 } else { locked_amount };
+        let mut effective = if self.is_boosted() {
+            self.get_boosted_stake(deposit, lp_balance, locked_amount, total_locked)?
+        } else {
+            deposit
+        };
+
+        if self.has_loyalty() && self.loyalty_max_numerator > self.loyalty_max_denominator {
+            let duration = cur_timestamp.saturating_sub(user_info.stake_start_timestamp).min(self.loyalty_period);
+            let bonus = effective
+                .checked_mul((self.loyalty_max_numerator - self.loyalty_max_denominator) as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_mul(duration as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_div(self.loyalty_max_denominator as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_div(self.loyalty_period as u128).ok_or(FarmError::CalculationFailure)?;
+            effective = effective.checked_add(bonus).ok_or(FarmError::CalculationFailure)?;
+        }
+        let effective = u64::try_from(effective).map_err(|_| FarmError::CalculationFailure)?;
+
+        self.total_effective_balance = self.total_effective_balance
+            .checked_sub(user_info.effective_balance).ok_or(FarmError::CalculationFailure)?
+            .checked_add(effective).ok_or(FarmError::CalculationFailure)?;
+        user_info.effective_balance = effective;
+        Ok(())
+    }
+
+    /// get boosted stake of a deposit with CRP lock share
+    fn get_boosted_stake(&self, deposit:u128, lp_balance:u64, locked_amount:u64, total_locked:u64) -> Result<u128, ProgramError> {
+        let numerator = self.boost_base_numerator as u128;
+        let denominator = self.boost_base_denominator as u128;
+
+        let mut effective = deposit
+            .checked_mul(numerator).ok_or(FarmError::CalculationFailure)?
+            .checked_div(denominator).ok_or(FarmError::CalculationFailure)?;
+        if total_locked > 0 {
+            let total_deposit = lp_balance.saturating_sub(self.total_unbonding_amount) as u128;
+            let boost = total_deposit
+                .checked_mul(locked_amount as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_div(total_locked as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_mul(denominator - numerator).ok_or(FarmError::CalculationFailure)?
+                .checked_div(denominator).ok_or(FarmError::CalculationFailure)?;
+            effective = effective.checked_add(boost).ok_or(FarmError::CalculationFailure)?;
+        }
+        Ok(effective.min(deposit))
+    }
+
+    /// get emission delivered now out of farm's emission including pending one
+    /// emission earned while nobody stakes is dropped, shortfall of emitter vault stays pending
+    pub fn take_emission(&mut self, emission:u64, total_stake:u64, vault_amount:u64) -> u64 {
+        if total_stake == 0 {
+            return 0;
+        }
+        let delivered = emission.min(vault_amount);
+        self.pending_emission = emission - delivered;
+        delivered
+    }
+
+    /// distribute pulled emission to current stakers
+    pub fn add_emission(&mut self, amount:u64, lp_balance:u64) -> Result<(), ProgramError> {
+        let share = (amount as u128)
+            .checked_mul(REWARD_MULTIPLER as u128).ok_or(FarmError::CalculationFailure)?
+            .checked_div(lp_balance as u128).ok_or(FarmError::CalculationFailure)?;
+        self.reward_per_share_net = self.reward_per_share_net
+            .checked_add(share).ok_or(FarmError::CalculationFailure)?;
+        self.total_rewards_added = self.total_rewards_added.saturating_add(amount);
+        Ok(())
+    }
+
     /// get current pending reward amount for a user
     pub fn pending_rewards(&self, user_info:&mut UserInfo) -> Result<u64, ProgramError> {
         
         msg!("pending_rewards() ...");
-        let deposit_balance = PreciseNumber::new(user_info.deposit_balance as u128).ok_or(FarmError::PreciseError)?;
-        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net as u128).ok_or(FarmError::PreciseError)?;
+        let deposit_balance = PreciseNumber::new(self.get_user_stake(user_info) as u128).ok_or(FarmError::PreciseError)?;
+        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net).ok_or(FarmError::PreciseError)?;
         let reward_multipler = PreciseNumber::new(REWARD_MULTIPLER as u128).ok_or(FarmError::PreciseError)?;
         if user_info.reward_debt < JUMP_DEBT {
             msg!("put JUMP_DEBT");
@@ -126,14 +588,14 @@ impl FarmPool {
         msg!("pending_rewards():reward_multipler = {}",reward_multipler.to_imprecise().ok_or(FarmError::PreciseError)?);
         msg!("pending_rewards():reward_debt = {}",reward_debt.to_imprecise().ok_or(FarmError::PreciseError)?);
 
-        Ok(u64::try_from(result.to_imprecise().ok_or(FarmError::PreciseError)?).unwrap_or(0))
+        Ok(u64::try_from(result.to_imprecise().ok_or(FarmError::PreciseError)?).unwrap_or(0).saturating_add(user_info.reward_credit))
     }
 
     /// get total reward amount for a user so far
     pub fn get_new_reward_debt(&self, user_info:&UserInfo) -> Result<u64, ProgramError>{
         msg!("get_new_reward_debt() ...");
-        let deposit_balance = PreciseNumber::new(user_info.deposit_balance as u128).ok_or(FarmError::PreciseError)?;
-        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net as u128).ok_or(FarmError::PreciseError)?;
+        let deposit_balance = PreciseNumber::new(self.get_user_stake(user_info) as u128).ok_or(FarmError::PreciseError)?;
+        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net).ok_or(FarmError::PreciseError)?;
         let reward_multipler = PreciseNumber::new(REWARD_MULTIPLER as u128).ok_or(FarmError::PreciseError)?;
 
         let result = deposit_balance.checked_mul(&reward_per_share_net).ok_or(FarmError::PreciseError)?
@@ -141,6 +603,14 @@ impl FarmPool {
                     
         Ok(JUMP_DEBT + u64::try_from(result.to_imprecise().ok_or(FarmError::PreciseError)?).unwrap_or(0))
     }
+    /// keep given pending amount for a user as reward credit and restart reward debt
+    /// this is used when deposit balance changes without harvest,
+    /// reward debt can't go below `JUMP_DEBT` when pending is more than the new balance has earned
+    pub fn keep_pending_rewards(&self, user_info:&mut UserInfo, pending:u64) -> Result<(), ProgramError>{
+        user_info.reward_debt = self.get_new_reward_debt(user_info)?;
+        user_info.reward_credit = pending;
+        Ok(())
+    }
     /// get harvest fee
     pub fn get_harvest_fee(&self, pending:u64, program_data:&FarmProgram) -> Result<u64, ProgramError>{
         msg!("get_harvest_fee() ...");
@@ -153,6 +623,37 @@ impl FarmPool {
                     
         Ok(u64::try_from(result.to_imprecise().ok_or(FarmError::PreciseError)?).unwrap_or(0))
     }
+    /// get early withdraw fee in lp token for given withdraw amount
+    pub fn get_early_withdraw_fee(&self, amount:u64, user_info:&UserInfo, cur_timestamp:u64) -> Result<u64, ProgramError>{
+        if  self.early_withdraw_fee_numerator == 0 ||
+            cur_timestamp >= user_info.deposit_timestamp.saturating_add(self.min_stake_duration) {
+            return Ok(0);
+        }
+        let numerator = PreciseNumber::new(self.early_withdraw_fee_numerator as u128).ok_or(FarmError::PreciseError)?;
+        let denominator = PreciseNumber::new(self.early_withdraw_fee_denominator as u128).ok_or(FarmError::PreciseError)?;
+        let amount = PreciseNumber::new(amount as u128).ok_or(FarmError::PreciseError)?;
+
+        let result = amount.checked_mul(&numerator).ok_or(FarmError::PreciseError)?
+                    .checked_div(&denominator).ok_or(FarmError::PreciseError)?;
+
+        Ok(u64::try_from(result.to_imprecise().ok_or(FarmError::PreciseError)?).unwrap_or(0))
+    }
+    /// get crank bounty payable at given time, it is taken from rewards not distributed yet
+    pub fn get_crank_bounty(&self, cur_timestamp: u64) -> u64 {
+        if  self.crank_bounty == 0 ||
+            cur_timestamp > self.end_timestamp ||
+            cur_timestamp < self.last_crank_timestamp.saturating_add(self.crank_interval) {
+            return 0;
+        }
+        self.crank_bounty.min(self.remained_reward_amount)
+    }
+    /// get current reward amount distributed per second
+    pub fn get_reward_per_second(&self) -> u64 {
+        if self.last_timestamp >= self.end_timestamp {
+            return 0;
+        }
+        self.remained_reward_amount / (self.end_timestamp - self.last_timestamp)
+    }
     pub fn get_pool_version(&self)->u8 {
         self.is_allowed / 10     
     }
@@ -160,7 +661,7 @@ impl FarmPool {
         self.is_allowed = self.is_allowed % 10 + ver * 10;
     }
     pub fn is_allowed(&self)->bool{
-        self.is_allowed % 10 > 0
+        matches!(self.is_allowed % 10, 1..=9)
     }
     pub fn set_allowed(&mut self, is_allowed: u8){
         self.is_allowed = (self.is_allowed / 10) * 10 + is_allowed;
@@ -195,7 +696,7 @@ impl FarmPool {
         msg!("duration {}", duration.to_imprecise().ok_or(FarmError::PreciseError)?);
         let reward_multipler = PreciseNumber::new(REWARD_MULTIPLER as u128).ok_or(FarmError::PreciseError)?;
         msg!("reward_multipler {}", reward_multipler.to_imprecise().ok_or(FarmError::PreciseError)?);
-        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net as u128).ok_or(FarmError::PreciseError)?;
+        let reward_per_share_net = PreciseNumber::new(self.reward_per_share_net).ok_or(FarmError::PreciseError)?;
         msg!("reward_per_share_net {}", reward_per_share_net.to_imprecise().ok_or(FarmError::PreciseError)?);
         let lp_balance = PreciseNumber::new(_lp_balance as u128).ok_or(FarmError::PreciseError)?;
         msg!("lp_balance {}", lp_balance.to_imprecise().ok_or(FarmError::PreciseError)?);
@@ -219,6 +720,427 @@ impl FarmPool {
     }
 }
 
+/// Global emitter
+/// CRP in the emitter vault is emitted per second and split across registered farms by allocation points
+/// Each farm pulls its share into its reward vault when it is updated
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Emitter {
+    /// nonce is used to authorize emitter vault
+    pub nonce: u8,
+
+    /// reward token's mint address, CRP
+    pub reward_mint: Pubkey,
+
+    /// This account stores reward token to emit
+    pub reward_vault: Pubkey,
+
+    /// spl-token program id of reward token
+    pub token_program_id: Pubkey,
+
+    /// reward amount emitted per second
+    pub reward_per_second: u64,
+
+    /// sum of allocation points of registered farms
+    pub total_alloc_point: u64,
+
+    /// This represents the total reward amount emitted for unit allocation point
+    pub reward_per_point_net: u128,
+
+    /// latest emission time
+    pub last_timestamp: u64,
+
+    /// total reward amount pulled by farms so far
+    pub total_emitted: u64,
+}
+impl Emitter {
+    /// accumulate emission per allocation point until given time
+    pub fn update(&mut self, cur_timestamp:u64) -> Result<(), ProgramError> {
+        if cur_timestamp <= self.last_timestamp {
+            return Ok(());
+        }
+        if self.total_alloc_point > 0 {
+            let reward = ((cur_timestamp - self.last_timestamp) as u128)
+                .checked_mul(self.reward_per_second as u128).ok_or(FarmError::CalculationFailure)?;
+            let share = reward
+                .checked_mul(REWARD_MULTIPLER as u128).ok_or(FarmError::CalculationFailure)?
+                .checked_div(self.total_alloc_point as u128).ok_or(FarmError::CalculationFailure)?;
+            self.reward_per_point_net = self.reward_per_point_net
+                .checked_add(share).ok_or(FarmError::CalculationFailure)?;
+        }
+        self.last_timestamp = cur_timestamp;
+        Ok(())
+    }
+
+    /// get emission amount of a farm since its latest pull, undelivered emission of former pulls included
+    pub fn get_farm_emission(&self, farm_pool:&FarmPool) -> Result<u64, ProgramError> {
+        let emission = self.reward_per_point_net
+            .checked_sub(farm_pool.emitter_reward_debt).ok_or(FarmError::CalculationFailure)?
+            .checked_mul(farm_pool.alloc_point as u128).ok_or(FarmError::CalculationFailure)?
+            .checked_div(REWARD_MULTIPLER as u128).ok_or(FarmError::CalculationFailure)?;
+        Ok(u64::try_from(emission).unwrap_or(u64::MAX).saturating_add(farm_pool.pending_emission))
+    }
+}
+
+/// Gauge controller
+/// CRP stakers lock CRP to get voting power and vote for gauges of farms
+/// Votes are turned into allocation points of the emitter once per epoch
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct GaugeController {
+    /// nonce is used to authorize lock vault
+    pub nonce: u8,
+
+    /// This account stores locked CRP
+    pub lock_vault: Pubkey,
+
+    /// spl-token program id of CRP
+    pub token_program_id: Pubkey,
+
+    /// epoch length in seconds
+    pub epoch_duration: u64,
+
+    /// maximum lock duration in seconds, locking for this duration gives voting power same with locked amount
+    pub max_lock_duration: u64,
+
+    /// start time of the first epoch
+    pub start_timestamp: u64,
+
+    /// total locked CRP amount
+    pub total_locked: u64,
+
+    /// total voted power of all gauges
+    pub total_weight: u64,
+}
+impl GaugeController {
+    /// get epoch number of given time
+    pub fn get_epoch(&self, cur_timestamp:u64) -> u64 {
+        cur_timestamp.saturating_sub(self.start_timestamp) / self.epoch_duration
+    }
+
+    /// get voting power of locked amount until given time
+    pub fn get_voting_power(&self, amount:u64, lock_end_timestamp:u64, cur_timestamp:u64) -> Result<u64, ProgramError> {
+        let duration = lock_end_timestamp.saturating_sub(cur_timestamp).min(self.max_lock_duration);
+        let power = (amount as u128)
+            .checked_mul(duration as u128).ok_or(FarmError::CalculationFailure)?
+            .checked_div(self.max_lock_duration as u128).ok_or(FarmError::CalculationFailure)?;
+        Ok(u64::try_from(power).unwrap_or(0))
+    }
+}
+
+/// Voter who locked CRP
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Voter {
+    /// voter's wallet address
+    pub wallet: Pubkey,
+
+    /// locked CRP amount
+    pub locked_amount: u64,
+
+    /// locked CRP can be withdrawn after this time
+    pub lock_end_timestamp: u64,
+
+    /// voting power given by lock
+    pub voting_power: u64,
+
+    /// voting power used for gauges
+    pub used_power: u64,
+}
+
+/// Gauge of a farm which collects votes
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Gauge {
+    /// farm account address of this gauge
+    pub farm_id: Pubkey,
+
+    /// voted power, this becomes allocation points of the farm from the next epoch
+    pub weight: u64,
+
+    /// gauge can be checkpointed from this epoch
+    pub next_epoch: u64,
+
+    /// snapshot of weight at the start of `weight_epoch`, checkpoints of that epoch use this
+    pub epoch_weight: u64,
+
+    /// epoch of `epoch_weight`
+    pub weight_epoch: u64,
+}
+impl Gauge {
+    /// take snapshot of weight once the epoch changes
+    /// votes of an epoch are counted from the next epoch, so moved votes are never counted twice in one epoch
+    pub fn roll_epoch(&mut self, epoch:u64) {
+        if epoch > self.weight_epoch {
+            self.epoch_weight = self.weight;
+            self.weight_epoch = epoch;
+        }
+    }
+}
+
+/// Voting power given by a voter to a gauge
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct GaugeVote {
+    /// voter's wallet address
+    pub wallet: Pubkey,
+
+    /// farm account address of voted gauge
+    pub farm_id: Pubkey,
+
+    /// voted power
+    pub power: u64,
+}
+
+/// Rewards added by a contributor to an open funding farm
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Sponsor {
+    /// farm account address
+    pub farm_id: Pubkey,
+
+    /// contributor's wallet address
+    pub wallet: Pubkey,
+
+    /// reward amount received by the farm from this contributor
+    pub amount: u64,
+
+    /// 1 if unspent share is refunded
+    pub is_refunded: u8,
+
+    /// refunded reward amount
+    pub refunded_amount: u64,
+}
+
+/// Deposit balance of a user from given time
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct StakeCheckpoint {
+    /// time when balance changed
+    pub timestamp: u64,
+
+    /// deposit balance from this time
+    pub balance: u64,
+}
+
+/// Result of harvesting one farm in HarvestMany instruction
+/// A list of this struct is returned by `set_return_data`
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct HarvestResult {
+    /// farm account address
+    pub farm_id: Pubkey,
+
+    /// 0 if harvested, otherwise the error code why this farm was skipped
+    pub status: u64,
+
+    /// reward amount transferred to user
+    pub amount: u64,
+
+    /// harvest fee transferred to fee owner
+    pub fee: u64,
+}
+
+/// Farm state of a user returned by ViewPendingRewards instruction
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct PendingRewardsView {
+    /// pending reward amount what the user can harvest now, before harvest fee
+    pub pending_rewards: u64,
+
+    /// reward amount distributed per second from now
+    pub reward_per_second: u64,
+
+    /// reward amount not distributed yet
+    pub remained_reward_amount: u64,
+
+    /// total staked lp amount in this farm
+    pub total_lp_amount: u64,
+
+    /// total reward amount for unit lp so far
+    pub reward_per_share_net: u128,
+
+    /// timestamp used for this view
+    pub timestamp: u64,
+}
+
+/// Staked balance of a user at a past time returned by ViewStakeAt instruction
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct StakeSnapshotView {
+    /// farm account address
+    pub farm_id: Pubkey,
+
+    /// user's wallet address
+    pub wallet: Pubkey,
+
+    /// queried timestamp
+    pub timestamp: u64,
+
+    /// deposit balance at the end of queried second
+    pub balance: u64,
+}
+
+/// Token whitelisted to pay farm fee
+/// USDC value of payment is computed from reserves of an AMM pool pairing this token with USDC
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct FeeToken {
+    /// mint address of this token
+    pub mint: Pubkey,
+
+    /// enabled flag, farm fee can be paid in this token only if enabled
+    pub is_enabled: u8,
+
+    /// AMM pool pairing this token with USDC
+    pub amm_id: Pubkey,
+
+    /// allowed difference between paid value and farm fee, numerator
+    pub tolerance_numerator: u64,
+
+    /// allowed difference between paid value and farm fee, denominator
+    pub tolerance_denominator: u64,
+
+    /// farm fee discount for this token such as CRP, numerator
+    pub discount_numerator: u64,
+
+    /// farm fee discount for this token such as CRP, denominator
+    pub discount_denominator: u64,
+
+    /// reference price set by super owner, USDC amount paid for `reference_token_amount` of this token
+    pub reference_usdc_amount: u64,
+
+    /// token amount of reference price
+    pub reference_token_amount: u64,
+
+    /// allowed deviation of amm pool price from reference price in basis points
+    pub max_price_deviation_bps: u64,
+}
+impl FeeToken {
+    /// check if reference price is set and deviation is in bounds
+    pub fn validate_reference_price(&self) -> Result<(), ProgramError> {
+        if  self.reference_usdc_amount == 0 ||
+            self.reference_token_amount == 0 ||
+            self.max_price_deviation_bps > BPS_DENOMINATOR {
+            return Err(FarmError::InvalidReferencePrice.into());
+        }
+        Ok(())
+    }
+
+    /// check if amm pool price is close to reference price
+    /// spot reserves can be moved in the same transaction, so pool price alone is not trusted
+    pub fn check_pool_price(&self, token_reserve: u64, usdc_reserve: u64) -> Result<(), ProgramError> {
+        self.validate_reference_price()?;
+        let pool_value = u128::from(usdc_reserve)
+            .checked_mul(u128::from(self.reference_token_amount))
+            .ok_or(FarmError::CalculationFailure)?;
+        let reference_value = u128::from(self.reference_usdc_amount)
+            .checked_mul(u128::from(token_reserve))
+            .ok_or(FarmError::CalculationFailure)?;
+        let band = reference_value
+            .checked_mul(u128::from(self.max_price_deviation_bps))
+            .ok_or(FarmError::CalculationFailure)?
+            .checked_div(u128::from(BPS_DENOMINATOR))
+            .ok_or(FarmError::CalculationFailure)?;
+        if pool_value.saturating_add(band) < reference_value || pool_value > reference_value.saturating_add(band) {
+            msg!("pool price deviates from reference price more than {} bps", self.max_price_deviation_bps);
+            return Err(FarmError::PriceOutOfReference.into());
+        }
+        Ok(())
+    }
+
+    /// get farm fee in USDC after discount of this token
+    pub fn get_discounted_fee(&self, farm_fee: u64) -> Result<u64, ProgramError> {
+        if self.discount_numerator == 0 || self.discount_denominator == 0 {
+            return Ok(farm_fee);
+        }
+        let discount = u128::from(farm_fee)
+            .checked_mul(u128::from(self.discount_numerator))
+            .ok_or(FarmError::CalculationFailure)?
+            .checked_div(u128::from(self.discount_denominator))
+            .ok_or(FarmError::CalculationFailure)?;
+        let discount = u64::try_from(discount).map_err(|_| FarmError::CalculationFailure)?;
+        farm_fee.checked_sub(discount).ok_or_else(|| FarmError::CalculationFailure.into())
+    }
+
+    /// check if paid USDC value is in the tolerance band around the fee
+    /// price can move between the time client computes payment amount and the execution
+    pub fn check_fee_value(&self, value: u64, fee: u64) -> Result<(), ProgramError> {
+        let band = if self.tolerance_denominator == 0 {
+            0
+        } else {
+            u128::from(fee)
+                .checked_mul(u128::from(self.tolerance_numerator))
+                .ok_or(FarmError::CalculationFailure)?
+                .checked_div(u128::from(self.tolerance_denominator))
+                .ok_or(FarmError::CalculationFailure)?
+        };
+        let value = u128::from(value);
+        let fee = u128::from(fee);
+        if value.saturating_add(band) < fee || value > fee.saturating_add(band) {
+            msg!("fee value {} is out of {} +- {}", value, fee, band);
+            return Err(FarmError::FeeOutOfTolerance.into());
+        }
+        Ok(())
+    }
+}
+
+/// Account of an instruction proposed to multisig
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct ProposalAccount {
+    /// account address
+    pub pubkey: Pubkey,
+
+    /// signer flag
+    pub is_signer: bool,
+
+    /// writable flag
+    pub is_writable: bool,
+}
+
+/// Admin instruction of this program proposed to multisig
+/// Multisig account uses `spl_token::state::Multisig` layout
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct Proposal {
+    /// multisig account to approve this proposal
+    pub multisig: Pubkey,
+
+    /// multisig signer who created this proposal
+    pub proposer: Pubkey,
+
+    /// approved signer positions of multisig, bit i is set if signer i approved
+    pub approvals: u16,
+
+    /// executed flag
+    pub is_executed: u8,
+
+    /// accounts of proposed instruction
+    pub accounts: Vec<ProposalAccount>,
+
+    /// data of proposed instruction
+    pub data: Vec<u8>,
+}
+impl Proposal {
+    /// record approval of all multisig positions held by the signer, returns false if signer is not in multisig
+    pub fn approve(&mut self, signers: &[Pubkey], signer: &Pubkey) -> bool {
+        let mut matched = false;
+        for (i, key) in signers.iter().enumerate() {
+            if key == signer {
+                self.approvals |= 1 << i;
+                matched = true;
+            }
+        }
+        matched
+    }
+
+    /// get number of approvals
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+}
+
 /// User information struct
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
@@ -235,4 +1157,705 @@ pub struct UserInfo {
 
     /// reward debt so far
     pub reward_debt: u64,
+
+    /// latest deposit time, minimum stake duration is counted from this time
+    pub deposit_timestamp: u64,
+
+    /// withdrawn lp amount waiting for unbonding period
+    pub unbonding_amount: u64,
+
+    /// time when unbonding lp can be claimed
+    pub unbonding_timestamp: u64,
+
+    /// effective stake in boosted farm
+    pub effective_balance: u64,
+
+    /// number of checkpoints written so far, next one is stored at checkpoint_count % STAKE_CHECKPOINTS
+    pub checkpoint_count: u64,
+
+    /// ring buffer of deposit balance changes
+    pub checkpoints: [StakeCheckpoint; STAKE_CHECKPOINTS],
+
+    /// start time of uninterrupted staking, loyalty multiplier grows from this time
+    pub stake_start_timestamp: u64,
+
+    /// index of this position among the wallet's positions in the farm, 0 is the default user info and the only one boosted by voting power
+    pub position_index: u64,
+
+    /// rewards kept for the user when deposit balance moved without harvest, paid by the next harvest
+    pub reward_credit: u64,
+}
+impl UserInfo {
+    /// check if user has stake or kept rewards to harvest
+    pub fn has_rewards_to_harvest(&self) -> bool {
+        self.deposit_balance > 0 || self.reward_credit > 0
+    }
+
+    /// record deposit balance after a change at given time
+    /// user info without history starts it with the balance held since the latest deposit
+    pub fn write_checkpoint(&mut self, old_balance: u64, cur_timestamp: u64) {
+        if self.checkpoint_count == 0 && old_balance > 0 {
+            self.push_checkpoint(self.deposit_timestamp.min(cur_timestamp), old_balance);
+        }
+        self.push_checkpoint(cur_timestamp, self.deposit_balance);
+    }
+
+    /// store a checkpoint, several changes in the same second keep only the last balance
+    fn push_checkpoint(&mut self, timestamp: u64, balance: u64) {
+        if self.checkpoint_count > 0 {
+            let last = ((self.checkpoint_count - 1) as usize) % STAKE_CHECKPOINTS;
+            if self.checkpoints[last].timestamp == timestamp {
+                self.checkpoints[last].balance = balance;
+                return;
+            }
+        }
+        let index = (self.checkpoint_count as usize) % STAKE_CHECKPOINTS;
+        self.checkpoints[index] = StakeCheckpoint { timestamp, balance };
+        self.checkpoint_count += 1;
+    }
+
+    /// deposit balance at the end of given second
+    pub fn get_stake_at(&self, timestamp: u64) -> Result<u64, ProgramError> {
+        // no history, balance hasn't changed since the latest deposit
+        if self.checkpoint_count == 0 {
+            if timestamp >= self.deposit_timestamp {
+                return Ok(self.deposit_balance);
+            }
+            return Ok(0);
+        }
+
+        let count = (self.checkpoint_count as usize).min(STAKE_CHECKPOINTS);
+        for i in 1..=count {
+            let index = ((self.checkpoint_count as usize) - i) % STAKE_CHECKPOINTS;
+            if self.checkpoints[index].timestamp <= timestamp {
+                return Ok(self.checkpoints[index].balance);
+            }
+        }
+
+        // older checkpoints are overwritten, balance at this time is unknown
+        if self.checkpoint_count as usize > STAKE_CHECKPOINTS {
+            return Err(FarmError::CheckpointOverwritten.into());
+        }
+        Ok(0)
+    }
+
+    /// read user info from account data, accounts created before a layout extension are zero padded
+    pub fn unpack_from_slice(data: &[u8]) -> Result<UserInfo, ProgramError> {
+        unpack_extended::<UserInfo>(data, get_packed_len::<UserInfo>())
+    }
+
+    /// write user info to account data
+    pub fn pack_into_slice(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        pack_extended(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_owner() -> Pubkey {
+        Pubkey::new_unique()
+    }
+
+    #[test]
+    fn validate_params_accepts_bounded_fee() {
+        assert_eq!(FarmProgram::validate_params(&fee_owner(), 0, 1000), Ok(()));
+        assert_eq!(FarmProgram::validate_params(&fee_owner(), 1, 1000), Ok(()));
+        assert_eq!(FarmProgram::validate_params(&fee_owner(), MAX_HARVEST_FEE_BPS, BPS_DENOMINATOR), Ok(()));
+    }
+
+    #[test]
+    fn validate_params_rejects_zero_denominator() {
+        assert_eq!(
+            FarmProgram::validate_params(&fee_owner(), 0, 0),
+            Err(FarmError::ZeroFeeDenominator.into())
+        );
+        assert_eq!(
+            FarmProgram::validate_params(&fee_owner(), 1, 0),
+            Err(FarmError::ZeroFeeDenominator.into())
+        );
+    }
+
+    #[test]
+    fn validate_params_rejects_fee_above_max() {
+        assert_eq!(
+            FarmProgram::validate_params(&fee_owner(), MAX_HARVEST_FEE_BPS + 1, BPS_DENOMINATOR),
+            Err(FarmError::HarvestFeeTooHigh.into())
+        );
+    }
+
+    #[test]
+    fn validate_params_rejects_numerator_above_denominator() {
+        assert_eq!(
+            FarmProgram::validate_params(&fee_owner(), 2, 1),
+            Err(FarmError::HarvestFeeTooHigh.into())
+        );
+        assert_eq!(
+            FarmProgram::validate_params(&fee_owner(), u64::MAX, u64::MAX - 1),
+            Err(FarmError::HarvestFeeTooHigh.into())
+        );
+    }
+
+    #[test]
+    fn validate_params_rejects_default_fee_owner() {
+        assert_eq!(
+            FarmProgram::validate_params(&Pubkey::default(), 1, 1000),
+            Err(FarmError::FeeOwnerNotSet.into())
+        );
+    }
+
+    fn audit_entry(kind: u8) -> AuditEntry {
+        AuditEntry {
+            kind,
+            slot: u64::from(kind),
+            ..AuditEntry::default()
+        }
+    }
+
+    #[test]
+    fn append_to_slice_rejects_short_data() {
+        let mut data = vec![0; get_packed_len::<AuditLog>() - 1];
+        assert_eq!(
+            AuditLog::append_to_slice(&mut data, &audit_entry(AUDIT_SET_PROGRAM_DATA)),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        let mut data = vec![];
+        assert_eq!(
+            AuditLog::append_to_slice(&mut data, &audit_entry(AUDIT_SET_PROGRAM_DATA)),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    #[test]
+    fn append_to_slice_overwrites_oldest_entry() {
+        let mut data = vec![0; get_packed_len::<AuditLog>()];
+        for i in 0..AUDIT_LOG_ENTRIES + 2 {
+            AuditLog::append_to_slice(&mut data, &audit_entry(i as u8)).unwrap();
+        }
+        let audit_log = AuditLog::try_from_slice(&data).unwrap();
+        let entries = audit_log.get_entries();
+        assert_eq!(audit_log.count, (AUDIT_LOG_ENTRIES + 2) as u64);
+        assert_eq!(entries.len(), AUDIT_LOG_ENTRIES);
+        assert_eq!(entries[0], audit_entry(2));
+        assert_eq!(entries[AUDIT_LOG_ENTRIES - 1], audit_entry((AUDIT_LOG_ENTRIES + 1) as u8));
+    }
+
+    fn fee_token() -> FeeToken {
+        FeeToken {
+            reference_usdc_amount: 2_000_000,
+            reference_token_amount: 1_000_000_000,
+            max_price_deviation_bps: 500,
+            ..FeeToken::default()
+        }
+    }
+
+    #[test]
+    fn check_pool_price_accepts_price_in_band() {
+        let fee_token = fee_token();
+        assert_eq!(fee_token.check_pool_price(1_000_000_000_000, 2_000_000_000), Ok(()));
+        assert_eq!(fee_token.check_pool_price(1_000_000_000_000, 2_100_000_000), Ok(()));
+        assert_eq!(fee_token.check_pool_price(1_000_000_000_000, 1_900_000_000), Ok(()));
+    }
+
+    #[test]
+    fn check_pool_price_rejects_manipulated_reserves() {
+        let fee_token = fee_token();
+        assert_eq!(
+            fee_token.check_pool_price(1_000_000_000_000, 2_100_000_001),
+            Err(FarmError::PriceOutOfReference.into())
+        );
+        assert_eq!(
+            fee_token.check_pool_price(500_000_000_000, 2_000_000_000),
+            Err(FarmError::PriceOutOfReference.into())
+        );
+    }
+
+    #[test]
+    fn check_pool_price_requires_reference_price() {
+        let fee_token = FeeToken { reference_usdc_amount: 0, ..fee_token() };
+        assert_eq!(
+            fee_token.check_pool_price(1_000_000_000_000, 2_000_000_000),
+            Err(FarmError::InvalidReferencePrice.into())
+        );
+    }
+
+    #[test]
+    fn moved_vote_is_not_counted_twice_in_one_epoch() {
+        let mut gauge_a = Gauge { weight: 100, ..Gauge::default() };
+        let mut gauge_b = Gauge::default();
+
+        // epoch 1 - checkpoint A, then move the vote from A to B and checkpoint B
+        gauge_a.roll_epoch(1);
+        let allocated_a = gauge_a.epoch_weight;
+        gauge_a.roll_epoch(1);
+        gauge_a.weight -= 100;
+        gauge_b.roll_epoch(1);
+        gauge_b.weight += 100;
+        gauge_b.roll_epoch(1);
+        let allocated_b = gauge_b.epoch_weight;
+        assert_eq!(allocated_a + allocated_b, 100);
+
+        // epoch 2 - moved vote is counted for B only
+        gauge_a.roll_epoch(2);
+        gauge_b.roll_epoch(2);
+        assert_eq!(gauge_a.epoch_weight, 0);
+        assert_eq!(gauge_b.epoch_weight, 100);
+    }
+
+    #[test]
+    fn roll_epoch_keeps_snapshot_within_epoch() {
+        let mut gauge = Gauge { weight: 10, weight_epoch: 3, epoch_weight: 7, ..Gauge::default() };
+        gauge.roll_epoch(3);
+        assert_eq!(gauge.epoch_weight, 7);
+        gauge.roll_epoch(5);
+        assert_eq!((gauge.epoch_weight, gauge.weight_epoch), (10, 5));
+    }
+
+    #[test]
+    fn farm_emission_includes_pending_emission() {
+        let mut emitter = Emitter {
+            reward_per_second: 10,
+            total_alloc_point: 4,
+            ..Emitter::default()
+        };
+        let mut farm_pool = FarmPool {
+            alloc_point: 1,
+            ..FarmPool::default()
+        };
+        emitter.update(100).unwrap();
+        assert_eq!(emitter.get_farm_emission(&farm_pool), Ok(250));
+
+        // nothing was delivered, debt moved to the latest emission
+        farm_pool.emitter_reward_debt = emitter.reward_per_point_net;
+        farm_pool.pending_emission = 250;
+        assert_eq!(emitter.get_farm_emission(&farm_pool), Ok(250));
+
+        emitter.update(140).unwrap();
+        assert_eq!(emitter.get_farm_emission(&farm_pool), Ok(350));
+    }
+
+    /// packed length of farm pool layout before statistics were added
+    const LEGACY_FARM_POOL_LEN: usize = 242;
+
+    fn legacy_farm_pool() -> FarmPool {
+        FarmPool {
+            is_allowed: 1,
+            nonce: 255,
+            owner: Pubkey::new_unique(),
+            reward_per_share_net: 12345,
+            last_timestamp: 100,
+            remained_reward_amount: 5000,
+            start_timestamp: 50,
+            end_timestamp: 1000,
+            ..FarmPool::default()
+        }
+    }
+
+    #[test]
+    fn unpack_extended_pads_legacy_farm_pool() {
+        let farm_pool = legacy_farm_pool();
+        let data = farm_pool.try_to_vec().unwrap();
+        assert_eq!(FarmPool::unpack_from_slice(&data[..LEGACY_FARM_POOL_LEN]), Ok(farm_pool));
+    }
+
+    #[test]
+    fn pack_extended_writes_legacy_farm_pool_without_new_fields() {
+        let farm_pool = legacy_farm_pool();
+        let mut data = vec![0; LEGACY_FARM_POOL_LEN];
+        farm_pool.pack_into_slice(&mut data).unwrap();
+        assert_eq!(data, farm_pool.try_to_vec().unwrap()[..LEGACY_FARM_POOL_LEN].to_vec());
+    }
+
+    #[test]
+    fn pack_extended_rejects_new_fields_in_legacy_farm_pool() {
+        let farm_pool = FarmPool {
+            total_lp_deposited: 1,
+            ..legacy_farm_pool()
+        };
+        let mut data = vec![0; LEGACY_FARM_POOL_LEN];
+        assert_eq!(
+            farm_pool.pack_into_slice(&mut data),
+            Err(FarmError::AccountNotExtended.into())
+        );
+    }
+
+    #[test]
+    fn early_withdraw_fee_is_charged_within_min_stake_duration() {
+        let farm_pool = FarmPool {
+            min_stake_duration: 100,
+            early_withdraw_fee_numerator: 5,
+            early_withdraw_fee_denominator: 1000,
+            ..FarmPool::default()
+        };
+        let user_info = UserInfo {
+            deposit_timestamp: 1000,
+            ..UserInfo::default()
+        };
+        assert_eq!(farm_pool.get_early_withdraw_fee(10_000, &user_info, 1000), Ok(50));
+        assert_eq!(farm_pool.get_early_withdraw_fee(10_000, &user_info, 1099), Ok(50));
+        assert_eq!(farm_pool.get_early_withdraw_fee(10_000, &user_info, 1100), Ok(0));
+    }
+
+    #[test]
+    fn early_withdraw_fee_is_zero_without_fee_numerator() {
+        let farm_pool = FarmPool {
+            min_stake_duration: 100,
+            early_withdraw_fee_denominator: 1000,
+            ..FarmPool::default()
+        };
+        let user_info = UserInfo {
+            deposit_timestamp: 1000,
+            ..UserInfo::default()
+        };
+        assert_eq!(farm_pool.get_early_withdraw_fee(10_000, &user_info, 1000), Ok(0));
+    }
+
+    #[test]
+    fn approve_records_every_position_of_signer() {
+        let signer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let signers = [signer, other, signer];
+        let mut proposal = Proposal::default();
+
+        assert!(proposal.approve(&signers, &signer));
+        assert_eq!(proposal.approvals, 0b101);
+        assert_eq!(proposal.approval_count(), 2);
+
+        // approving twice doesn't add approvals
+        assert!(proposal.approve(&signers, &signer));
+        assert_eq!(proposal.approval_count(), 2);
+
+        assert!(proposal.approve(&signers, &other));
+        assert_eq!(proposal.approval_count(), 3);
+    }
+
+    #[test]
+    fn approve_rejects_signer_outside_multisig() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut proposal = Proposal::default();
+        assert!(!proposal.approve(&signers, &Pubkey::new_unique()));
+        assert_eq!(proposal.approvals, 0);
+    }
+
+    #[test]
+    fn add_emission_distributes_by_lp_balance() {
+        let mut farm_pool = FarmPool {
+            reward_per_share_net: 7,
+            ..FarmPool::default()
+        };
+        farm_pool.add_emission(500, 1000).unwrap();
+        assert_eq!(farm_pool.reward_per_share_net, 7 + (REWARD_MULTIPLER as u128) / 2);
+        assert_eq!(farm_pool.total_rewards_added, 500);
+
+        let mut user_info = UserInfo {
+            deposit_balance: 200,
+            reward_debt: JUMP_DEBT,
+            ..UserInfo::default()
+        };
+        farm_pool.reward_per_share_net = 0;
+        farm_pool.add_emission(500, 1000).unwrap();
+        assert_eq!(farm_pool.pending_rewards(&mut user_info), Ok(100));
+    }
+
+    #[test]
+    fn keep_pending_rewards_survives_moving_whole_balance() {
+        let farm_pool = FarmPool {
+            reward_per_share_net: 2 * REWARD_MULTIPLER as u128,
+            ..FarmPool::default()
+        };
+        let mut user_info = UserInfo {
+            deposit_balance: 100,
+            reward_debt: JUMP_DEBT + 50,
+            ..UserInfo::default()
+        };
+        let pending = farm_pool.pending_rewards(&mut user_info).unwrap();
+        assert_eq!(pending, 150);
+
+        user_info.deposit_balance = 0;
+        farm_pool.keep_pending_rewards(&mut user_info, pending).unwrap();
+        assert_eq!(user_info.reward_debt, JUMP_DEBT);
+        assert!(user_info.has_rewards_to_harvest());
+        assert_eq!(farm_pool.pending_rewards(&mut user_info), Ok(150));
+    }
+
+    #[test]
+    fn add_emission_rejects_zero_lp_balance() {
+        let mut farm_pool = FarmPool::default();
+        assert_eq!(
+            farm_pool.add_emission(500, 0),
+            Err(FarmError::CalculationFailure.into())
+        );
+    }
+
+    fn boosted_farm_pool() -> FarmPool {
+        FarmPool {
+            boost_base_numerator: 4,
+            boost_base_denominator: 10,
+            ..FarmPool::default()
+        }
+    }
+
+    #[test]
+    fn boosted_stake_grows_with_lock_share_up_to_deposit() {
+        let farm_pool = boosted_farm_pool();
+        assert_eq!(farm_pool.get_boosted_stake(100, 1000, 0, 0), Ok(40));
+        assert_eq!(farm_pool.get_boosted_stake(100, 1000, 0, 100), Ok(40));
+        assert_eq!(farm_pool.get_boosted_stake(100, 1000, 5, 100), Ok(70));
+        assert_eq!(farm_pool.get_boosted_stake(100, 1000, 50, 100), Ok(100));
+    }
+
+    #[test]
+    fn boosted_stake_ignores_unbonding_lp() {
+        let farm_pool = FarmPool {
+            total_unbonding_amount: 500,
+            ..boosted_farm_pool()
+        };
+        assert_eq!(farm_pool.get_boosted_stake(100, 1000, 5, 100), Ok(55));
+    }
+
+    #[test]
+    fn update_effective_balance_replaces_user_stake_in_total() {
+        let mut farm_pool = boosted_farm_pool();
+        let mut user_info = UserInfo {
+            deposit_balance: 100,
+            ..UserInfo::default()
+        };
+        farm_pool.update_effective_balance(&mut user_info, 1000, 5, 100, 0).unwrap();
+        assert_eq!((user_info.effective_balance, farm_pool.total_effective_balance), (70, 70));
+
+        // lock ended
+        farm_pool.update_effective_balance(&mut user_info, 1000, 0, 100, 0).unwrap();
+        assert_eq!((user_info.effective_balance, farm_pool.total_effective_balance), (40, 40));
+    }
+
+    #[test]
+    fn update_effective_balance_boosts_only_default_position() {
+        let mut farm_pool = boosted_farm_pool();
+        let mut user_info = UserInfo {
+            deposit_balance: 100,
+            position_index: 1,
+            ..UserInfo::default()
+        };
+        farm_pool.update_effective_balance(&mut user_info, 1000, 50, 100, 0).unwrap();
+        assert_eq!(user_info.effective_balance, 40);
+    }
+
+    #[test]
+    fn update_effective_balance_skips_farm_without_effective_stake() {
+        let mut farm_pool = FarmPool::default();
+        let mut user_info = UserInfo {
+            deposit_balance: 100,
+            ..UserInfo::default()
+        };
+        farm_pool.update_effective_balance(&mut user_info, 1000, 50, 100, 0).unwrap();
+        assert_eq!((user_info.effective_balance, farm_pool.total_effective_balance), (0, 0));
+    }
+
+    /// writes a deposit of 10 at each timestamp 100, 200, ...
+    fn user_info_with_deposits(count: u64) -> UserInfo {
+        let mut user_info = UserInfo::default();
+        for i in 1..=count {
+            let old_balance = user_info.deposit_balance;
+            user_info.deposit_balance += 10;
+            user_info.deposit_timestamp = i * 100;
+            user_info.write_checkpoint(old_balance, i * 100);
+        }
+        user_info
+    }
+
+    #[test]
+    fn stake_at_reads_latest_checkpoint_before_time() {
+        let user_info = user_info_with_deposits(3);
+        assert_eq!(user_info.get_stake_at(99), Ok(0));
+        assert_eq!(user_info.get_stake_at(100), Ok(10));
+        assert_eq!(user_info.get_stake_at(250), Ok(20));
+        assert_eq!(user_info.get_stake_at(1000), Ok(30));
+    }
+
+    #[test]
+    fn stake_at_keeps_last_balance_of_same_second() {
+        let mut user_info = user_info_with_deposits(1);
+        user_info.deposit_balance = 25;
+        user_info.write_checkpoint(10, 100);
+        assert_eq!(user_info.checkpoint_count, 1);
+        assert_eq!(user_info.get_stake_at(100), Ok(25));
+    }
+
+    #[test]
+    fn stake_at_wraps_ring_buffer() {
+        let count = STAKE_CHECKPOINTS as u64 + 4;
+        let user_info = user_info_with_deposits(count);
+        assert_eq!(user_info.checkpoint_count, count);
+        assert_eq!(user_info.get_stake_at(count * 100), Ok(count * 10));
+        assert_eq!(user_info.get_stake_at(500), Ok(50));
+        assert_eq!(
+            user_info.get_stake_at(499),
+            Err(FarmError::CheckpointOverwritten.into())
+        );
+    }
+
+    #[test]
+    fn stake_at_starts_history_with_balance_before_first_checkpoint() {
+        let mut user_info = UserInfo {
+            deposit_balance: 40,
+            deposit_timestamp: 100,
+            ..UserInfo::default()
+        };
+        assert_eq!(user_info.get_stake_at(99), Ok(0));
+        assert_eq!(user_info.get_stake_at(150), Ok(40));
+
+        user_info.deposit_balance = 15;
+        user_info.write_checkpoint(40, 300);
+        assert_eq!(user_info.get_stake_at(150), Ok(40));
+        assert_eq!(user_info.get_stake_at(300), Ok(15));
+    }
+
+    #[test]
+    fn sponsor_refund_is_pro_rata_share_of_unspent_rewards() {
+        let farm_pool = FarmPool {
+            total_funded: 1000,
+            refund_unspent: 600,
+            remained_reward_amount: 600,
+            ..FarmPool::default()
+        };
+        let sponsor = Sponsor {
+            amount: 250,
+            ..Sponsor::default()
+        };
+        assert_eq!(farm_pool.get_sponsor_refund(&sponsor), Ok(150));
+    }
+
+    #[test]
+    fn sponsor_refund_is_capped_by_remained_rewards() {
+        let farm_pool = FarmPool {
+            total_funded: 1000,
+            refund_unspent: 600,
+            remained_reward_amount: 100,
+            ..FarmPool::default()
+        };
+        let sponsor = Sponsor {
+            amount: 250,
+            ..Sponsor::default()
+        };
+        assert_eq!(farm_pool.get_sponsor_refund(&sponsor), Ok(100));
+    }
+
+    #[test]
+    fn sponsor_refund_is_zero_without_funding() {
+        let farm_pool = FarmPool {
+            refund_unspent: 600,
+            remained_reward_amount: 600,
+            ..FarmPool::default()
+        };
+        let sponsor = Sponsor {
+            amount: 250,
+            ..Sponsor::default()
+        };
+        assert_eq!(farm_pool.get_sponsor_refund(&sponsor), Ok(0));
+    }
+
+    #[test]
+    fn loyalty_multiplier_grows_over_loyalty_period() {
+        let mut farm_pool = FarmPool {
+            loyalty_period: 100,
+            loyalty_max_numerator: 2,
+            loyalty_max_denominator: 1,
+            ..FarmPool::default()
+        };
+        let mut user_info = UserInfo {
+            deposit_balance: 100,
+            stake_start_timestamp: 1000,
+            ..UserInfo::default()
+        };
+        farm_pool.update_effective_balance(&mut user_info, 100, 0, 0, 1000).unwrap();
+        assert_eq!(user_info.effective_balance, 100);
+        farm_pool.update_effective_balance(&mut user_info, 100, 0, 0, 1050).unwrap();
+        assert_eq!(user_info.effective_balance, 150);
+        farm_pool.update_effective_balance(&mut user_info, 100, 0, 0, 5000).unwrap();
+        assert_eq!((user_info.effective_balance, farm_pool.total_effective_balance), (200, 200));
+
+        // loyalty clock restarts after full withdrawal and new deposit
+        farm_pool.restart_loyalty(&mut user_info, 6000);
+        farm_pool.update_effective_balance(&mut user_info, 100, 0, 0, 6000).unwrap();
+        assert_eq!((user_info.effective_balance, farm_pool.total_effective_balance), (100, 100));
+    }
+
+    fn crank_farm_pool() -> FarmPool {
+        FarmPool {
+            crank_bounty: 10,
+            crank_interval: 60,
+            last_crank_timestamp: 1000,
+            end_timestamp: 2000,
+            remained_reward_amount: 500,
+            ..FarmPool::default()
+        }
+    }
+
+    #[test]
+    fn crank_bounty_is_paid_once_per_interval() {
+        let farm_pool = crank_farm_pool();
+        assert_eq!(farm_pool.get_crank_bounty(1059), 0);
+        assert_eq!(farm_pool.get_crank_bounty(1060), 10);
+        assert_eq!(farm_pool.get_crank_bounty(2000), 10);
+        assert_eq!(farm_pool.get_crank_bounty(2001), 0);
+    }
+
+    #[test]
+    fn crank_bounty_is_capped_by_remained_rewards() {
+        let farm_pool = FarmPool {
+            remained_reward_amount: 3,
+            ..crank_farm_pool()
+        };
+        assert_eq!(farm_pool.get_crank_bounty(1060), 3);
+
+        let farm_pool = FarmPool {
+            crank_bounty: 0,
+            ..crank_farm_pool()
+        };
+        assert_eq!(farm_pool.get_crank_bounty(1060), 0);
+    }
+
+    #[test]
+    fn take_emission_drops_emission_without_stake() {
+        let mut emitter = Emitter {
+            reward_per_second: 10,
+            total_alloc_point: 1,
+            ..Emitter::default()
+        };
+        let mut farm_pool = FarmPool {
+            alloc_point: 1,
+            ..FarmPool::default()
+        };
+
+        // nobody stakes for 100 seconds
+        emitter.update(100).unwrap();
+        let emission = emitter.get_farm_emission(&farm_pool).unwrap();
+        farm_pool.emitter_reward_debt = emitter.reward_per_point_net;
+        assert_eq!(farm_pool.take_emission(emission, 0, 10_000), 0);
+        assert_eq!(farm_pool.pending_emission, 0);
+
+        // first depositor doesn't get emission of the idle period
+        let emission = emitter.get_farm_emission(&farm_pool).unwrap();
+        assert_eq!(farm_pool.take_emission(emission, 1, 10_000), 0);
+        assert_eq!(farm_pool.reward_per_share_net, 0);
+
+        emitter.update(110).unwrap();
+        let emission = emitter.get_farm_emission(&farm_pool).unwrap();
+        assert_eq!(farm_pool.take_emission(emission, 1, 10_000), 100);
+    }
+
+    #[test]
+    fn take_emission_keeps_vault_shortfall_pending() {
+        let mut farm_pool = FarmPool::default();
+        assert_eq!(farm_pool.take_emission(250, 10, 100), 100);
+        assert_eq!(farm_pool.pending_emission, 150);
+
+        // pending emission isn't dropped while nobody stakes
+        assert_eq!(farm_pool.take_emission(400, 0, 1000), 0);
+        assert_eq!(farm_pool.pending_emission, 150);
+
+        assert_eq!(farm_pool.take_emission(400, 10, 1000), 400);
+        assert_eq!(farm_pool.pending_emission, 0);
+    }
 }
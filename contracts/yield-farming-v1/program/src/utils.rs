@@ -1,6 +1,9 @@
 use {
+    crate::error::FarmError,
+    borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     solana_program::{
         account_info::AccountInfo,
+        borsh0_9,
         msg,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
@@ -12,6 +15,18 @@ use {
 };
 
 
+/// get packed length of a borsh 0.9 type, account layouts of this program are borsh 0.9
+#[allow(deprecated)]
+pub fn get_packed_len<S: BorshSchema>() -> usize {
+    borsh0_9::get_packed_len::<S>()
+}
+
+/// deserialize a borsh 0.9 type from account data which may have trailing bytes
+#[allow(deprecated)]
+pub fn try_from_slice_unchecked<T: BorshDeserialize>(data: &[u8]) -> Result<T, std::io::Error> {
+    borsh0_9::try_from_slice_unchecked::<T>(data)
+}
+
 #[inline(always)]
 pub fn create_or_allocate_account_raw<'a>(
     program_id: Pubkey,
@@ -31,7 +46,7 @@ pub fn create_or_allocate_account_raw<'a>(
     if required_lamports > 0 {
         msg!("Transfer {} lamports to the new account", required_lamports);
         invoke(
-            &system_instruction::transfer(&payer_info.key, new_account_info.key, required_lamports),
+            &system_instruction::transfer(payer_info.key, new_account_info.key, required_lamports),
             &[
                 payer_info.clone(),
                 new_account_info.clone(),
@@ -44,16 +59,76 @@ pub fn create_or_allocate_account_raw<'a>(
     invoke_signed(
         &system_instruction::allocate(new_account_info.key, size.try_into().unwrap()),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
 
     msg!("Assign the account to the owning program");
     invoke_signed(
         &system_instruction::assign(new_account_info.key, &program_id),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
     msg!("Completed assignation!");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// grow a program owned account to `size` bytes, topping up rent from the payer
+#[inline(always)]
+pub fn realloc_account<'a>(
+    account_info: &AccountInfo<'a>,
+    rent_sysvar_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    size: usize,
+) -> Result<(), ProgramError> {
+    if account_info.data_len() >= size {
+        return Ok(());
+    }
+    let rent = &Rent::from_account_info(rent_sysvar_info)?;
+    let required_lamports = rent
+        .minimum_balance(size)
+        .saturating_sub(account_info.lamports());
+
+    if required_lamports > 0 {
+        msg!("Transfer {} lamports to extend the account", required_lamports);
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, required_lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    msg!("Reallocate account to {} bytes", size);
+    account_info.realloc(size, true)
+}
+
+/// deserialize account data which may be created with an older and shorter layout
+/// fields appended after the account was created are read as zero
+pub fn unpack_extended<T: BorshDeserialize>(data: &[u8], len: usize) -> Result<T, ProgramError> {
+    if data.len() >= len {
+        return try_from_slice_unchecked::<T>(data).map_err(|e| e.into());
+    }
+    let mut buf = data.to_vec();
+    buf.resize(len, 0);
+    try_from_slice_unchecked::<T>(&buf).map_err(|e| e.into())
+}
+
+/// serialize into account data which may be shorter than the current layout
+/// the part which doesn't fit must be zero, otherwise the account has to be extended first
+pub fn pack_extended<T: BorshSerialize>(value: &T, data: &mut [u8]) -> Result<(), ProgramError> {
+    let buf = value.try_to_vec()?;
+    if buf.len() <= data.len() {
+        data[..buf.len()].copy_from_slice(&buf);
+        return Ok(());
+    }
+    let len = data.len();
+    if buf[len..].iter().any(|b| *b != 0) {
+        return Err(FarmError::AccountNotExtended.into());
+    }
+    data.copy_from_slice(&buf[..len]);
+    Ok(())
+}
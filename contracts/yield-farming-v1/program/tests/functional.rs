@@ -0,0 +1,708 @@
+//! Functional tests of farm instructions running in a local bank
+
+use {
+    borsh::BorshSerialize,
+    cropper_farm_v1::{
+        constant::{
+            ASSOCIATED_TOKEN_PROGRAM_ID, AUDIT_EXECUTE_PROGRAM_DATA, AUDIT_LOG_SEED, EMITTER_SEED,
+            PAY_FARM_FEE_ACCOUNTS, PREFIX, SOL_MINT_ADDRESS, TIMELOCK_SEED,
+        },
+        error::FarmError,
+        instruction,
+        state::{AuditLog, Emitter, FarmPool, FarmProgram, ProgramDataChange, UserInfo},
+        utils::get_packed_len,
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        clock::Clock,
+        instruction::{AccountMeta, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::{Keypair, Signer},
+        system_program,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::{Account as TokenAccount, AccountState, Mint},
+    std::str::FromStr,
+};
+
+const START: u64 = 1_000_000;
+const END: u64 = START + 1_000;
+const REWARD: u64 = 1_000_000;
+
+/// accounts of a farm injected into the bank
+struct Farm {
+    program_id: Pubkey,
+    program_data: Pubkey,
+    fee_owner: Pubkey,
+    owner: Keypair,
+    farm_id: Pubkey,
+    authority: Pubkey,
+    nonce: u8,
+    lp_mint: Pubkey,
+    reward_mint: Pubkey,
+    pool_lp: Pubkey,
+    pool_reward: Pubkey,
+    fee_reward_ata: Pubkey,
+}
+
+/// wallet of a staker with its token accounts
+struct Staker {
+    wallet: Keypair,
+    lp: Pubkey,
+    reward: Pubkey,
+}
+
+fn rent_exempt(len: usize) -> u64 {
+    Rent::default().minimum_balance(len)
+}
+
+fn program_account(program_id: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: rent_exempt(data.len()),
+        data,
+        owner: *program_id,
+        ..Account::default()
+    }
+}
+
+fn mint_account() -> Account {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: Some(Pubkey::new_unique()).into(),
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    }.pack_into_slice(&mut data);
+    Account {
+        lamports: rent_exempt(Mint::LEN),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    token_account_in_state(mint, owner, amount, AccountState::Initialized)
+}
+
+fn token_account_in_state(mint: &Pubkey, owner: &Pubkey, amount: u64, state: AccountState) -> Account {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state,
+        ..TokenAccount::default()
+    }.pack_into_slice(&mut data);
+    Account {
+        lamports: rent_exempt(TokenAccount::LEN),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+impl Farm {
+    /// farm with `REWARD` reward tokens distributed from `START` to `END`
+    fn new(program_test: &mut ProgramTest, program_id: Pubkey) -> Farm {
+        let (program_data, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], &program_id);
+        let farm_id = Pubkey::new_unique();
+        let (authority, nonce) = Pubkey::find_program_address(&[farm_id.as_ref()], &program_id);
+        let farm = Farm {
+            program_id,
+            program_data,
+            fee_owner: Pubkey::new_unique(),
+            owner: Keypair::new(),
+            farm_id,
+            authority,
+            nonce,
+            lp_mint: Pubkey::new_unique(),
+            reward_mint: Pubkey::new_unique(),
+            pool_lp: Pubkey::new_unique(),
+            pool_reward: Pubkey::new_unique(),
+            fee_reward_ata: Pubkey::new_unique(),
+        };
+
+        let farm_program = FarmProgram {
+            version: 1,
+            fee_owner: farm.fee_owner,
+            harvest_fee_numerator: 0,
+            harvest_fee_denominator: 1000,
+            ..FarmProgram::default()
+        };
+        program_test.add_account(program_data, program_account(&program_id, farm_program.try_to_vec().unwrap()));
+        program_test.add_account(farm.lp_mint, mint_account());
+        program_test.add_account(farm.reward_mint, mint_account());
+        program_test.add_account(farm.pool_lp, token_account(&farm.lp_mint, &authority, 0));
+        program_test.add_account(farm.pool_reward, token_account(&farm.reward_mint, &authority, REWARD));
+        program_test.add_account(farm.fee_reward_ata, token_account(&farm.reward_mint, &farm.fee_owner, 0));
+        program_test.add_account(farm.owner.pubkey(), Account::new(1_000_000_000, 0, &system_program::id()));
+        farm.set_pool(program_test, |_| {});
+        farm
+    }
+
+    /// farm pool state, pool version 1 and allowed
+    fn pool(&self) -> FarmPool {
+        FarmPool {
+            is_allowed: 11,
+            nonce: self.nonce,
+            pool_lp_token_account: self.pool_lp,
+            pool_reward_token_account: self.pool_reward,
+            pool_mint_address: self.lp_mint,
+            reward_mint_address: self.reward_mint,
+            token_program_id: spl_token::id(),
+            owner: self.owner.pubkey(),
+            last_timestamp: START,
+            remained_reward_amount: REWARD,
+            start_timestamp: START,
+            end_timestamp: END,
+            total_rewards_added: REWARD,
+            ..FarmPool::default()
+        }
+    }
+
+    /// inject farm pool account changed by `update`
+    fn set_pool(&self, program_test: &mut ProgramTest, update: impl FnOnce(&mut FarmPool)) {
+        let mut farm_pool = self.pool();
+        update(&mut farm_pool);
+        program_test.add_account(self.farm_id, program_account(&self.program_id, farm_pool.try_to_vec().unwrap()));
+    }
+
+    /// add a staker holding `lp_amount` LP tokens
+    fn add_staker(&self, program_test: &mut ProgramTest, lp_amount: u64) -> Staker {
+        let staker = Staker {
+            wallet: Keypair::new(),
+            lp: Pubkey::new_unique(),
+            reward: Pubkey::new_unique(),
+        };
+        program_test.add_account(staker.wallet.pubkey(), Account::new(1_000_000_000, 0, &system_program::id()));
+        program_test.add_account(staker.lp, token_account(&self.lp_mint, &staker.wallet.pubkey(), lp_amount));
+        program_test.add_account(staker.reward, token_account(&self.reward_mint, &staker.wallet.pubkey(), 0));
+        staker
+    }
+
+    fn user_info_address(&self, staker: &Staker) -> Pubkey {
+        self.position_address(staker, 0)
+    }
+
+    fn position_address(&self, staker: &Staker, position_index: u64) -> Pubkey {
+        instruction::find_user_info_address(&self.program_id, &self.farm_id, &staker.wallet.pubkey(), position_index).0
+    }
+
+    fn deposit(&self, staker: &Staker, amount: u64) -> solana_sdk::instruction::Instruction {
+        self.deposit_to_position(staker, amount, 0)
+    }
+
+    fn deposit_to_position(&self, staker: &Staker, amount: u64, position_index: u64) -> solana_sdk::instruction::Instruction {
+        instruction::deposit(
+            &self.farm_id,
+            &self.authority,
+            &staker.wallet.pubkey(),
+            &self.position_address(staker, position_index),
+            &staker.lp,
+            &self.pool_lp,
+            &staker.reward,
+            &self.pool_reward,
+            &self.lp_mint,
+            &self.fee_reward_ata,
+            &self.program_data,
+            &spl_token::id(),
+            &self.reward_mint,
+            &spl_token::id(),
+            &self.fee_owner,
+            amount,
+            position_index,
+            &self.program_id,
+        )
+    }
+
+    fn withdraw(&self, staker: &Staker, amount: u64) -> solana_sdk::instruction::Instruction {
+        instruction::withdraw(
+            &self.farm_id,
+            &self.authority,
+            &staker.wallet.pubkey(),
+            &self.user_info_address(staker),
+            &staker.lp,
+            &self.pool_lp,
+            &staker.reward,
+            &self.pool_reward,
+            &self.lp_mint,
+            &self.fee_reward_ata,
+            &self.program_data,
+            &spl_token::id(),
+            &self.reward_mint,
+            &spl_token::id(),
+            &self.fee_owner,
+            amount,
+            &self.program_id,
+        )
+    }
+
+    fn close_farm(&self, owner_reward: &Pubkey, reward_token_program_id: &Pubkey) -> solana_sdk::instruction::Instruction {
+        instruction::close_farm(
+            &self.farm_id,
+            &self.authority,
+            &self.owner.pubkey(),
+            &self.pool_lp,
+            &self.pool_reward,
+            owner_reward,
+            &self.reward_mint,
+            &spl_token::id(),
+            reward_token_program_id,
+            &self.program_id,
+        )
+    }
+
+    fn claim_unbonded(&self, staker: &Staker) -> solana_sdk::instruction::Instruction {
+        instruction::claim_unbonded(
+            &self.farm_id,
+            &self.authority,
+            &staker.wallet.pubkey(),
+            &self.user_info_address(staker),
+            &staker.lp,
+            &self.pool_lp,
+            &self.lp_mint,
+            &spl_token::id(),
+            &self.program_id,
+        )
+    }
+}
+
+/// emitter funding registered farms from its vault
+struct EmitterAccounts {
+    emitter: Pubkey,
+    authority: Pubkey,
+    vault: Pubkey,
+}
+
+impl EmitterAccounts {
+    /// emitter sending `reward_per_second` to `farm` which has all allocation points
+    fn new(program_test: &mut ProgramTest, farm: &Farm, reward_per_second: u64, vault_amount: u64) -> EmitterAccounts {
+        let program_id = farm.program_id;
+        let (emitter, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), EMITTER_SEED.as_bytes(), program_id.as_ref()], &program_id);
+        let (authority, nonce) = Pubkey::find_program_address(&[emitter.as_ref()], &program_id);
+        let vault = Pubkey::new_unique();
+        let state = Emitter {
+            nonce,
+            reward_mint: farm.reward_mint,
+            reward_vault: vault,
+            token_program_id: spl_token::id(),
+            reward_per_second,
+            total_alloc_point: 100,
+            last_timestamp: START,
+            ..Emitter::default()
+        };
+        program_test.add_account(emitter, program_account(&program_id, state.try_to_vec().unwrap()));
+        program_test.add_account(vault, token_account(&farm.reward_mint, &authority, vault_amount));
+        EmitterAccounts { emitter, authority, vault }
+    }
+
+    /// optional accounts following fixed accounts of a farm instruction
+    fn metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.emitter, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(self.vault, false),
+        ]
+    }
+}
+
+fn with_accounts(mut instruction: solana_sdk::instruction::Instruction, metas: Vec<AccountMeta>) -> solana_sdk::instruction::Instruction {
+    instruction.accounts.extend(metas);
+    instruction
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let associated_token_program_id = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap();
+    Pubkey::find_program_address(&[wallet.as_ref(), spl_token::id().as_ref(), mint.as_ref()], &associated_token_program_id).0
+}
+
+fn program_test() -> (ProgramTest, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("cropper_farm_v1", program_id, processor!(cropper_farm_v1::process_instruction));
+    (program_test, program_id)
+}
+
+async fn set_time(context: &mut ProgramTestContext, unix_timestamp: u64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp as i64;
+    context.set_sysvar(&clock);
+}
+
+async fn send(context: &mut ProgramTestContext, instruction: solana_sdk::instruction::Instruction, signer: &Keypair) -> Result<(), solana_program_test::BanksClientError> {
+    let blockhash = context.banks_client.get_new_latest_blockhash(&context.last_blockhash).await.unwrap();
+    context.last_blockhash = blockhash;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, signer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn get_lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_account(*address).await.unwrap().map_or(0, |account| account.lamports)
+}
+
+async fn get_token_amount(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn get_token_state(context: &mut ProgramTestContext, address: &Pubkey) -> AccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().state
+}
+
+async fn get_user_info(context: &mut ProgramTestContext, address: &Pubkey) -> (UserInfo, usize) {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    (UserInfo::unpack_from_slice(&account.data).unwrap(), account.data.len())
+}
+
+async fn get_farm_pool(context: &mut ProgramTestContext, farm: &Farm) -> FarmPool {
+    let account = context.banks_client.get_account(farm.farm_id).await.unwrap().unwrap();
+    FarmPool::unpack_from_slice(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn withdraw_extends_legacy_user_info_and_claims_after_unbonding() {
+    let (mut program_test, program_id) = program_test();
+    let farm = Farm::new(&mut program_test, program_id);
+    let staker = farm.add_staker(&mut program_test, 0);
+
+    // position staked before the layout extension, user info holds 80 bytes only
+    let legacy = UserInfo {
+        wallet: staker.wallet.pubkey(),
+        farm_id: farm.farm_id,
+        deposit_balance: 100,
+        ..UserInfo::default()
+    };
+    let user_info_address = farm.user_info_address(&staker);
+    program_test.add_account(user_info_address, program_account(&program_id, legacy.try_to_vec().unwrap()[..80].to_vec()));
+    program_test.add_account(farm.pool_lp, token_account(&farm.lp_mint, &farm.authority, 100));
+    farm.set_pool(&mut program_test, |pool| {
+        pool.total_lp_deposited = 100;
+        pool.staker_count = 1;
+    });
+
+    let mut context = program_test.start_with_context().await;
+    set_time(&mut context, START + 100).await;
+
+    send(&mut context, instruction::set_lockup_options(&farm.farm_id, &farm.owner.pubkey(), 0, 0, 1, 50, &program_id), &farm.owner).await.unwrap();
+    send(&mut context, farm.withdraw(&staker, 40), &staker.wallet).await.unwrap();
+
+    let (user_info, len) = get_user_info(&mut context, &user_info_address).await;
+    assert_eq!(len, get_packed_len::<UserInfo>());
+    assert_eq!(user_info.deposit_balance, 60);
+    assert_eq!(user_info.unbonding_amount, 40);
+    assert_eq!(user_info.unbonding_timestamp, START + 150);
+    assert_eq!(user_info.checkpoint_count, 2);
+    assert_eq!(get_token_amount(&mut context, &staker.lp).await, 0);
+    assert!(get_token_amount(&mut context, &staker.reward).await > 0);
+
+    // unbonded LP can't be claimed before the unbonding period ends
+    assert!(send(&mut context, farm.claim_unbonded(&staker), &staker.wallet).await.is_err());
+
+    set_time(&mut context, START + 150).await;
+    send(&mut context, farm.claim_unbonded(&staker), &staker.wallet).await.unwrap();
+    assert_eq!(get_token_amount(&mut context, &staker.lp).await, 40);
+    assert_eq!(get_token_amount(&mut context, &farm.pool_lp).await, 60);
+    let (user_info, _) = get_user_info(&mut context, &user_info_address).await;
+    assert_eq!(user_info.unbonding_amount, 0);
+    assert_eq!(get_farm_pool(&mut context, &farm).await.total_unbonding_amount, 0);
+}
+
+#[tokio::test]
+async fn close_native_farm_moves_authority_lamports_to_owner() {
+    let (mut program_test, program_id) = program_test();
+    let mut farm = Farm::new(&mut program_test, program_id);
+
+    // native SOL rewards are held by farm authority as lamports
+    farm.reward_mint = Pubkey::from_str(SOL_MINT_ADDRESS).unwrap();
+    farm.pool_reward = farm.authority;
+    let authority_lamports = rent_exempt(0) + 300;
+    program_test.add_account(farm.authority, Account::new(authority_lamports, 0, &system_program::id()));
+    farm.set_pool(&mut program_test, |pool| pool.native_reward = 1);
+
+    let mut context = program_test.start_with_context().await;
+    set_time(&mut context, END + 1).await;
+
+    let owner = farm.owner.pubkey();
+    let farm_lamports = get_lamports(&mut context, &farm.farm_id).await;
+    let pool_lp_lamports = get_lamports(&mut context, &farm.pool_lp).await;
+    let owner_lamports = get_lamports(&mut context, &owner).await;
+
+    send(&mut context, farm.close_farm(&owner, &system_program::id()), &farm.owner).await.unwrap();
+
+    assert_eq!(get_lamports(&mut context, &farm.authority).await, 0);
+    assert_eq!(get_lamports(&mut context, &farm.pool_lp).await, 0);
+    assert_eq!(get_lamports(&mut context, &farm.farm_id).await, 0);
+    assert_eq!(
+        get_lamports(&mut context, &owner).await,
+        owner_lamports + authority_lamports + pool_lp_lamports + farm_lamports
+    );
+}
+
+#[tokio::test]
+async fn deposit_after_idle_emitter_farm_gets_no_back_emission() {
+    let (mut program_test, program_id) = program_test();
+    let farm = Farm::new(&mut program_test, program_id);
+    let staker = farm.add_staker(&mut program_test, 100);
+    let emitter = EmitterAccounts::new(&mut program_test, &farm, 10, 1_000_000);
+
+    // farm is funded by emission only
+    program_test.add_account(farm.pool_reward, token_account(&farm.reward_mint, &farm.authority, 0));
+    farm.set_pool(&mut program_test, |pool| {
+        pool.alloc_point = 100;
+        pool.remained_reward_amount = 0;
+        pool.total_rewards_added = 0;
+    });
+
+    let mut context = program_test.start_with_context().await;
+
+    // nobody staked for 500 seconds, emission of this time is dropped
+    set_time(&mut context, START + 500).await;
+    send(&mut context, with_accounts(farm.deposit(&staker, 100), emitter.metas()), &staker.wallet).await.unwrap();
+
+    let farm_pool = get_farm_pool(&mut context, &farm).await;
+    assert_eq!(farm_pool.pending_emission, 0);
+    assert_eq!(farm_pool.reward_per_share_net, 0);
+    assert_eq!(get_token_amount(&mut context, &farm.pool_reward).await, 0);
+    assert_eq!(get_token_amount(&mut context, &emitter.vault).await, 1_000_000);
+
+    // staker earns emission only since the deposit
+    set_time(&mut context, START + 600).await;
+    send(&mut context, with_accounts(farm.withdraw(&staker, 100), emitter.metas()), &staker.wallet).await.unwrap();
+
+    assert_eq!(get_token_amount(&mut context, &staker.reward).await, 1_000);
+    assert_eq!(get_token_amount(&mut context, &staker.lp).await, 100);
+    assert_eq!(get_token_amount(&mut context, &farm.pool_reward).await, 0);
+    assert_eq!(get_token_amount(&mut context, &emitter.vault).await, 999_000);
+}
+
+#[tokio::test]
+async fn transfer_receipt_moves_stake_and_keeps_pending_rewards() {
+    let (mut program_test, program_id) = program_test();
+    let farm = Farm::new(&mut program_test, program_id);
+    let sender = farm.add_staker(&mut program_test, 0);
+    let receiver = farm.add_staker(&mut program_test, 0);
+
+    // receipt tokens are minted and frozen by farm authority
+    let receipt_mint = Pubkey::new_unique();
+    let sender_receipt = Pubkey::new_unique();
+    let receiver_receipt = Pubkey::new_unique();
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: Some(farm.authority).into(),
+        supply: 100,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: Some(farm.authority).into(),
+    }.pack_into_slice(&mut data);
+    program_test.add_account(receipt_mint, Account { lamports: rent_exempt(Mint::LEN), data, owner: spl_token::id(), ..Account::default() });
+    program_test.add_account(sender_receipt, token_account_in_state(&receipt_mint, &sender.wallet.pubkey(), 100, AccountState::Frozen));
+    program_test.add_account(receiver_receipt, token_account_in_state(&receipt_mint, &receiver.wallet.pubkey(), 0, AccountState::Frozen));
+
+    let sender_info = UserInfo {
+        wallet: sender.wallet.pubkey(),
+        farm_id: farm.farm_id,
+        deposit_balance: 100,
+        deposit_timestamp: START,
+        ..UserInfo::default()
+    };
+    program_test.add_account(farm.user_info_address(&sender), program_account(&program_id, sender_info.try_to_vec().unwrap()));
+    program_test.add_account(farm.pool_lp, token_account(&farm.lp_mint, &farm.authority, 100));
+    farm.set_pool(&mut program_test, |pool| {
+        pool.receipt_mint = receipt_mint;
+        pool.total_lp_deposited = 100;
+        pool.staker_count = 1;
+    });
+
+    let mut context = program_test.start_with_context().await;
+
+    // sender earned 100 seconds of rewards alone, 1000 per second
+    set_time(&mut context, START + 100).await;
+    let transfer = instruction::transfer_receipt(
+        &farm.farm_id,
+        &farm.authority,
+        &sender.wallet.pubkey(),
+        &farm.user_info_address(&sender),
+        &sender_receipt,
+        &receiver.wallet.pubkey(),
+        &farm.user_info_address(&receiver),
+        &receiver_receipt,
+        &receipt_mint,
+        &farm.pool_lp,
+        &farm.pool_reward,
+        &spl_token::id(),
+        40,
+        0,
+        &program_id,
+    );
+    send(&mut context, transfer, &sender.wallet).await.unwrap();
+
+    let (sender_info, _) = get_user_info(&mut context, &farm.user_info_address(&sender)).await;
+    let (receiver_info, _) = get_user_info(&mut context, &farm.user_info_address(&receiver)).await;
+    assert_eq!(sender_info.deposit_balance, 60);
+    assert_eq!(receiver_info.deposit_balance, 40);
+    assert_eq!(get_token_amount(&mut context, &sender_receipt).await, 60);
+    assert_eq!(get_token_amount(&mut context, &receiver_receipt).await, 40);
+    assert_eq!(get_token_state(&mut context, &sender_receipt).await, AccountState::Frozen);
+    assert_eq!(get_token_state(&mut context, &receiver_receipt).await, AccountState::Frozen);
+    assert_eq!(get_farm_pool(&mut context, &farm).await.staker_count, 2);
+
+    // pending rewards stay with the sender, both earn by their new balances afterwards
+    set_time(&mut context, START + 200).await;
+    let receipt_metas = |receipt: &Pubkey| vec![AccountMeta::new(receipt_mint, false), AccountMeta::new(*receipt, false)];
+    send(&mut context, with_accounts(farm.withdraw(&sender, 60), receipt_metas(&sender_receipt)), &sender.wallet).await.unwrap();
+    send(&mut context, with_accounts(farm.withdraw(&receiver, 40), receipt_metas(&receiver_receipt)), &receiver.wallet).await.unwrap();
+
+    assert_eq!(get_token_amount(&mut context, &sender.reward).await, 160_000);
+    assert_eq!(get_token_amount(&mut context, &receiver.reward).await, 40_000);
+    assert_eq!(get_token_amount(&mut context, &sender.lp).await, 60);
+    assert_eq!(get_token_amount(&mut context, &receiver.lp).await, 40);
+    assert_eq!(get_token_amount(&mut context, &sender_receipt).await, 0);
+    assert_eq!(get_token_amount(&mut context, &receiver_receipt).await, 0);
+}
+
+#[tokio::test]
+async fn deposit_creates_missing_reward_token_accounts() {
+    let (mut program_test, program_id) = program_test();
+    let mut farm = Farm::new(&mut program_test, program_id);
+    let mut staker = farm.add_staker(&mut program_test, 100);
+
+    // neither the staker nor the fee owner holds the reward token yet
+    staker.reward = associated_token_address(&staker.wallet.pubkey(), &farm.reward_mint);
+    farm.fee_reward_ata = associated_token_address(&farm.fee_owner, &farm.reward_mint);
+
+    let mut context = program_test.start_with_context().await;
+    set_time(&mut context, START + 10).await;
+    send(&mut context, farm.deposit(&staker, 100), &staker.wallet).await.unwrap();
+
+    assert_eq!(get_token_amount(&mut context, &staker.reward).await, 0);
+    assert_eq!(get_token_amount(&mut context, &farm.fee_reward_ata).await, 0);
+
+    set_time(&mut context, START + 110).await;
+    send(&mut context, farm.withdraw(&staker, 100), &staker.wallet).await.unwrap();
+
+    // rewards left when nobody staked are spread over the rest of the farm period
+    assert_eq!(get_token_amount(&mut context, &staker.reward).await, 1_000_000 * 100 / 990);
+}
+
+#[tokio::test]
+async fn deposit_to_position_checks_user_info_address() {
+    let (mut program_test, program_id) = program_test();
+    let farm = Farm::new(&mut program_test, program_id);
+    let staker = farm.add_staker(&mut program_test, 100);
+
+    // user info of the staker injected at an address which isn't the PDA of its position
+    let fake_user_info = Pubkey::new_unique();
+    let user_info = UserInfo {
+        wallet: staker.wallet.pubkey(),
+        farm_id: farm.farm_id,
+        ..UserInfo::default()
+    };
+    program_test.add_account(fake_user_info, program_account(&program_id, user_info.try_to_vec().unwrap()));
+
+    let mut context = program_test.start_with_context().await;
+    set_time(&mut context, START + 10).await;
+    let invalid_address = TransactionError::InstructionError(0, InstructionError::Custom(FarmError::InvalidProgramAddress as u32));
+
+    let mut deposit = farm.deposit(&staker, 10);
+    deposit.accounts[3].pubkey = fake_user_info;
+    assert_eq!(send(&mut context, deposit, &staker.wallet).await.unwrap_err().unwrap(), invalid_address);
+
+    // user info of position 0 can't be created for another position
+    let mut deposit = farm.deposit_to_position(&staker, 10, 1);
+    deposit.accounts[3].pubkey = farm.user_info_address(&staker);
+    assert_eq!(send(&mut context, deposit, &staker.wallet).await.unwrap_err().unwrap(), invalid_address);
+
+    send(&mut context, farm.deposit_to_position(&staker, 40, 1), &staker.wallet).await.unwrap();
+    send(&mut context, farm.deposit(&staker, 60), &staker.wallet).await.unwrap();
+
+    let (position, _) = get_user_info(&mut context, &farm.position_address(&staker, 1)).await;
+    assert_eq!(position.position_index, 1);
+    assert_eq!(position.deposit_balance, 40);
+    let (default_position, _) = get_user_info(&mut context, &farm.user_info_address(&staker)).await;
+    assert_eq!(default_position.position_index, 0);
+    assert_eq!(default_position.deposit_balance, 60);
+    assert_eq!(get_farm_pool(&mut context, &farm).await.total_lp_deposited, 100);
+}
+
+#[tokio::test]
+async fn execute_program_data_writes_audit_entry() {
+    let (mut program_test, program_id) = program_test();
+    let (program_data, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], &program_id);
+    let (timelock, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), TIMELOCK_SEED.as_bytes(), program_id.as_ref()], &program_id);
+    let (audit_log, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), AUDIT_LOG_SEED.as_bytes(), program_id.as_ref()], &program_id);
+    let super_owner = Pubkey::new_unique();
+
+    let farm_program = FarmProgram {
+        version: 1,
+        super_owner,
+        fee_owner: Pubkey::new_unique(),
+        harvest_fee_denominator: 1000,
+        audit_log: 1,
+        ..FarmProgram::default()
+    };
+    let change = ProgramDataChange {
+        is_queued: 1,
+        eta: START,
+        farm_fee: 5_000,
+        harvest_fee_numerator: 1,
+        harvest_fee_denominator: 100,
+        ..ProgramDataChange::default()
+    };
+    program_test.add_account(program_data, program_account(&program_id, farm_program.try_to_vec().unwrap()));
+    program_test.add_account(timelock, program_account(&program_id, change.try_to_vec().unwrap()));
+    program_test.add_account(audit_log, program_account(&program_id, vec![0; get_packed_len::<AuditLog>()]));
+
+    let mut context = program_test.start_with_context().await;
+    set_time(&mut context, START).await;
+    let execute = instruction::with_audit_log(instruction::execute_program_data(&program_data, &timelock, &program_id));
+    assert_eq!(execute.accounts.last().unwrap().pubkey, audit_log);
+    let blockhash = context.banks_client.get_new_latest_blockhash(&context.last_blockhash).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[execute], Some(&context.payer.pubkey()), &[&context.payer], blockhash);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let data = context.banks_client.get_account(audit_log).await.unwrap().unwrap().data;
+    let entries = instruction::decode_audit_log(&data).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, AUDIT_EXECUTE_PROGRAM_DATA);
+    assert_eq!(entries[0].get_kind_name(), "ExecuteProgramData");
+    assert_eq!(entries[0].signer, super_owner);
+    assert_eq!(entries[0].timestamp, START);
+    assert_ne!(entries[0].old_hash, entries[0].new_hash);
+}
+
+#[test]
+fn audit_log_follows_fixed_accounts_of_pay_farm_fee() {
+    let program_id = Pubkey::new_unique();
+    let price_accounts = instruction::FeePriceAccounts {
+        fee_token: Pubkey::new_unique(),
+        amm_id: Pubkey::new_unique(),
+        amm_token_a_account: Pubkey::new_unique(),
+        amm_token_b_account: Pubkey::new_unique(),
+    };
+    let pay_farm_fee = instruction::pay_farm_fee(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &spl_token::id(),
+        &Pubkey::new_unique(),
+        Some(&price_accounts),
+        1,
+        &program_id,
+    );
+    let pay_farm_fee = instruction::with_audit_log(pay_farm_fee);
+    let (audit_log, _) = instruction::find_audit_log_address(&program_id);
+    assert_eq!(pay_farm_fee.accounts[PAY_FARM_FEE_ACCOUNTS].pubkey, audit_log);
+    assert_eq!(pay_farm_fee.accounts[PAY_FARM_FEE_ACCOUNTS + 1].pubkey, price_accounts.fee_token);
+}